@@ -1,14 +1,26 @@
-use std::{env, fs, fs::File, io::Write, path::PathBuf};
+use std::{collections::HashSet, env, fs, fs::File, io::Write, path::PathBuf};
 
 use serde::{Deserialize, Serialize};
 use tera::{Context, Tera};
 
 struct Exec {
     output: PathBuf,
+    visitor_output: PathBuf,
+    syntax_kinds: PathBuf,
     tera: Tera,
     nodes: Vec<Node>,
 }
 
+/// Node kinds that deliberately do not have a generated node.
+const UNGENERATED_KINDS: [&str; 3] = [
+    // Only appears in ADTs; cast by `Function::cast_constructor` in ast/src/lib.rs
+    "Constructor",
+    // Never created by the parser; expressions are the `Expression` enum
+    "Expression",
+    // Tokens the parser skipped while recovering from a syntax error
+    "ErrorNode",
+];
+
 #[derive(Deserialize)]
 struct Node {
     #[serde(default = "default_template")]
@@ -41,19 +53,66 @@ struct Item {
     r#type: String,
     #[serde(default = "default_strategy")]
     strategy: String,
+    /// If visitors should walk this item, when it is a node
+    #[serde(default = "default_walk")]
+    walk: bool,
 }
 
 fn default_strategy() -> String {
     "single".to_string()
 }
 
+fn default_walk() -> bool {
+    true
+}
+
+/// Context of the visitor template.
+#[derive(Serialize)]
+struct VisitorCtx {
+    nodes: Vec<VisitNode>,
+}
+
+/// A node as seen by visitors.
+#[derive(Serialize)]
+struct VisitNode {
+    name: String,
+    /// Name of the node in method names, like 'generic_ident'
+    method: String,
+    /// If this is the root node, which has more fields than its CST node
+    root: bool,
+    /// Variants of the node, if it is an enum
+    variants: Vec<VisitVariant>,
+    children: Vec<VisitChild>,
+}
+
+#[derive(Serialize)]
+struct VisitVariant {
+    name: String,
+    method: String,
+}
+
+/// A child of a node, walked by visitors.
+#[derive(Serialize)]
+struct VisitChild {
+    accessor: String,
+    method: String,
+    /// One of 'single', 'opt' or 'list'
+    shape: &'static str,
+}
+
 fn main() {
     let path = env::current_dir().unwrap();
     let parent = path.parent().unwrap();
     let mut output = PathBuf::from(parent);
     output.push("ast");
     output.push("src");
+    let mut visitor_output = output.clone();
     output.push("generated_nodes.rs");
+    visitor_output.push("generated_visitor.rs");
+    let mut syntax_kinds = PathBuf::from(parent);
+    syntax_kinds.push("syntax");
+    syntax_kinds.push("src");
+    syntax_kinds.push("kind.rs");
 
     let tera = match Tera::new("templates/*.rs") {
         Ok(t) => t,
@@ -69,15 +128,19 @@ fn main() {
 
     generate(Exec {
         output,
+        visitor_output,
+        syntax_kinds,
         tera,
         nodes,
     })
 }
 
 fn generate(e: Exec) {
-    let mut out = String::with_capacity(10_000);
+    check_kinds(&e.nodes, &fs::read_to_string(e.syntax_kinds).unwrap());
 
-    for mut node in e.nodes {
+    let mut out = String::with_capacity(10_000);
+    let mut nodes = e.nodes;
+    for node in &mut nodes {
         out.push('\n');
         process_context(&mut node.context);
         let ctx = Context::from_serialize(&node.context).unwrap();
@@ -90,6 +153,140 @@ fn generate(e: Exec) {
         .unwrap()
         .write_all(out.as_bytes())
         .unwrap();
+
+    let ctx = Context::from_serialize(visitor_context(&nodes)).unwrap();
+    let visitor = e.tera.render("visitor.rs", &ctx).unwrap();
+    File::create(e.visitor_output)
+        .unwrap()
+        .write_all(visitor.as_bytes())
+        .unwrap();
+}
+
+/// Ensures that every node kind in `SyntaxKind` (given as the source of its
+/// declaration) has a generated node, either of its own or as the wrapper
+/// of a nested item, unless it is in `UNGENERATED_KINDS`.
+fn check_kinds(nodes: &[Node], syntax_kinds: &str) {
+    let mut generated = HashSet::new();
+    for node in nodes {
+        generated.extend(node.context.kind.split(" | SyntaxKind::"));
+        if node.context.kind.is_empty() {
+            generated.insert(&node.context.name[..]);
+        }
+        for item in &node.context.items {
+            if node.template == "expression.rs" || item.strategy.starts_with("nested_") {
+                let kind = if item.kind.is_empty() {
+                    &item.r#type
+                } else {
+                    &item.kind
+                };
+                generated.insert(&kind[..]);
+            }
+        }
+    }
+
+    let missing = syntax_kinds
+        .lines()
+        .map(str::trim)
+        .skip_while(|line| !line.starts_with("pub enum SyntaxKind"))
+        .skip(1)
+        .take_while(|line| !line.starts_with("__TokenStart"))
+        .filter(|line| !line.is_empty() && !line.starts_with("//"))
+        .map(|line| line.trim_end_matches(','))
+        .filter(|kind| !generated.contains(kind) && !UNGENERATED_KINDS.contains(kind))
+        .collect::<Vec<_>>();
+    if !missing.is_empty() {
+        println!(
+            "Node kinds without a generated node: {}",
+            missing.join(", ")
+        );
+        std::process::exit(1);
+    }
+}
+
+/// Creates the context of the visitor template from all nodes,
+/// which must already be processed.
+fn visitor_context(nodes: &[Node]) -> VisitorCtx {
+    let names = nodes
+        .iter()
+        .map(|node| match &node.template[..] {
+            "expression.rs" => "Expression",
+            _ => &node.context.name[..],
+        })
+        .filter(|name| !name.is_empty())
+        .collect::<HashSet<_>>();
+
+    let nodes = nodes.iter().filter_map(|node| {
+        let ctx = &node.context;
+        Some(match &node.template[..] {
+            "expression.rs" => VisitNode {
+                name: "Expression".to_string(),
+                method: "expression".to_string(),
+                root: false,
+                variants: ctx
+                    .items
+                    .iter()
+                    .map(|item| VisitVariant {
+                        name: item.name.clone(),
+                        method: snake_case(&item.r#type),
+                    })
+                    .collect(),
+                children: vec![],
+            },
+
+            _ if ctx.name.is_empty() => return None,
+
+            template => VisitNode {
+                name: ctx.name.clone(),
+                method: snake_case(&ctx.name),
+                root: template == "root.rs",
+                variants: vec![],
+                children: ctx
+                    .items
+                    .iter()
+                    .filter(|item| item.walk)
+                    .filter_map(|item| {
+                        let (shape, ty) = child_shape(&item.r#type);
+                        if names.contains(ty) {
+                            Some(VisitChild {
+                                accessor: item.name.clone(),
+                                method: snake_case(ty),
+                                shape,
+                            })
+                        } else {
+                            None
+                        }
+                    })
+                    .collect(),
+            },
+        })
+    });
+
+    VisitorCtx {
+        nodes: nodes.collect(),
+    }
+}
+
+/// Returns the shape of an item from the return type of its accessor,
+/// together with the type of the node(s) it returns.
+fn child_shape(ty: &str) -> (&'static str, &str) {
+    if let Some(inner) = ty.strip_prefix("Option<") {
+        ("opt", inner.trim_end_matches('>'))
+    } else if let Some(inner) = ty.strip_prefix("impl Iterator<Item = ") {
+        ("list", inner.trim_end_matches("> + '_"))
+    } else {
+        ("single", ty)
+    }
+}
+
+fn snake_case(name: &str) -> String {
+    let mut out = String::with_capacity(name.len() + 4);
+    for (i, c) in name.char_indices() {
+        if c.is_uppercase() && i != 0 {
+            out.push('_');
+        }
+        out.push(c.to_ascii_lowercase());
+    }
+    out
 }
 
 fn process_context(ctx: &mut Ctx) {
@@ -104,8 +301,14 @@ fn process_context(ctx: &mut Ctx) {
 
         let strat = item.strategy.clone();
         item.strategy = match &item.strategy[..] {
-            "single" => format!("children().find_map({}::cast).unwrap()", item.r#type),
-            "nested_single" => format!("children().find(|i| i.kind() == SyntaxKind::{}).unwrap().children().find_map({}::cast).unwrap()", item.kind, item.r#type),
+            "single" => format!(
+                "children().find_map({}::cast).unwrap_or_else(|| {}::missing(&self.cst))",
+                item.r#type, item.r#type
+            ),
+            "nested_single" => format!(
+                "children().find(|i| i.kind() == SyntaxKind::{}).and_then(|i| i.children().find_map({}::cast)).unwrap_or_else(|| {}::missing(&self.cst))",
+                item.kind, item.r#type, item.r#type
+            ),
 
             "opt_single" => format!("children().find_map({}::cast)", item.r#type),
             "nested_opt_single" => format!(
@@ -115,23 +318,23 @@ fn process_context(ctx: &mut Ctx) {
 
             "list" => format!("children().filter_map({}::cast)", item.r#type),
             "nested_list" => format!(
-                "children().filter(|i| i.kind() == SyntaxKind::{}).map(|i| i.children().find_map({}::cast).unwrap())",
+                "children().filter(|i| i.kind() == SyntaxKind::{}).filter_map(|i| i.children().find_map({}::cast))",
                 item.kind, item.r#type
             ),
 
             "token" => "children_with_tokens()\
             .find(|c| c.as_token().map(Token::kind).as_ref().map(SyntaxKind::is_token) == Some(true))\
-            .unwrap().as_token().unwrap().kind()".to_string(),
-            "nested_token" => format!("children().find(|i| i.kind() == SyntaxKind::{}).unwrap().children_with_tokens()\
-            .find(|c| c.as_token().map(Token::kind).as_ref().map(SyntaxKind::is_token) == Some(true))\
-            .unwrap().as_token().unwrap().kind()", item.kind),
+            .map_or(SyntaxKind::ErrorNode, |c| c.as_token().unwrap().kind())".to_string(),
+            "nested_token" => format!("children().find(|i| i.kind() == SyntaxKind::{}).and_then(|i| i.children_with_tokens()\
+            .find(|c| c.as_token().map(Token::kind).as_ref().map(SyntaxKind::is_token) == Some(true)))\
+            .map_or(SyntaxKind::ErrorNode, |c| c.as_token().unwrap().kind())", item.kind),
             "nested_token_list" => format!("children().filter(|i| i.kind() == SyntaxKind::{}).map(|c| c.children_with_tokens()\
             .find(|c| c.as_token().map(Token::kind).as_ref().map(SyntaxKind::is_token) == Some(true)))\
             .flatten().map(|c| c.as_token().unwrap().kind())", item.kind),
 
             "ident" => "children_with_tokens()\
             .find(|c| c.as_token().map(Token::kind) == Some(SyntaxKind::Identifier))\
-            .unwrap().as_token().unwrap().text().clone()".to_string(),
+            .map(|c| c.as_token().unwrap().text().clone()).unwrap_or_default()".to_string(),
             "ident_list" => "children_with_tokens()\
             .filter(|c| c.as_token().map(Token::kind) == Some(SyntaxKind::Identifier))\
             .map(|c| c.as_token().unwrap().text().clone())".to_string(),
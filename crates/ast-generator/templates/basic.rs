@@ -13,6 +13,14 @@ impl {{ name }} {
         }
     }
 
+    /// A placeholder for this node when it is missing from `parent`
+    /// due to a syntax error; all its children are missing as well.
+    pub fn missing(parent: &CSTNode) -> Self {
+        Self {
+            cst: CSTNode::missing(parent),
+        }
+    }
+
     pub fn cst(&self) -> CSTNode {
         self.cst.clone()
     }
@@ -13,6 +13,12 @@ impl Expression {
         None
     }
 
+    /// A placeholder for an expression missing from `parent`
+    /// due to a syntax error; behaves like an empty block.
+    pub fn missing(parent: &CSTNode) -> Self {
+        Self::Block(Block::missing(parent))
+    }
+
     pub fn cst(&self) -> CSTNode {
         match self {
             {% for item in items %}Self::{{ item.name }}(inner) => inner.cst(),{% endfor %}
@@ -0,0 +1,86 @@
+//! This file is autogenerated by ast-generator. Do not modify!
+
+use crate::{CSTNode, {% for node in nodes %}{{ node.name }}, {% endfor %}};
+
+/// A traversal of the AST. Every method visits a node of the given kind
+/// and walks its children by default, using the `walk_*` function of the
+/// node; implementations override the methods of the nodes they are
+/// interested in and call the `walk_*` function to keep descending.
+/// Children are walked in the order of their accessors, which is not
+/// always the order they appear in the source.
+pub trait Visitor {
+    {% for node in nodes %}
+    fn visit_{{ node.method }}(&mut self, node: &{{ node.name }}) {
+        walk_{{ node.method }}(self, node)
+    }{% endfor %}
+}
+{% for node in nodes %}
+{% if node.variants %}
+pub fn walk_{{ node.method }}<V: Visitor + ?Sized>(visitor: &mut V, node: &{{ node.name }}) {
+    match node {
+        {% for variant in node.variants %}{{ node.name }}::{{ variant.name }}(inner) => visitor.visit_{{ variant.method }}(inner),{% endfor %}
+    }
+}
+{% elif node.children %}
+pub fn walk_{{ node.method }}<V: Visitor + ?Sized>(visitor: &mut V, node: &{{ node.name }}) {
+    {% for child in node.children %}{% if child.shape == "single" %}
+    visitor.visit_{{ child.method }}(&node.{{ child.accessor }}());{% elif child.shape == "opt" %}
+    if let Some(child) = node.{{ child.accessor }}() {
+        visitor.visit_{{ child.method }}(&child);
+    }{% else %}
+    for child in node.{{ child.accessor }}() {
+        visitor.visit_{{ child.method }}(&child);
+    }{% endif %}{% endfor %}
+}
+{% else %}
+pub fn walk_{{ node.method }}<V: Visitor + ?Sized>(_visitor: &mut V, _node: &{{ node.name }}) {}
+{% endif %}{% endfor %}
+
+/// A traversal of the AST that rewrites nodes. Every method returns the
+/// node it was given, or a replacement for it; the parents of a replaced node
+/// are rebuilt around it. By default, methods rewrite the children of the node,
+/// using the `rewrite_*_children` function of the node.
+/// Nodes missing due to syntax errors cannot be replaced, their rewrites are discarded.
+pub trait Rewriter {
+    {% for node in nodes %}
+    fn rewrite_{{ node.method }}(&mut self, node: {{ node.name }}) -> {{ node.name }} {
+        rewrite_{{ node.method }}_children(self, node)
+    }{% endfor %}
+}
+{% for node in nodes %}
+{% if node.variants %}
+pub fn rewrite_{{ node.method }}_children<R: Rewriter + ?Sized>(rewriter: &mut R, node: {{ node.name }}) -> {{ node.name }} {
+    match node {
+        {% for variant in node.variants %}{{ node.name }}::{{ variant.name }}(inner) => {{ node.name }}::{{ variant.name }}(rewriter.rewrite_{{ variant.method }}(inner)),{% endfor %}
+    }
+}
+{% elif node.children %}
+pub fn rewrite_{{ node.method }}_children<R: Rewriter + ?Sized>(rewriter: &mut R, node: {{ node.name }}) -> {{ node.name }} {
+    let mut replacements = Vec::new();{% for child in node.children %}{% if child.shape == "single" %}
+    let child = node.{{ child.accessor }}();
+    record(&mut replacements, child.cst(), rewriter.rewrite_{{ child.method }}(child).cst());{% elif child.shape == "opt" %}
+    if let Some(child) = node.{{ child.accessor }}() {
+        record(&mut replacements, child.cst(), rewriter.rewrite_{{ child.method }}(child).cst());
+    }{% else %}
+    for child in node.{{ child.accessor }}() {
+        record(&mut replacements, child.cst(), rewriter.rewrite_{{ child.method }}(child).cst());
+    }{% endif %}{% endfor %}
+    {% if node.root %}{{ node.name }} {
+        cst: node.cst.replace(&replacements),
+        ..node
+    }{% else %}{{ node.name }} {
+        cst: node.cst.replace(&replacements),
+    }{% endif %}
+}
+{% else %}
+pub fn rewrite_{{ node.method }}_children<R: Rewriter + ?Sized>(_rewriter: &mut R, node: {{ node.name }}) -> {{ node.name }} {
+    node
+}
+{% endif %}{% endfor %}
+
+/// Records that `old` was rewritten to `new`, unless it was returned unchanged.
+fn record(replacements: &mut Vec<(CSTNode, CSTNode)>, old: CSTNode, new: CSTNode) {
+    if old != new {
+        replacements.push((old, new));
+    }
+}
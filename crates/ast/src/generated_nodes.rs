@@ -22,6 +22,14 @@ impl GenericIdent {
         }
     }
 
+    /// A placeholder for this node when it is missing from `parent`
+    /// due to a syntax error; all its children are missing as well.
+    pub fn missing(parent: &CSTNode) -> Self {
+        Self {
+            cst: CSTNode::missing(parent),
+        }
+    }
+
     pub fn cst(&self) -> CSTNode {
         self.cst.clone()
     }
@@ -30,11 +38,8 @@ impl GenericIdent {
         self.cst
             .children_with_tokens()
             .find(|c| c.as_token().map(Token::kind) == Some(SyntaxKind::Identifier))
-            .unwrap()
-            .as_token()
-            .unwrap()
-            .text()
-            .clone()
+            .map(|c| c.as_token().unwrap().text().clone())
+            .unwrap_or_default()
     }
     pub fn type_args(&self) -> impl Iterator<Item = Type> + '_ {
         self.cst.children().filter_map(Type::cast)
@@ -56,6 +61,14 @@ impl DeclName {
         }
     }
 
+    /// A placeholder for this node when it is missing from `parent`
+    /// due to a syntax error; all its children are missing as well.
+    pub fn missing(parent: &CSTNode) -> Self {
+        Self {
+            cst: CSTNode::missing(parent),
+        }
+    }
+
     pub fn cst(&self) -> CSTNode {
         self.cst.clone()
     }
@@ -64,11 +77,8 @@ impl DeclName {
         self.cst
             .children_with_tokens()
             .find(|c| c.as_token().map(Token::kind) == Some(SyntaxKind::Identifier))
-            .unwrap()
-            .as_token()
-            .unwrap()
-            .text()
-            .clone()
+            .map(|c| c.as_token().unwrap().text().clone())
+            .unwrap_or_default()
     }
     pub fn type_parameters(&self) -> impl Iterator<Item = TypeParameter> + '_ {
         self.cst.children().filter_map(TypeParameter::cast)
@@ -90,6 +100,14 @@ impl TypeParameter {
         }
     }
 
+    /// A placeholder for this node when it is missing from `parent`
+    /// due to a syntax error; all its children are missing as well.
+    pub fn missing(parent: &CSTNode) -> Self {
+        Self {
+            cst: CSTNode::missing(parent),
+        }
+    }
+
     pub fn cst(&self) -> CSTNode {
         self.cst.clone()
     }
@@ -98,11 +116,8 @@ impl TypeParameter {
         self.cst
             .children_with_tokens()
             .find(|c| c.as_token().map(Token::kind) == Some(SyntaxKind::Identifier))
-            .unwrap()
-            .as_token()
-            .unwrap()
-            .text()
-            .clone()
+            .map(|c| c.as_token().unwrap().text().clone())
+            .unwrap_or_default()
     }
     pub fn bound(&self) -> Option<Type> {
         self.cst.children().find_map(Type::cast)
@@ -124,6 +139,14 @@ impl Type {
         }
     }
 
+    /// A placeholder for this node when it is missing from `parent`
+    /// due to a syntax error; all its children are missing as well.
+    pub fn missing(parent: &CSTNode) -> Self {
+        Self {
+            cst: CSTNode::missing(parent),
+        }
+    }
+
     pub fn cst(&self) -> CSTNode {
         self.cst.clone()
     }
@@ -144,6 +167,14 @@ impl Literal {
         }
     }
 
+    /// A placeholder for this node when it is missing from `parent`
+    /// due to a syntax error; all its children are missing as well.
+    pub fn missing(parent: &CSTNode) -> Self {
+        Self {
+            cst: CSTNode::missing(parent),
+        }
+    }
+
     pub fn cst(&self) -> CSTNode {
         self.cst.clone()
     }
@@ -193,6 +224,14 @@ impl Adt {
         }
     }
 
+    /// A placeholder for this node when it is missing from `parent`
+    /// due to a syntax error; all its children are missing as well.
+    pub fn missing(parent: &CSTNode) -> Self {
+        Self {
+            cst: CSTNode::missing(parent),
+        }
+    }
+
     pub fn cst(&self) -> CSTNode {
         self.cst.clone()
     }
@@ -211,7 +250,10 @@ impl Adt {
             .unwrap_or(SyntaxKind::EnumCase)
     }
     pub fn name(&self) -> DeclName {
-        self.cst.children().find_map(DeclName::cast).unwrap()
+        self.cst
+            .children()
+            .find_map(DeclName::cast)
+            .unwrap_or_else(|| DeclName::missing(&self.cst))
     }
     pub fn modifiers(&self) -> impl Iterator<Item = SyntaxKind> + '_ {
         self.cst
@@ -260,6 +302,14 @@ impl Function {
         }
     }
 
+    /// A placeholder for this node when it is missing from `parent`
+    /// due to a syntax error; all its children are missing as well.
+    pub fn missing(parent: &CSTNode) -> Self {
+        Self {
+            cst: CSTNode::missing(parent),
+        }
+    }
+
     pub fn cst(&self) -> CSTNode {
         self.cst.clone()
     }
@@ -268,7 +318,7 @@ impl Function {
         self.cst
             .children()
             .find_map(FunctionSignature::cast)
-            .unwrap()
+            .unwrap_or_else(|| FunctionSignature::missing(&self.cst))
     }
     pub fn modifiers(&self) -> impl Iterator<Item = SyntaxKind> + '_ {
         self.cst
@@ -310,19 +360,30 @@ impl FunctionSignature {
         }
     }
 
+    /// A placeholder for this node when it is missing from `parent`
+    /// due to a syntax error; all its children are missing as well.
+    pub fn missing(parent: &CSTNode) -> Self {
+        Self {
+            cst: CSTNode::missing(parent),
+        }
+    }
+
     pub fn cst(&self) -> CSTNode {
         self.cst.clone()
     }
 
     pub fn name(&self) -> DeclName {
-        self.cst.children().find_map(DeclName::cast).unwrap()
-    }
-    pub fn ret_type(&self) -> Option<Type> {
-        self.cst.children().find_map(Type::cast)
+        self.cst
+            .children()
+            .find_map(DeclName::cast)
+            .unwrap_or_else(|| DeclName::missing(&self.cst))
     }
     pub fn parameters(&self) -> impl Iterator<Item = Parameter> + '_ {
         self.cst.children().filter_map(Parameter::cast)
     }
+    pub fn ret_type(&self) -> Option<Type> {
+        self.cst.children().find_map(Type::cast)
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -340,6 +401,14 @@ impl Parameter {
         }
     }
 
+    /// A placeholder for this node when it is missing from `parent`
+    /// due to a syntax error; all its children are missing as well.
+    pub fn missing(parent: &CSTNode) -> Self {
+        Self {
+            cst: CSTNode::missing(parent),
+        }
+    }
+
     pub fn cst(&self) -> CSTNode {
         self.cst.clone()
     }
@@ -348,18 +417,25 @@ impl Parameter {
         self.cst
             .children_with_tokens()
             .find(|c| c.as_token().map(Token::kind) == Some(SyntaxKind::Identifier))
-            .unwrap()
-            .as_token()
-            .unwrap()
-            .text()
-            .clone()
+            .map(|c| c.as_token().unwrap().text().clone())
+            .unwrap_or_default()
     }
     pub fn _type(&self) -> Type {
-        self.cst.children().find_map(Type::cast).unwrap()
+        self.cst
+            .children()
+            .find_map(Type::cast)
+            .unwrap_or_else(|| Type::missing(&self.cst))
     }
     pub fn maybe_type(&self) -> Option<Type> {
         self.cst.children().find_map(Type::cast)
     }
+    pub fn default(&self) -> Option<Expression> {
+        self.cst
+            .children()
+            .find(|i| i.kind() == SyntaxKind::Initializer)
+            .map(|i| i.children().find_map(Expression::cast))
+            .flatten()
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -377,9 +453,57 @@ impl Import {
         }
     }
 
+    /// A placeholder for this node when it is missing from `parent`
+    /// due to a syntax error; all its children are missing as well.
+    pub fn missing(parent: &CSTNode) -> Self {
+        Self {
+            cst: CSTNode::missing(parent),
+        }
+    }
+
     pub fn cst(&self) -> CSTNode {
         self.cst.clone()
     }
+
+    pub fn symbols(&self) -> impl Iterator<Item = ImportSymbol> + '_ {
+        self.cst.children().filter_map(ImportSymbol::cast)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct ImportSymbol {
+    pub cst: CSTNode,
+}
+impl ImportSymbol {
+    #[allow(unused)]
+    pub fn cast(node: CSTNode) -> Option<Self> {
+        if let SyntaxKind::ImportSymbol = node.kind() {
+            Some(Self { cst: node })
+        } else {
+            None
+        }
+    }
+
+    /// A placeholder for this node when it is missing from `parent`
+    /// due to a syntax error; all its children are missing as well.
+    pub fn missing(parent: &CSTNode) -> Self {
+        Self {
+            cst: CSTNode::missing(parent),
+        }
+    }
+
+    pub fn cst(&self) -> CSTNode {
+        self.cst.clone()
+    }
+
+    pub fn name(&self) -> SmolStr {
+        self.cst
+            .children_with_tokens()
+            .find(|c| c.as_token().map(Token::kind) == Some(SyntaxKind::Identifier))
+            .map(|c| c.as_token().unwrap().text().clone())
+            .unwrap_or_default()
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -397,27 +521,31 @@ impl IfaceImpl {
         }
     }
 
+    /// A placeholder for this node when it is missing from `parent`
+    /// due to a syntax error; all its children are missing as well.
+    pub fn missing(parent: &CSTNode) -> Self {
+        Self {
+            cst: CSTNode::missing(parent),
+        }
+    }
+
     pub fn cst(&self) -> CSTNode {
         self.cst.clone()
     }
 
-    pub fn implementor(&self) -> Type {
+    pub fn iface(&self) -> Option<Type> {
         self.cst
             .children()
-            .find(|i| i.kind() == SyntaxKind::Implementor)
-            .unwrap()
-            .children()
-            .find_map(Type::cast)
-            .unwrap()
+            .find(|i| i.kind() == SyntaxKind::Implementing)
+            .map(|i| i.children().find_map(Type::cast))
+            .flatten()
     }
-    pub fn iface(&self) -> Type {
+    pub fn implementor(&self) -> Type {
         self.cst
             .children()
-            .find(|i| i.kind() == SyntaxKind::Implementing)
-            .unwrap()
-            .children()
-            .find_map(Type::cast)
-            .unwrap()
+            .find(|i| i.kind() == SyntaxKind::Implementor)
+            .and_then(|i| i.children().find_map(Type::cast))
+            .unwrap_or_else(|| Type::missing(&self.cst))
     }
     pub fn methods(&self) -> impl Iterator<Item = Function> + '_ {
         self.cst.children().filter_map(Function::cast)
@@ -502,6 +630,12 @@ impl Expression {
         None
     }
 
+    /// A placeholder for an expression missing from `parent`
+    /// due to a syntax error; behaves like an empty block.
+    pub fn missing(parent: &CSTNode) -> Self {
+        Self::Block(Block::missing(parent))
+    }
+
     pub fn cst(&self) -> CSTNode {
         match self {
             Self::Binary(inner) => inner.cst(),
@@ -540,6 +674,14 @@ impl Variable {
         }
     }
 
+    /// A placeholder for this node when it is missing from `parent`
+    /// due to a syntax error; all its children are missing as well.
+    pub fn missing(parent: &CSTNode) -> Self {
+        Self {
+            cst: CSTNode::missing(parent),
+        }
+    }
+
     pub fn cst(&self) -> CSTNode {
         self.cst.clone()
     }
@@ -554,20 +696,14 @@ impl Variable {
                     .map(SyntaxKind::is_token)
                     == Some(true)
             })
-            .unwrap()
-            .as_token()
-            .unwrap()
-            .kind()
+            .map_or(SyntaxKind::ErrorNode, |c| c.as_token().unwrap().kind())
     }
     pub fn name(&self) -> SmolStr {
         self.cst
             .children_with_tokens()
             .find(|c| c.as_token().map(Token::kind) == Some(SyntaxKind::Identifier))
-            .unwrap()
-            .as_token()
-            .unwrap()
-            .text()
-            .clone()
+            .map(|c| c.as_token().unwrap().text().clone())
+            .unwrap_or_default()
     }
     pub fn _type(&self) -> Option<Type> {
         self.cst.children().find_map(Type::cast)
@@ -576,10 +712,8 @@ impl Variable {
         self.cst
             .children()
             .find(|i| i.kind() == SyntaxKind::Initializer)
-            .unwrap()
-            .children()
-            .find_map(Expression::cast)
-            .unwrap()
+            .and_then(|i| i.children().find_map(Expression::cast))
+            .unwrap_or_else(|| Expression::missing(&self.cst))
     }
     pub fn maybe_initializer(&self) -> Option<Expression> {
         self.cst
@@ -621,12 +755,23 @@ impl Grouping {
         }
     }
 
+    /// A placeholder for this node when it is missing from `parent`
+    /// due to a syntax error; all its children are missing as well.
+    pub fn missing(parent: &CSTNode) -> Self {
+        Self {
+            cst: CSTNode::missing(parent),
+        }
+    }
+
     pub fn cst(&self) -> CSTNode {
         self.cst.clone()
     }
 
     pub fn inner(&self) -> Expression {
-        self.cst.children().find_map(Expression::cast).unwrap()
+        self.cst
+            .children()
+            .find_map(Expression::cast)
+            .unwrap_or_else(|| Expression::missing(&self.cst))
     }
 }
 
@@ -645,6 +790,14 @@ impl Binary {
         }
     }
 
+    /// A placeholder for this node when it is missing from `parent`
+    /// due to a syntax error; all its children are missing as well.
+    pub fn missing(parent: &CSTNode) -> Self {
+        Self {
+            cst: CSTNode::missing(parent),
+        }
+    }
+
     pub fn cst(&self) -> CSTNode {
         self.cst.clone()
     }
@@ -653,35 +806,30 @@ impl Binary {
         self.cst
             .children()
             .find(|i| i.kind() == SyntaxKind::Operator)
-            .unwrap()
-            .children_with_tokens()
-            .find(|c| {
-                c.as_token()
-                    .map(Token::kind)
-                    .as_ref()
-                    .map(SyntaxKind::is_token)
-                    == Some(true)
+            .and_then(|i| {
+                i.children_with_tokens().find(|c| {
+                    c.as_token()
+                        .map(Token::kind)
+                        .as_ref()
+                        .map(SyntaxKind::is_token)
+                        == Some(true)
+                })
             })
-            .unwrap()
-            .as_token()
-            .unwrap()
-            .kind()
+            .map_or(SyntaxKind::ErrorNode, |c| c.as_token().unwrap().kind())
     }
     pub fn left(&self) -> Expression {
         self.cst
             .children()
             .next()
-            .map(Expression::cast)
-            .unwrap()
-            .unwrap()
+            .and_then(Expression::cast)
+            .unwrap_or_else(|| Expression::missing(&self.cst))
     }
     pub fn right(&self) -> Expression {
         self.cst
             .children()
             .nth(2)
-            .map(Expression::cast)
-            .unwrap()
-            .unwrap()
+            .and_then(Expression::cast)
+            .unwrap_or_else(|| Expression::missing(&self.cst))
     }
 }
 
@@ -700,6 +848,14 @@ impl Prefix {
         }
     }
 
+    /// A placeholder for this node when it is missing from `parent`
+    /// due to a syntax error; all its children are missing as well.
+    pub fn missing(parent: &CSTNode) -> Self {
+        Self {
+            cst: CSTNode::missing(parent),
+        }
+    }
+
     pub fn cst(&self) -> CSTNode {
         self.cst.clone()
     }
@@ -708,27 +864,23 @@ impl Prefix {
         self.cst
             .children()
             .find(|i| i.kind() == SyntaxKind::Operator)
-            .unwrap()
-            .children_with_tokens()
-            .find(|c| {
-                c.as_token()
-                    .map(Token::kind)
-                    .as_ref()
-                    .map(SyntaxKind::is_token)
-                    == Some(true)
+            .and_then(|i| {
+                i.children_with_tokens().find(|c| {
+                    c.as_token()
+                        .map(Token::kind)
+                        .as_ref()
+                        .map(SyntaxKind::is_token)
+                        == Some(true)
+                })
             })
-            .unwrap()
-            .as_token()
-            .unwrap()
-            .kind()
+            .map_or(SyntaxKind::ErrorNode, |c| c.as_token().unwrap().kind())
     }
     pub fn right(&self) -> Expression {
         self.cst
             .children()
             .nth(1)
-            .map(Expression::cast)
-            .unwrap()
-            .unwrap()
+            .and_then(Expression::cast)
+            .unwrap_or_else(|| Expression::missing(&self.cst))
     }
 }
 
@@ -747,6 +899,14 @@ impl Call {
         }
     }
 
+    /// A placeholder for this node when it is missing from `parent`
+    /// due to a syntax error; all its children are missing as well.
+    pub fn missing(parent: &CSTNode) -> Self {
+        Self {
+            cst: CSTNode::missing(parent),
+        }
+    }
+
     pub fn cst(&self) -> CSTNode {
         self.cst.clone()
     }
@@ -755,16 +915,14 @@ impl Call {
         self.cst
             .children()
             .find(|i| i.kind() == SyntaxKind::Callee)
-            .unwrap()
-            .children()
-            .find_map(Expression::cast)
-            .unwrap()
+            .and_then(|i| i.children().find_map(Expression::cast))
+            .unwrap_or_else(|| Expression::missing(&self.cst))
     }
     pub fn args(&self) -> impl Iterator<Item = Expression> + '_ {
         self.cst
             .children()
             .filter(|i| i.kind() == SyntaxKind::CallArgument)
-            .map(|i| i.children().find_map(Expression::cast).unwrap())
+            .filter_map(|i| i.children().find_map(Expression::cast))
     }
 }
 
@@ -783,6 +941,14 @@ impl Get {
         }
     }
 
+    /// A placeholder for this node when it is missing from `parent`
+    /// due to a syntax error; all its children are missing as well.
+    pub fn missing(parent: &CSTNode) -> Self {
+        Self {
+            cst: CSTNode::missing(parent),
+        }
+    }
+
     pub fn cst(&self) -> CSTNode {
         self.cst.clone()
     }
@@ -791,13 +957,14 @@ impl Get {
         self.cst
             .children()
             .find(|i| i.kind() == SyntaxKind::Callee)
-            .unwrap()
-            .children()
-            .find_map(Expression::cast)
-            .unwrap()
+            .and_then(|i| i.children().find_map(Expression::cast))
+            .unwrap_or_else(|| Expression::missing(&self.cst))
     }
     pub fn property(&self) -> GenericIdent {
-        self.cst.children().find_map(GenericIdent::cast).unwrap()
+        self.cst
+            .children()
+            .find_map(GenericIdent::cast)
+            .unwrap_or_else(|| GenericIdent::missing(&self.cst))
     }
 }
 
@@ -816,6 +983,14 @@ impl GetStatic {
         }
     }
 
+    /// A placeholder for this node when it is missing from `parent`
+    /// due to a syntax error; all its children are missing as well.
+    pub fn missing(parent: &CSTNode) -> Self {
+        Self {
+            cst: CSTNode::missing(parent),
+        }
+    }
+
     pub fn cst(&self) -> CSTNode {
         self.cst.clone()
     }
@@ -824,20 +999,15 @@ impl GetStatic {
         self.cst
             .children()
             .find(|i| i.kind() == SyntaxKind::Callee)
-            .unwrap()
-            .children()
-            .find_map(Expression::cast)
-            .unwrap()
+            .and_then(|i| i.children().find_map(Expression::cast))
+            .unwrap_or_else(|| Expression::missing(&self.cst))
     }
     pub fn property(&self) -> SmolStr {
         self.cst
             .children_with_tokens()
             .find(|c| c.as_token().map(Token::kind) == Some(SyntaxKind::Identifier))
-            .unwrap()
-            .as_token()
-            .unwrap()
-            .text()
-            .clone()
+            .map(|c| c.as_token().unwrap().text().clone())
+            .unwrap_or_default()
     }
 }
 
@@ -856,6 +1026,14 @@ impl Block {
         }
     }
 
+    /// A placeholder for this node when it is missing from `parent`
+    /// due to a syntax error; all its children are missing as well.
+    pub fn missing(parent: &CSTNode) -> Self {
+        Self {
+            cst: CSTNode::missing(parent),
+        }
+    }
+
     pub fn cst(&self) -> CSTNode {
         self.cst.clone()
     }
@@ -880,6 +1058,14 @@ impl IfExpr {
         }
     }
 
+    /// A placeholder for this node when it is missing from `parent`
+    /// due to a syntax error; all its children are missing as well.
+    pub fn missing(parent: &CSTNode) -> Self {
+        Self {
+            cst: CSTNode::missing(parent),
+        }
+    }
+
     pub fn cst(&self) -> CSTNode {
         self.cst.clone()
     }
@@ -888,19 +1074,15 @@ impl IfExpr {
         self.cst
             .children()
             .find(|i| i.kind() == SyntaxKind::ExprCondition)
-            .unwrap()
-            .children()
-            .find_map(Expression::cast)
-            .unwrap()
+            .and_then(|i| i.children().find_map(Expression::cast))
+            .unwrap_or_else(|| Expression::missing(&self.cst))
     }
     pub fn then_branch(&self) -> Expression {
         self.cst
             .children()
             .find(|i| i.kind() == SyntaxKind::ExprBody)
-            .unwrap()
-            .children()
-            .find_map(Expression::cast)
-            .unwrap()
+            .and_then(|i| i.children().find_map(Expression::cast))
+            .unwrap_or_else(|| Expression::missing(&self.cst))
     }
     pub fn else_branch(&self) -> Option<Expression> {
         self.cst
@@ -926,6 +1108,14 @@ impl ForExpr {
         }
     }
 
+    /// A placeholder for this node when it is missing from `parent`
+    /// due to a syntax error; all its children are missing as well.
+    pub fn missing(parent: &CSTNode) -> Self {
+        Self {
+            cst: CSTNode::missing(parent),
+        }
+    }
+
     pub fn cst(&self) -> CSTNode {
         self.cst.clone()
     }
@@ -944,10 +1134,8 @@ impl ForExpr {
         self.cst
             .children()
             .find(|i| i.kind() == SyntaxKind::ExprBody)
-            .unwrap()
-            .children()
-            .find_map(Expression::cast)
-            .unwrap()
+            .and_then(|i| i.children().find_map(Expression::cast))
+            .unwrap_or_else(|| Expression::missing(&self.cst))
     }
     pub fn else_branch(&self) -> Option<Expression> {
         self.cst
@@ -973,6 +1161,14 @@ impl ForIterCond {
         }
     }
 
+    /// A placeholder for this node when it is missing from `parent`
+    /// due to a syntax error; all its children are missing as well.
+    pub fn missing(parent: &CSTNode) -> Self {
+        Self {
+            cst: CSTNode::missing(parent),
+        }
+    }
+
     pub fn cst(&self) -> CSTNode {
         self.cst.clone()
     }
@@ -981,14 +1177,14 @@ impl ForIterCond {
         self.cst
             .children_with_tokens()
             .find(|c| c.as_token().map(Token::kind) == Some(SyntaxKind::Identifier))
-            .unwrap()
-            .as_token()
-            .unwrap()
-            .text()
-            .clone()
+            .map(|c| c.as_token().unwrap().text().clone())
+            .unwrap_or_default()
     }
     pub fn iterator(&self) -> Expression {
-        self.cst.children().find_map(Expression::cast).unwrap()
+        self.cst
+            .children()
+            .find_map(Expression::cast)
+            .unwrap_or_else(|| Expression::missing(&self.cst))
     }
 }
 
@@ -1007,6 +1203,14 @@ impl Return {
         }
     }
 
+    /// A placeholder for this node when it is missing from `parent`
+    /// due to a syntax error; all its children are missing as well.
+    pub fn missing(parent: &CSTNode) -> Self {
+        Self {
+            cst: CSTNode::missing(parent),
+        }
+    }
+
     pub fn cst(&self) -> CSTNode {
         self.cst.clone()
     }
@@ -1031,6 +1235,14 @@ impl Break {
         }
     }
 
+    /// A placeholder for this node when it is missing from `parent`
+    /// due to a syntax error; all its children are missing as well.
+    pub fn missing(parent: &CSTNode) -> Self {
+        Self {
+            cst: CSTNode::missing(parent),
+        }
+    }
+
     pub fn cst(&self) -> CSTNode {
         self.cst.clone()
     }
@@ -1055,6 +1267,14 @@ impl When {
         }
     }
 
+    /// A placeholder for this node when it is missing from `parent`
+    /// due to a syntax error; all its children are missing as well.
+    pub fn missing(parent: &CSTNode) -> Self {
+        Self {
+            cst: CSTNode::missing(parent),
+        }
+    }
+
     pub fn cst(&self) -> CSTNode {
         self.cst.clone()
     }
@@ -1063,10 +1283,8 @@ impl When {
         self.cst
             .children()
             .find(|i| i.kind() == SyntaxKind::ExprCondition)
-            .unwrap()
-            .children()
-            .find_map(Expression::cast)
-            .unwrap()
+            .and_then(|i| i.children().find_map(Expression::cast))
+            .unwrap_or_else(|| Expression::missing(&self.cst))
     }
     pub fn branches(&self) -> impl Iterator<Item = WhenBranch> + '_ {
         self.cst.children().filter_map(WhenBranch::cast)
@@ -1095,6 +1313,14 @@ impl WhenBranch {
         }
     }
 
+    /// A placeholder for this node when it is missing from `parent`
+    /// due to a syntax error; all its children are missing as well.
+    pub fn missing(parent: &CSTNode) -> Self {
+        Self {
+            cst: CSTNode::missing(parent),
+        }
+    }
+
     pub fn cst(&self) -> CSTNode {
         self.cst.clone()
     }
@@ -1103,18 +1329,21 @@ impl WhenBranch {
         self.cst
             .children()
             .find(|i| i.kind() == SyntaxKind::ExprCondition)
-            .unwrap()
+            .and_then(|i| i.children().find_map(Expression::cast))
+            .unwrap_or_else(|| Expression::missing(&self.cst))
+    }
+    pub fn guard(&self) -> Option<Expression> {
+        self.cst
             .children()
-            .find_map(Expression::cast)
-            .unwrap()
+            .find(|i| i.kind() == SyntaxKind::ExprGuard)
+            .map(|i| i.children().find_map(Expression::cast))
+            .flatten()
     }
     pub fn branch(&self) -> Expression {
         self.cst
             .children()
             .find(|i| i.kind() == SyntaxKind::ExprBody)
-            .unwrap()
-            .children()
-            .find_map(Expression::cast)
-            .unwrap()
+            .and_then(|i| i.children().find_map(Expression::cast))
+            .unwrap_or_else(|| Expression::missing(&self.cst))
     }
 }
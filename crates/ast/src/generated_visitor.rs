@@ -0,0 +1,1016 @@
+//! This file is autogenerated by ast-generator. Do not modify!
+
+use crate::{
+    Adt, Binary, Block, Break, CSTNode, Call, DeclName, Expression, ForExpr, ForIterCond, Function,
+    FunctionSignature, GenericIdent, Get, GetStatic, Grouping, IfExpr, IfaceImpl, Import,
+    ImportSymbol, Literal, Module, Parameter, Prefix, Return, Type, TypeParameter, Variable, When,
+    WhenBranch,
+};
+
+/// A traversal of the AST. Every method visits a node of the given kind
+/// and walks its children by default, using the `walk_*` function of the
+/// node; implementations override the methods of the nodes they are
+/// interested in and call the `walk_*` function to keep descending.
+/// Children are walked in the order of their accessors, which is not
+/// always the order they appear in the source.
+pub trait Visitor {
+    fn visit_generic_ident(&mut self, node: &GenericIdent) {
+        walk_generic_ident(self, node)
+    }
+    fn visit_decl_name(&mut self, node: &DeclName) {
+        walk_decl_name(self, node)
+    }
+    fn visit_type_parameter(&mut self, node: &TypeParameter) {
+        walk_type_parameter(self, node)
+    }
+    fn visit_type(&mut self, node: &Type) {
+        walk_type(self, node)
+    }
+    fn visit_literal(&mut self, node: &Literal) {
+        walk_literal(self, node)
+    }
+    fn visit_module(&mut self, node: &Module) {
+        walk_module(self, node)
+    }
+    fn visit_adt(&mut self, node: &Adt) {
+        walk_adt(self, node)
+    }
+    fn visit_function(&mut self, node: &Function) {
+        walk_function(self, node)
+    }
+    fn visit_function_signature(&mut self, node: &FunctionSignature) {
+        walk_function_signature(self, node)
+    }
+    fn visit_parameter(&mut self, node: &Parameter) {
+        walk_parameter(self, node)
+    }
+    fn visit_import(&mut self, node: &Import) {
+        walk_import(self, node)
+    }
+    fn visit_import_symbol(&mut self, node: &ImportSymbol) {
+        walk_import_symbol(self, node)
+    }
+    fn visit_iface_impl(&mut self, node: &IfaceImpl) {
+        walk_iface_impl(self, node)
+    }
+    fn visit_expression(&mut self, node: &Expression) {
+        walk_expression(self, node)
+    }
+    fn visit_variable(&mut self, node: &Variable) {
+        walk_variable(self, node)
+    }
+    fn visit_grouping(&mut self, node: &Grouping) {
+        walk_grouping(self, node)
+    }
+    fn visit_binary(&mut self, node: &Binary) {
+        walk_binary(self, node)
+    }
+    fn visit_prefix(&mut self, node: &Prefix) {
+        walk_prefix(self, node)
+    }
+    fn visit_call(&mut self, node: &Call) {
+        walk_call(self, node)
+    }
+    fn visit_get(&mut self, node: &Get) {
+        walk_get(self, node)
+    }
+    fn visit_get_static(&mut self, node: &GetStatic) {
+        walk_get_static(self, node)
+    }
+    fn visit_block(&mut self, node: &Block) {
+        walk_block(self, node)
+    }
+    fn visit_if_expr(&mut self, node: &IfExpr) {
+        walk_if_expr(self, node)
+    }
+    fn visit_for_expr(&mut self, node: &ForExpr) {
+        walk_for_expr(self, node)
+    }
+    fn visit_for_iter_cond(&mut self, node: &ForIterCond) {
+        walk_for_iter_cond(self, node)
+    }
+    fn visit_return(&mut self, node: &Return) {
+        walk_return(self, node)
+    }
+    fn visit_break(&mut self, node: &Break) {
+        walk_break(self, node)
+    }
+    fn visit_when(&mut self, node: &When) {
+        walk_when(self, node)
+    }
+    fn visit_when_branch(&mut self, node: &WhenBranch) {
+        walk_when_branch(self, node)
+    }
+}
+
+pub fn walk_generic_ident<V: Visitor + ?Sized>(visitor: &mut V, node: &GenericIdent) {
+    for child in node.type_args() {
+        visitor.visit_type(&child);
+    }
+}
+
+pub fn walk_decl_name<V: Visitor + ?Sized>(visitor: &mut V, node: &DeclName) {
+    for child in node.type_parameters() {
+        visitor.visit_type_parameter(&child);
+    }
+}
+
+pub fn walk_type_parameter<V: Visitor + ?Sized>(visitor: &mut V, node: &TypeParameter) {
+    if let Some(child) = node.bound() {
+        visitor.visit_type(&child);
+    }
+}
+
+pub fn walk_type<V: Visitor + ?Sized>(_visitor: &mut V, _node: &Type) {}
+
+pub fn walk_literal<V: Visitor + ?Sized>(_visitor: &mut V, _node: &Literal) {}
+
+pub fn walk_module<V: Visitor + ?Sized>(visitor: &mut V, node: &Module) {
+    for child in node.adts() {
+        visitor.visit_adt(&child);
+    }
+    for child in node.functions() {
+        visitor.visit_function(&child);
+    }
+    for child in node.impls() {
+        visitor.visit_iface_impl(&child);
+    }
+    for child in node.imports() {
+        visitor.visit_import(&child);
+    }
+}
+
+pub fn walk_adt<V: Visitor + ?Sized>(visitor: &mut V, node: &Adt) {
+    visitor.visit_decl_name(&node.name());
+    for child in node.members() {
+        visitor.visit_variable(&child);
+    }
+    for child in node.constructors() {
+        visitor.visit_function(&child);
+    }
+    for child in node.methods() {
+        visitor.visit_function(&child);
+    }
+    for child in node.cases() {
+        visitor.visit_adt(&child);
+    }
+}
+
+pub fn walk_function<V: Visitor + ?Sized>(visitor: &mut V, node: &Function) {
+    visitor.visit_function_signature(&node.sig());
+    if let Some(child) = node.body() {
+        visitor.visit_expression(&child);
+    }
+}
+
+pub fn walk_function_signature<V: Visitor + ?Sized>(visitor: &mut V, node: &FunctionSignature) {
+    visitor.visit_decl_name(&node.name());
+    for child in node.parameters() {
+        visitor.visit_parameter(&child);
+    }
+    if let Some(child) = node.ret_type() {
+        visitor.visit_type(&child);
+    }
+}
+
+pub fn walk_parameter<V: Visitor + ?Sized>(visitor: &mut V, node: &Parameter) {
+    if let Some(child) = node.maybe_type() {
+        visitor.visit_type(&child);
+    }
+    if let Some(child) = node.default() {
+        visitor.visit_expression(&child);
+    }
+}
+
+pub fn walk_import<V: Visitor + ?Sized>(visitor: &mut V, node: &Import) {
+    for child in node.symbols() {
+        visitor.visit_import_symbol(&child);
+    }
+}
+
+pub fn walk_import_symbol<V: Visitor + ?Sized>(_visitor: &mut V, _node: &ImportSymbol) {}
+
+pub fn walk_iface_impl<V: Visitor + ?Sized>(visitor: &mut V, node: &IfaceImpl) {
+    if let Some(child) = node.iface() {
+        visitor.visit_type(&child);
+    }
+    visitor.visit_type(&node.implementor());
+    for child in node.methods() {
+        visitor.visit_function(&child);
+    }
+}
+
+pub fn walk_expression<V: Visitor + ?Sized>(visitor: &mut V, node: &Expression) {
+    match node {
+        Expression::Binary(inner) => visitor.visit_binary(inner),
+        Expression::Block(inner) => visitor.visit_block(inner),
+        Expression::Break(inner) => visitor.visit_break(inner),
+        Expression::Call(inner) => visitor.visit_call(inner),
+        Expression::For(inner) => visitor.visit_for_expr(inner),
+        Expression::Get(inner) => visitor.visit_get(inner),
+        Expression::GetNullable(inner) => visitor.visit_get(inner),
+        Expression::GetStatic(inner) => visitor.visit_get_static(inner),
+        Expression::Grouping(inner) => visitor.visit_grouping(inner),
+        Expression::If(inner) => visitor.visit_if_expr(inner),
+        Expression::Literal(inner) => visitor.visit_literal(inner),
+        Expression::LiteralClosure(inner) => visitor.visit_function(inner),
+        Expression::Prefix(inner) => visitor.visit_prefix(inner),
+        Expression::Return(inner) => visitor.visit_return(inner),
+        Expression::Variable(inner) => visitor.visit_generic_ident(inner),
+        Expression::VarDef(inner) => visitor.visit_variable(inner),
+        Expression::When(inner) => visitor.visit_when(inner),
+    }
+}
+
+pub fn walk_variable<V: Visitor + ?Sized>(visitor: &mut V, node: &Variable) {
+    if let Some(child) = node._type() {
+        visitor.visit_type(&child);
+    }
+    if let Some(child) = node.maybe_initializer() {
+        visitor.visit_expression(&child);
+    }
+}
+
+pub fn walk_grouping<V: Visitor + ?Sized>(visitor: &mut V, node: &Grouping) {
+    visitor.visit_expression(&node.inner());
+}
+
+pub fn walk_binary<V: Visitor + ?Sized>(visitor: &mut V, node: &Binary) {
+    visitor.visit_expression(&node.left());
+    visitor.visit_expression(&node.right());
+}
+
+pub fn walk_prefix<V: Visitor + ?Sized>(visitor: &mut V, node: &Prefix) {
+    visitor.visit_expression(&node.right());
+}
+
+pub fn walk_call<V: Visitor + ?Sized>(visitor: &mut V, node: &Call) {
+    visitor.visit_expression(&node.callee());
+    for child in node.args() {
+        visitor.visit_expression(&child);
+    }
+}
+
+pub fn walk_get<V: Visitor + ?Sized>(visitor: &mut V, node: &Get) {
+    visitor.visit_expression(&node.callee());
+    visitor.visit_generic_ident(&node.property());
+}
+
+pub fn walk_get_static<V: Visitor + ?Sized>(visitor: &mut V, node: &GetStatic) {
+    visitor.visit_expression(&node.callee());
+}
+
+pub fn walk_block<V: Visitor + ?Sized>(visitor: &mut V, node: &Block) {
+    for child in node.expressions() {
+        visitor.visit_expression(&child);
+    }
+}
+
+pub fn walk_if_expr<V: Visitor + ?Sized>(visitor: &mut V, node: &IfExpr) {
+    visitor.visit_expression(&node.condition());
+    visitor.visit_expression(&node.then_branch());
+    if let Some(child) = node.else_branch() {
+        visitor.visit_expression(&child);
+    }
+}
+
+pub fn walk_for_expr<V: Visitor + ?Sized>(visitor: &mut V, node: &ForExpr) {
+    if let Some(child) = node.condition() {
+        visitor.visit_expression(&child);
+    }
+    if let Some(child) = node.iter_cond() {
+        visitor.visit_for_iter_cond(&child);
+    }
+    visitor.visit_expression(&node.body());
+    if let Some(child) = node.else_branch() {
+        visitor.visit_expression(&child);
+    }
+}
+
+pub fn walk_for_iter_cond<V: Visitor + ?Sized>(visitor: &mut V, node: &ForIterCond) {
+    visitor.visit_expression(&node.iterator());
+}
+
+pub fn walk_return<V: Visitor + ?Sized>(visitor: &mut V, node: &Return) {
+    if let Some(child) = node.value() {
+        visitor.visit_expression(&child);
+    }
+}
+
+pub fn walk_break<V: Visitor + ?Sized>(visitor: &mut V, node: &Break) {
+    if let Some(child) = node.value() {
+        visitor.visit_expression(&child);
+    }
+}
+
+pub fn walk_when<V: Visitor + ?Sized>(visitor: &mut V, node: &When) {
+    visitor.visit_expression(&node.condition());
+    for child in node.branches() {
+        visitor.visit_when_branch(&child);
+    }
+    if let Some(child) = node.else_branch() {
+        visitor.visit_expression(&child);
+    }
+}
+
+pub fn walk_when_branch<V: Visitor + ?Sized>(visitor: &mut V, node: &WhenBranch) {
+    visitor.visit_expression(&node.condition());
+    if let Some(child) = node.guard() {
+        visitor.visit_expression(&child);
+    }
+    visitor.visit_expression(&node.branch());
+}
+
+/// A traversal of the AST that rewrites nodes. Every method returns the
+/// node it was given, or a replacement for it; the parents of a replaced node
+/// are rebuilt around it. By default, methods rewrite the children of the node,
+/// using the `rewrite_*_children` function of the node.
+/// Nodes missing due to syntax errors cannot be replaced, their rewrites are discarded.
+pub trait Rewriter {
+    fn rewrite_generic_ident(&mut self, node: GenericIdent) -> GenericIdent {
+        rewrite_generic_ident_children(self, node)
+    }
+    fn rewrite_decl_name(&mut self, node: DeclName) -> DeclName {
+        rewrite_decl_name_children(self, node)
+    }
+    fn rewrite_type_parameter(&mut self, node: TypeParameter) -> TypeParameter {
+        rewrite_type_parameter_children(self, node)
+    }
+    fn rewrite_type(&mut self, node: Type) -> Type {
+        rewrite_type_children(self, node)
+    }
+    fn rewrite_literal(&mut self, node: Literal) -> Literal {
+        rewrite_literal_children(self, node)
+    }
+    fn rewrite_module(&mut self, node: Module) -> Module {
+        rewrite_module_children(self, node)
+    }
+    fn rewrite_adt(&mut self, node: Adt) -> Adt {
+        rewrite_adt_children(self, node)
+    }
+    fn rewrite_function(&mut self, node: Function) -> Function {
+        rewrite_function_children(self, node)
+    }
+    fn rewrite_function_signature(&mut self, node: FunctionSignature) -> FunctionSignature {
+        rewrite_function_signature_children(self, node)
+    }
+    fn rewrite_parameter(&mut self, node: Parameter) -> Parameter {
+        rewrite_parameter_children(self, node)
+    }
+    fn rewrite_import(&mut self, node: Import) -> Import {
+        rewrite_import_children(self, node)
+    }
+    fn rewrite_import_symbol(&mut self, node: ImportSymbol) -> ImportSymbol {
+        rewrite_import_symbol_children(self, node)
+    }
+    fn rewrite_iface_impl(&mut self, node: IfaceImpl) -> IfaceImpl {
+        rewrite_iface_impl_children(self, node)
+    }
+    fn rewrite_expression(&mut self, node: Expression) -> Expression {
+        rewrite_expression_children(self, node)
+    }
+    fn rewrite_variable(&mut self, node: Variable) -> Variable {
+        rewrite_variable_children(self, node)
+    }
+    fn rewrite_grouping(&mut self, node: Grouping) -> Grouping {
+        rewrite_grouping_children(self, node)
+    }
+    fn rewrite_binary(&mut self, node: Binary) -> Binary {
+        rewrite_binary_children(self, node)
+    }
+    fn rewrite_prefix(&mut self, node: Prefix) -> Prefix {
+        rewrite_prefix_children(self, node)
+    }
+    fn rewrite_call(&mut self, node: Call) -> Call {
+        rewrite_call_children(self, node)
+    }
+    fn rewrite_get(&mut self, node: Get) -> Get {
+        rewrite_get_children(self, node)
+    }
+    fn rewrite_get_static(&mut self, node: GetStatic) -> GetStatic {
+        rewrite_get_static_children(self, node)
+    }
+    fn rewrite_block(&mut self, node: Block) -> Block {
+        rewrite_block_children(self, node)
+    }
+    fn rewrite_if_expr(&mut self, node: IfExpr) -> IfExpr {
+        rewrite_if_expr_children(self, node)
+    }
+    fn rewrite_for_expr(&mut self, node: ForExpr) -> ForExpr {
+        rewrite_for_expr_children(self, node)
+    }
+    fn rewrite_for_iter_cond(&mut self, node: ForIterCond) -> ForIterCond {
+        rewrite_for_iter_cond_children(self, node)
+    }
+    fn rewrite_return(&mut self, node: Return) -> Return {
+        rewrite_return_children(self, node)
+    }
+    fn rewrite_break(&mut self, node: Break) -> Break {
+        rewrite_break_children(self, node)
+    }
+    fn rewrite_when(&mut self, node: When) -> When {
+        rewrite_when_children(self, node)
+    }
+    fn rewrite_when_branch(&mut self, node: WhenBranch) -> WhenBranch {
+        rewrite_when_branch_children(self, node)
+    }
+}
+
+pub fn rewrite_generic_ident_children<R: Rewriter + ?Sized>(
+    rewriter: &mut R,
+    node: GenericIdent,
+) -> GenericIdent {
+    let mut replacements = Vec::new();
+    for child in node.type_args() {
+        record(
+            &mut replacements,
+            child.cst(),
+            rewriter.rewrite_type(child).cst(),
+        );
+    }
+    GenericIdent {
+        cst: node.cst.replace(&replacements),
+    }
+}
+
+pub fn rewrite_decl_name_children<R: Rewriter + ?Sized>(
+    rewriter: &mut R,
+    node: DeclName,
+) -> DeclName {
+    let mut replacements = Vec::new();
+    for child in node.type_parameters() {
+        record(
+            &mut replacements,
+            child.cst(),
+            rewriter.rewrite_type_parameter(child).cst(),
+        );
+    }
+    DeclName {
+        cst: node.cst.replace(&replacements),
+    }
+}
+
+pub fn rewrite_type_parameter_children<R: Rewriter + ?Sized>(
+    rewriter: &mut R,
+    node: TypeParameter,
+) -> TypeParameter {
+    let mut replacements = Vec::new();
+    if let Some(child) = node.bound() {
+        record(
+            &mut replacements,
+            child.cst(),
+            rewriter.rewrite_type(child).cst(),
+        );
+    }
+    TypeParameter {
+        cst: node.cst.replace(&replacements),
+    }
+}
+
+pub fn rewrite_type_children<R: Rewriter + ?Sized>(_rewriter: &mut R, node: Type) -> Type {
+    node
+}
+
+pub fn rewrite_literal_children<R: Rewriter + ?Sized>(_rewriter: &mut R, node: Literal) -> Literal {
+    node
+}
+
+pub fn rewrite_module_children<R: Rewriter + ?Sized>(rewriter: &mut R, node: Module) -> Module {
+    let mut replacements = Vec::new();
+    for child in node.adts() {
+        record(
+            &mut replacements,
+            child.cst(),
+            rewriter.rewrite_adt(child).cst(),
+        );
+    }
+    for child in node.functions() {
+        record(
+            &mut replacements,
+            child.cst(),
+            rewriter.rewrite_function(child).cst(),
+        );
+    }
+    for child in node.impls() {
+        record(
+            &mut replacements,
+            child.cst(),
+            rewriter.rewrite_iface_impl(child).cst(),
+        );
+    }
+    for child in node.imports() {
+        record(
+            &mut replacements,
+            child.cst(),
+            rewriter.rewrite_import(child).cst(),
+        );
+    }
+    Module {
+        cst: node.cst.replace(&replacements),
+        ..node
+    }
+}
+
+pub fn rewrite_adt_children<R: Rewriter + ?Sized>(rewriter: &mut R, node: Adt) -> Adt {
+    let mut replacements = Vec::new();
+    let child = node.name();
+    record(
+        &mut replacements,
+        child.cst(),
+        rewriter.rewrite_decl_name(child).cst(),
+    );
+    for child in node.members() {
+        record(
+            &mut replacements,
+            child.cst(),
+            rewriter.rewrite_variable(child).cst(),
+        );
+    }
+    for child in node.constructors() {
+        record(
+            &mut replacements,
+            child.cst(),
+            rewriter.rewrite_function(child).cst(),
+        );
+    }
+    for child in node.methods() {
+        record(
+            &mut replacements,
+            child.cst(),
+            rewriter.rewrite_function(child).cst(),
+        );
+    }
+    for child in node.cases() {
+        record(
+            &mut replacements,
+            child.cst(),
+            rewriter.rewrite_adt(child).cst(),
+        );
+    }
+    Adt {
+        cst: node.cst.replace(&replacements),
+    }
+}
+
+pub fn rewrite_function_children<R: Rewriter + ?Sized>(
+    rewriter: &mut R,
+    node: Function,
+) -> Function {
+    let mut replacements = Vec::new();
+    let child = node.sig();
+    record(
+        &mut replacements,
+        child.cst(),
+        rewriter.rewrite_function_signature(child).cst(),
+    );
+    if let Some(child) = node.body() {
+        record(
+            &mut replacements,
+            child.cst(),
+            rewriter.rewrite_expression(child).cst(),
+        );
+    }
+    Function {
+        cst: node.cst.replace(&replacements),
+    }
+}
+
+pub fn rewrite_function_signature_children<R: Rewriter + ?Sized>(
+    rewriter: &mut R,
+    node: FunctionSignature,
+) -> FunctionSignature {
+    let mut replacements = Vec::new();
+    let child = node.name();
+    record(
+        &mut replacements,
+        child.cst(),
+        rewriter.rewrite_decl_name(child).cst(),
+    );
+    for child in node.parameters() {
+        record(
+            &mut replacements,
+            child.cst(),
+            rewriter.rewrite_parameter(child).cst(),
+        );
+    }
+    if let Some(child) = node.ret_type() {
+        record(
+            &mut replacements,
+            child.cst(),
+            rewriter.rewrite_type(child).cst(),
+        );
+    }
+    FunctionSignature {
+        cst: node.cst.replace(&replacements),
+    }
+}
+
+pub fn rewrite_parameter_children<R: Rewriter + ?Sized>(
+    rewriter: &mut R,
+    node: Parameter,
+) -> Parameter {
+    let mut replacements = Vec::new();
+    if let Some(child) = node.maybe_type() {
+        record(
+            &mut replacements,
+            child.cst(),
+            rewriter.rewrite_type(child).cst(),
+        );
+    }
+    if let Some(child) = node.default() {
+        record(
+            &mut replacements,
+            child.cst(),
+            rewriter.rewrite_expression(child).cst(),
+        );
+    }
+    Parameter {
+        cst: node.cst.replace(&replacements),
+    }
+}
+
+pub fn rewrite_import_children<R: Rewriter + ?Sized>(rewriter: &mut R, node: Import) -> Import {
+    let mut replacements = Vec::new();
+    for child in node.symbols() {
+        record(
+            &mut replacements,
+            child.cst(),
+            rewriter.rewrite_import_symbol(child).cst(),
+        );
+    }
+    Import {
+        cst: node.cst.replace(&replacements),
+    }
+}
+
+pub fn rewrite_import_symbol_children<R: Rewriter + ?Sized>(
+    _rewriter: &mut R,
+    node: ImportSymbol,
+) -> ImportSymbol {
+    node
+}
+
+pub fn rewrite_iface_impl_children<R: Rewriter + ?Sized>(
+    rewriter: &mut R,
+    node: IfaceImpl,
+) -> IfaceImpl {
+    let mut replacements = Vec::new();
+    if let Some(child) = node.iface() {
+        record(
+            &mut replacements,
+            child.cst(),
+            rewriter.rewrite_type(child).cst(),
+        );
+    }
+    let child = node.implementor();
+    record(
+        &mut replacements,
+        child.cst(),
+        rewriter.rewrite_type(child).cst(),
+    );
+    for child in node.methods() {
+        record(
+            &mut replacements,
+            child.cst(),
+            rewriter.rewrite_function(child).cst(),
+        );
+    }
+    IfaceImpl {
+        cst: node.cst.replace(&replacements),
+    }
+}
+
+pub fn rewrite_expression_children<R: Rewriter + ?Sized>(
+    rewriter: &mut R,
+    node: Expression,
+) -> Expression {
+    match node {
+        Expression::Binary(inner) => Expression::Binary(rewriter.rewrite_binary(inner)),
+        Expression::Block(inner) => Expression::Block(rewriter.rewrite_block(inner)),
+        Expression::Break(inner) => Expression::Break(rewriter.rewrite_break(inner)),
+        Expression::Call(inner) => Expression::Call(rewriter.rewrite_call(inner)),
+        Expression::For(inner) => Expression::For(rewriter.rewrite_for_expr(inner)),
+        Expression::Get(inner) => Expression::Get(rewriter.rewrite_get(inner)),
+        Expression::GetNullable(inner) => Expression::GetNullable(rewriter.rewrite_get(inner)),
+        Expression::GetStatic(inner) => Expression::GetStatic(rewriter.rewrite_get_static(inner)),
+        Expression::Grouping(inner) => Expression::Grouping(rewriter.rewrite_grouping(inner)),
+        Expression::If(inner) => Expression::If(rewriter.rewrite_if_expr(inner)),
+        Expression::Literal(inner) => Expression::Literal(rewriter.rewrite_literal(inner)),
+        Expression::LiteralClosure(inner) => {
+            Expression::LiteralClosure(rewriter.rewrite_function(inner))
+        }
+        Expression::Prefix(inner) => Expression::Prefix(rewriter.rewrite_prefix(inner)),
+        Expression::Return(inner) => Expression::Return(rewriter.rewrite_return(inner)),
+        Expression::Variable(inner) => Expression::Variable(rewriter.rewrite_generic_ident(inner)),
+        Expression::VarDef(inner) => Expression::VarDef(rewriter.rewrite_variable(inner)),
+        Expression::When(inner) => Expression::When(rewriter.rewrite_when(inner)),
+    }
+}
+
+pub fn rewrite_variable_children<R: Rewriter + ?Sized>(
+    rewriter: &mut R,
+    node: Variable,
+) -> Variable {
+    let mut replacements = Vec::new();
+    if let Some(child) = node._type() {
+        record(
+            &mut replacements,
+            child.cst(),
+            rewriter.rewrite_type(child).cst(),
+        );
+    }
+    if let Some(child) = node.maybe_initializer() {
+        record(
+            &mut replacements,
+            child.cst(),
+            rewriter.rewrite_expression(child).cst(),
+        );
+    }
+    Variable {
+        cst: node.cst.replace(&replacements),
+    }
+}
+
+pub fn rewrite_grouping_children<R: Rewriter + ?Sized>(
+    rewriter: &mut R,
+    node: Grouping,
+) -> Grouping {
+    let mut replacements = Vec::new();
+    let child = node.inner();
+    record(
+        &mut replacements,
+        child.cst(),
+        rewriter.rewrite_expression(child).cst(),
+    );
+    Grouping {
+        cst: node.cst.replace(&replacements),
+    }
+}
+
+pub fn rewrite_binary_children<R: Rewriter + ?Sized>(rewriter: &mut R, node: Binary) -> Binary {
+    let mut replacements = Vec::new();
+    let child = node.left();
+    record(
+        &mut replacements,
+        child.cst(),
+        rewriter.rewrite_expression(child).cst(),
+    );
+    let child = node.right();
+    record(
+        &mut replacements,
+        child.cst(),
+        rewriter.rewrite_expression(child).cst(),
+    );
+    Binary {
+        cst: node.cst.replace(&replacements),
+    }
+}
+
+pub fn rewrite_prefix_children<R: Rewriter + ?Sized>(rewriter: &mut R, node: Prefix) -> Prefix {
+    let mut replacements = Vec::new();
+    let child = node.right();
+    record(
+        &mut replacements,
+        child.cst(),
+        rewriter.rewrite_expression(child).cst(),
+    );
+    Prefix {
+        cst: node.cst.replace(&replacements),
+    }
+}
+
+pub fn rewrite_call_children<R: Rewriter + ?Sized>(rewriter: &mut R, node: Call) -> Call {
+    let mut replacements = Vec::new();
+    let child = node.callee();
+    record(
+        &mut replacements,
+        child.cst(),
+        rewriter.rewrite_expression(child).cst(),
+    );
+    for child in node.args() {
+        record(
+            &mut replacements,
+            child.cst(),
+            rewriter.rewrite_expression(child).cst(),
+        );
+    }
+    Call {
+        cst: node.cst.replace(&replacements),
+    }
+}
+
+pub fn rewrite_get_children<R: Rewriter + ?Sized>(rewriter: &mut R, node: Get) -> Get {
+    let mut replacements = Vec::new();
+    let child = node.callee();
+    record(
+        &mut replacements,
+        child.cst(),
+        rewriter.rewrite_expression(child).cst(),
+    );
+    let child = node.property();
+    record(
+        &mut replacements,
+        child.cst(),
+        rewriter.rewrite_generic_ident(child).cst(),
+    );
+    Get {
+        cst: node.cst.replace(&replacements),
+    }
+}
+
+pub fn rewrite_get_static_children<R: Rewriter + ?Sized>(
+    rewriter: &mut R,
+    node: GetStatic,
+) -> GetStatic {
+    let mut replacements = Vec::new();
+    let child = node.callee();
+    record(
+        &mut replacements,
+        child.cst(),
+        rewriter.rewrite_expression(child).cst(),
+    );
+    GetStatic {
+        cst: node.cst.replace(&replacements),
+    }
+}
+
+pub fn rewrite_block_children<R: Rewriter + ?Sized>(rewriter: &mut R, node: Block) -> Block {
+    let mut replacements = Vec::new();
+    for child in node.expressions() {
+        record(
+            &mut replacements,
+            child.cst(),
+            rewriter.rewrite_expression(child).cst(),
+        );
+    }
+    Block {
+        cst: node.cst.replace(&replacements),
+    }
+}
+
+pub fn rewrite_if_expr_children<R: Rewriter + ?Sized>(rewriter: &mut R, node: IfExpr) -> IfExpr {
+    let mut replacements = Vec::new();
+    let child = node.condition();
+    record(
+        &mut replacements,
+        child.cst(),
+        rewriter.rewrite_expression(child).cst(),
+    );
+    let child = node.then_branch();
+    record(
+        &mut replacements,
+        child.cst(),
+        rewriter.rewrite_expression(child).cst(),
+    );
+    if let Some(child) = node.else_branch() {
+        record(
+            &mut replacements,
+            child.cst(),
+            rewriter.rewrite_expression(child).cst(),
+        );
+    }
+    IfExpr {
+        cst: node.cst.replace(&replacements),
+    }
+}
+
+pub fn rewrite_for_expr_children<R: Rewriter + ?Sized>(rewriter: &mut R, node: ForExpr) -> ForExpr {
+    let mut replacements = Vec::new();
+    if let Some(child) = node.condition() {
+        record(
+            &mut replacements,
+            child.cst(),
+            rewriter.rewrite_expression(child).cst(),
+        );
+    }
+    if let Some(child) = node.iter_cond() {
+        record(
+            &mut replacements,
+            child.cst(),
+            rewriter.rewrite_for_iter_cond(child).cst(),
+        );
+    }
+    let child = node.body();
+    record(
+        &mut replacements,
+        child.cst(),
+        rewriter.rewrite_expression(child).cst(),
+    );
+    if let Some(child) = node.else_branch() {
+        record(
+            &mut replacements,
+            child.cst(),
+            rewriter.rewrite_expression(child).cst(),
+        );
+    }
+    ForExpr {
+        cst: node.cst.replace(&replacements),
+    }
+}
+
+pub fn rewrite_for_iter_cond_children<R: Rewriter + ?Sized>(
+    rewriter: &mut R,
+    node: ForIterCond,
+) -> ForIterCond {
+    let mut replacements = Vec::new();
+    let child = node.iterator();
+    record(
+        &mut replacements,
+        child.cst(),
+        rewriter.rewrite_expression(child).cst(),
+    );
+    ForIterCond {
+        cst: node.cst.replace(&replacements),
+    }
+}
+
+pub fn rewrite_return_children<R: Rewriter + ?Sized>(rewriter: &mut R, node: Return) -> Return {
+    let mut replacements = Vec::new();
+    if let Some(child) = node.value() {
+        record(
+            &mut replacements,
+            child.cst(),
+            rewriter.rewrite_expression(child).cst(),
+        );
+    }
+    Return {
+        cst: node.cst.replace(&replacements),
+    }
+}
+
+pub fn rewrite_break_children<R: Rewriter + ?Sized>(rewriter: &mut R, node: Break) -> Break {
+    let mut replacements = Vec::new();
+    if let Some(child) = node.value() {
+        record(
+            &mut replacements,
+            child.cst(),
+            rewriter.rewrite_expression(child).cst(),
+        );
+    }
+    Break {
+        cst: node.cst.replace(&replacements),
+    }
+}
+
+pub fn rewrite_when_children<R: Rewriter + ?Sized>(rewriter: &mut R, node: When) -> When {
+    let mut replacements = Vec::new();
+    let child = node.condition();
+    record(
+        &mut replacements,
+        child.cst(),
+        rewriter.rewrite_expression(child).cst(),
+    );
+    for child in node.branches() {
+        record(
+            &mut replacements,
+            child.cst(),
+            rewriter.rewrite_when_branch(child).cst(),
+        );
+    }
+    if let Some(child) = node.else_branch() {
+        record(
+            &mut replacements,
+            child.cst(),
+            rewriter.rewrite_expression(child).cst(),
+        );
+    }
+    When {
+        cst: node.cst.replace(&replacements),
+    }
+}
+
+pub fn rewrite_when_branch_children<R: Rewriter + ?Sized>(
+    rewriter: &mut R,
+    node: WhenBranch,
+) -> WhenBranch {
+    let mut replacements = Vec::new();
+    let child = node.condition();
+    record(
+        &mut replacements,
+        child.cst(),
+        rewriter.rewrite_expression(child).cst(),
+    );
+    if let Some(child) = node.guard() {
+        record(
+            &mut replacements,
+            child.cst(),
+            rewriter.rewrite_expression(child).cst(),
+        );
+    }
+    let child = node.branch();
+    record(
+        &mut replacements,
+        child.cst(),
+        rewriter.rewrite_expression(child).cst(),
+    );
+    WhenBranch {
+        cst: node.cst.replace(&replacements),
+    }
+}
+
+/// Records that `old` was rewritten to `new`, unless it was returned unchanged.
+fn record(replacements: &mut Vec<(CSTNode, CSTNode)>, old: CSTNode, new: CSTNode) {
+    if old != new {
+        replacements.push((old, new));
+    }
+}
@@ -1,4 +1,5 @@
 pub use generated_nodes::*;
+pub use generated_visitor::*;
 pub use literal::LiteralType;
 use smol_str::SmolStr;
 pub use types::TypeE;
@@ -9,6 +10,7 @@ use syntax::kind::SyntaxKind;
 pub type CSTNode = Node;
 
 mod generated_nodes;
+mod generated_visitor;
 mod literal;
 mod types;
 
@@ -19,20 +21,100 @@ impl Import {
             .any(|c| c.as_token().map(Token::kind) == Some(SyntaxKind::Export))
     }
 
-    pub fn parts(&self) -> impl Iterator<Item = SmolStr> + '_ {
+    /// The path of the module imported from, without the imported symbol(s).
+    pub fn path(&self) -> impl Iterator<Item = SmolStr> + '_ {
         self.cst
             .children_with_tokens()
-            .filter(|c| {
-                matches!(
-                    c.as_token().map(Token::kind),
-                    Some(SyntaxKind::Identifier) | Some(SyntaxKind::Plus)
-                )
-            })
+            .filter(|c| c.as_token().map(Token::kind) == Some(SyntaxKind::Identifier))
             .map(|c| c.as_token().unwrap().text().clone())
     }
+
+    /// If this is a relative import ('./' or '../'), the amount of
+    /// '../' segments at the start of the path.
+    pub fn relative_depth(&self) -> Option<usize> {
+        let (dots, slashes) = self
+            .cst
+            .children_with_tokens()
+            .filter_map(|c| c.into_token())
+            .take_while(|t| t.kind() != SyntaxKind::Identifier)
+            .fold((0, 0), |(dots, slashes), t| match t.kind() {
+                SyntaxKind::Dot => (dots + 1, slashes),
+                SyntaxKind::DotDot => (dots + 2, slashes),
+                SyntaxKind::Slash => (dots, slashes + 1),
+                _ => (dots, slashes),
+            });
+        if slashes == 0 {
+            None
+        } else {
+            Some(dots - slashes)
+        }
+    }
+
+    /// If this is a wildcard import ('import a/b/+').
+    pub fn is_wildcard(&self) -> bool {
+        self.cst
+            .children_with_tokens()
+            .any(|c| c.as_token().map(Token::kind) == Some(SyntaxKind::Plus))
+    }
+}
+
+impl ImportSymbol {
+    /// The alias given with 'as', if any.
+    pub fn alias(&self) -> Option<SmolStr> {
+        self.cst
+            .children_with_tokens()
+            .filter_map(|c| c.into_token())
+            .filter(|t| t.kind() == SyntaxKind::Identifier)
+            .nth(1)
+            .map(|t| t.text().clone())
+    }
+
+    /// The name this symbol is available as in the importing module;
+    /// the alias if present, otherwise the name itself.
+    pub fn binding(&self) -> SmolStr {
+        self.alias().unwrap_or_else(|| self.name())
+    }
+}
+
+impl Call {
+    /// The names of all arguments, in order; `None` if the
+    /// argument is positional and not named ('f(a: 5)').
+    pub fn arg_names(&self) -> impl Iterator<Item = Option<SmolStr>> + '_ {
+        self.cst
+            .children()
+            .filter(|c| c.kind() == SyntaxKind::CallArgument)
+            .map(|arg| {
+                arg.children_with_tokens()
+                    .filter_map(|c| c.into_token())
+                    .find(|t| t.kind() == SyntaxKind::Identifier)
+                    .map(|t| t.text().clone())
+            })
+    }
+}
+
+impl WhenBranch {
+    /// If this branch is an explicit type test ('is Type -> ...').
+    pub fn is_type_test(&self) -> bool {
+        self.cst
+            .children_with_tokens()
+            .any(|c| c.as_token().map(Token::kind) == Some(SyntaxKind::Is))
+    }
+
+    /// The name the value is bound to in this branch, if any ('is Type name -> ...').
+    pub fn binding(&self) -> Option<SmolStr> {
+        self.cst
+            .children_with_tokens()
+            .filter_map(|c| c.into_token())
+            .find(|t| t.kind() == SyntaxKind::Identifier)
+            .map(|t| t.text().clone())
+    }
 }
 
 impl Function {
+    pub fn is_static(&self) -> bool {
+        self.modifiers().any(|m| m == SyntaxKind::Static)
+    }
+
     pub fn cast_constructor(node: CSTNode) -> Option<Self> {
         if let SyntaxKind::Constructor = node.kind() {
             Some(Self { cst: node })
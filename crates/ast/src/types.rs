@@ -4,12 +4,18 @@ use smol_str::SmolStr;
 use syntax::kind::SyntaxKind;
 
 impl Type {
+    /// Returns the kind of type this is. Types that are missing or
+    /// malformed due to syntax errors are an empty identifier.
     pub fn get(&self) -> TypeE {
-        if self.cst.last_token().unwrap().kind() == SyntaxKind::QuestionMark {
-            return TypeE::Nullable(self.cst.first_child().map(Self::cast).unwrap().unwrap());
+        let token = match self.cst.first_token() {
+            Some(token) => token,
+            None => return TypeE::Ident(SmolStr::default()),
+        };
+
+        if self.cst.last_token().map(|t| t.kind()) == Some(SyntaxKind::QuestionMark) {
+            return TypeE::Nullable(self.inner());
         }
 
-        let token = self.cst.first_token().unwrap();
         match token.kind() {
             SyntaxKind::Identifier if self.cst.first_child().is_none() => {
                 // No children => only identifier
@@ -25,9 +31,7 @@ impl Type {
                 }
             }
 
-            SyntaxKind::Star => {
-                TypeE::RawPtr(self.cst.first_child().map(Self::cast).unwrap().unwrap())
-            }
+            SyntaxKind::Star => TypeE::RawPtr(self.inner()),
 
             SyntaxKind::LeftParen => {
                 let mut types: Vec<_> = self.cst.children().filter_map(Type::cast).collect();
@@ -37,9 +41,16 @@ impl Type {
                 }
             }
 
-            _ => panic!("Cannot parse type"),
+            _ => TypeE::Ident(SmolStr::default()),
         }
     }
+
+    fn inner(&self) -> Type {
+        self.cst
+            .first_child()
+            .and_then(Self::cast)
+            .unwrap_or_else(|| Self::missing(&self.cst))
+    }
 }
 
 pub enum TypeE {
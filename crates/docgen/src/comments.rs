@@ -0,0 +1,97 @@
+//! Extraction of doc comments ('///') from the CST.
+//! Comments are trivia the parser puts wherever it skipped them,
+//! often at the end of the node before, so they are found by
+//! walking the tokens of the whole module in order.
+
+use parser::{Node, NodeOrToken};
+use std::collections::HashMap;
+use syntax::kind::SyntaxKind;
+
+/// All doc comments of a module.
+#[derive(Default)]
+pub struct DocComments {
+    /// The comment at the start of the module, separated from
+    /// the code after it by an empty line. Describes the module itself.
+    pub module: Option<String>,
+    /// All other comments, by the start of the token directly after them.
+    items: HashMap<u32, String>,
+}
+
+impl DocComments {
+    pub fn new(root: &Node) -> Self {
+        let mut docs = DocComments::default();
+        let mut lines = Vec::new();
+        // If the current lines are followed by an empty line,
+        // which means they do not belong to the code after them
+        let mut detached = false;
+        let mut module_start = true;
+        for_each_token(root, &mut |start, kind, text| match kind {
+            SyntaxKind::Comment if is_doc(text) => {
+                if detached {
+                    lines.clear();
+                    detached = false;
+                }
+                let line = &text[3..];
+                lines.push(line.strip_prefix(' ').unwrap_or(line).to_string());
+            }
+
+            SyntaxKind::Whitespace => {
+                if !lines.is_empty() && text.matches('\n').count() > 1 && !detached {
+                    detached = true;
+                    if module_start && docs.module.is_none() {
+                        docs.module = Some(lines.join("\n"));
+                    }
+                }
+            }
+
+            SyntaxKind::Comment => {
+                lines.clear();
+                detached = false;
+            }
+
+            _ => {
+                if !lines.is_empty() && !detached {
+                    docs.items.insert(start, lines.join("\n"));
+                }
+                lines.clear();
+                detached = false;
+                module_start = false;
+            }
+        });
+        docs
+    }
+
+    /// Returns the doc comment directly before the given node.
+    pub fn of(&self, node: &Node) -> Option<&str> {
+        let mut first = None;
+        for_each_token(node, &mut |start, kind, _| {
+            if first.is_none() && !kind.should_skip() {
+                first = Some(start)
+            }
+        });
+        self.items.get(&first?).map(String::as_str)
+    }
+}
+
+/// Returns if the comment is a doc comment. Like in Rust,
+/// comments starting with 4 slashes are not.
+fn is_doc(comment: &str) -> bool {
+    comment.starts_with("///") && !comment.starts_with("////")
+}
+
+/// Calls `f` with the start, kind and text of all tokens of the node, in order.
+fn for_each_token(node: &Node, f: &mut impl FnMut(u32, SyntaxKind, &str)) {
+    fn walk(node: &Node, start: &mut u32, f: &mut impl FnMut(u32, SyntaxKind, &str)) {
+        for child in node.children_with_tokens() {
+            match child {
+                NodeOrToken::Node(node) => walk(&node, start, f),
+                NodeOrToken::Token(token) => {
+                    f(*start, token.kind(), token.text().as_str());
+                    *start += token.text().len() as u32;
+                }
+            }
+        }
+    }
+    let mut start = node.text_range().start;
+    walk(node, &mut start, f)
+}
@@ -0,0 +1,432 @@
+//! Rendering of the pages to HTML.
+
+use crate::{anchor, comments::DocComments, public_adts, public_functions, Links};
+use common::MutRc;
+use gir_generator::CompiledGIR;
+use gir_nodes::{
+    declaration::{ADTType, Visibility},
+    types::{Bound, TypeParameter, TypeParameterBound},
+    Function, Module, Type, ADT,
+};
+use pulldown_cmark::{html, Event, Parser};
+use std::rc::Rc;
+
+/// A member of an ADT, rendered as signature and docs.
+struct Member {
+    anchor: String,
+    signature: String,
+    docs: Option<String>,
+}
+
+/// Writes a single page.
+pub(crate) struct PageWriter<'a> {
+    /// Prefix of links to other pages, leading back to the root of the documentation.
+    root: String,
+    gir: &'a CompiledGIR,
+    links: &'a Links,
+    out: String,
+}
+
+impl<'a> PageWriter<'a> {
+    pub fn new(path: &str, gir: &'a CompiledGIR, links: &'a Links) -> Self {
+        Self {
+            root: "../".repeat(path.matches('/').count()),
+            gir,
+            links,
+            out: String::with_capacity(10_000),
+        }
+    }
+
+    /// Writes the index, listing all modules with the summary of their docs.
+    pub fn index(mut self, root: &str, modules: &[(MutRc<Module>, DocComments)]) -> String {
+        self.header(&format!("{} documentation", root));
+        self.out.push_str("<h1>Modules</h1>\n<dl>\n");
+        for (module, docs) in modules {
+            let path = module.borrow().path.to_string();
+            self.out.push_str(&format!(
+                "<dt><a href=\"{}.html\">{}</a></dt>\n<dd>",
+                path,
+                escape(&path)
+            ));
+            if let Some(summary) = docs.module.as_ref().and_then(|d| d.split("\n\n").next()) {
+                self.markdown(summary);
+            }
+            self.out.push_str("</dd>\n");
+        }
+        self.out.push_str("</dl>\n");
+        self.footer()
+    }
+
+    /// Writes the page of a module, documenting all its public declarations.
+    pub fn module(mut self, module: &MutRc<Module>, docs: &DocComments) -> String {
+        let module = module.borrow();
+        self.header(&module.path.to_string());
+        self.out.push_str(&format!(
+            "<h1>Module <code>{}</code></h1>\n",
+            escape(&module.path.to_string())
+        ));
+        if let Some(module_docs) = &docs.module {
+            self.markdown(module_docs);
+        }
+
+        let adts = public_adts(&module);
+        for (title, kind) in &[
+            ("Interfaces", "interface"),
+            ("Classes", "class"),
+            ("Enums", "enum"),
+        ] {
+            let adts = adts
+                .iter()
+                .filter(|adt| adt_keyword(&adt.borrow()) == *kind)
+                .collect::<Vec<_>>();
+            if !adts.is_empty() {
+                self.out.push_str(&format!("<h2>{}</h2>\n", title));
+                for adt in adts {
+                    self.adt(adt, docs);
+                }
+            }
+        }
+
+        let functions = public_functions(&module);
+        if !functions.is_empty() {
+            self.out.push_str("<h2>Functions</h2>\n");
+        }
+        for func in functions {
+            let func = func.borrow();
+            self.out.push_str(&format!(
+                "<section id=\"{}\">\n<pre class=\"signature\">{}</pre>\n",
+                anchor(&func.name),
+                self.function(&func, "func", &func.name, &[])
+            ));
+            self.docs(func.ast.as_ref().and_then(|ast| docs.of(&ast.cst)));
+            self.out.push_str("</section>\n");
+        }
+
+        self.footer()
+    }
+
+    fn adt(&mut self, adt: &MutRc<ADT>, docs: &DocComments) {
+        let adt_ref = adt.borrow();
+        self.out.push_str(&format!(
+            "<section id=\"{}\">\n<pre class=\"signature\">{}</pre>\n",
+            anchor(&adt_ref.name),
+            self.adt_signature(&adt_ref)
+        ));
+        self.docs(docs.of(&adt_ref.ast.cst));
+
+        if let ADTType::Enum { cases } = &adt_ref.ty {
+            let mut cases = cases.values().collect::<Vec<_>>();
+            cases.sort_by_key(|case| case.borrow().ast.cst.text_range().start);
+            let cases = cases
+                .into_iter()
+                .map(|case| {
+                    let case = case.borrow();
+                    Member {
+                        anchor: anchor(&case.name),
+                        signature: self.adt_signature(&case),
+                        docs: docs.of(&case.ast.cst).map(str::to_string),
+                    }
+                })
+                .collect();
+            self.members("Cases", cases);
+        }
+
+        let fields = adt_ref
+            .fields
+            .values()
+            .filter(|field| field.visibility == Visibility::Public)
+            .map(|field| {
+                let keyword = if field.mutable { "var" } else { "val" };
+                let member = adt_ref.ast.members().find(|m| m.name() == field.name);
+                Member {
+                    anchor: format!("{}.{}", anchor(&adt_ref.name), field.name),
+                    signature: format!(
+                        "{} <span class=\"name\">{}</span>: {}",
+                        keyword,
+                        escape(&field.name),
+                        self.ty(&field.ty)
+                    ),
+                    docs: member.and_then(|m| docs.of(&m.cst).map(str::to_string)),
+                }
+            })
+            .collect();
+        self.members("Fields", fields);
+
+        let constructors = adt_ref
+            .constructors
+            .iter()
+            .enumerate()
+            .map(|(i, constructor)| ("construct", format!("construct.{}", i + 1), constructor))
+            .collect::<Vec<_>>();
+        let constructors = self.functions(&adt_ref, &constructors, "", docs);
+        self.members("Constructors", constructors);
+
+        let methods = adt_ref
+            .methods
+            .iter()
+            .chain(adt_ref.static_methods.iter())
+            .map(|(name, method)| (name.as_str(), name.to_string(), method))
+            .collect::<Vec<_>>();
+        let methods = self.functions(&adt_ref, &methods, "func", docs);
+        self.members("Methods", methods);
+
+        self.implementations(adt);
+        self.out.push_str("</section>\n");
+    }
+
+    /// Returns the given user-written, public functions of an ADT as members.
+    fn functions(
+        &self,
+        adt: &ADT,
+        functions: &[(&str, String, &MutRc<Function>)],
+        keyword: &str,
+        docs: &DocComments,
+    ) -> Vec<Member> {
+        functions
+            .iter()
+            .filter_map(|(name, id, func)| {
+                let func = func.borrow();
+                let ast = func.ast.as_ref()?;
+                if func.visibility != Visibility::Public {
+                    return None;
+                }
+                Some(Member {
+                    anchor: format!("{}.{}", anchor(&adt.name), id),
+                    signature: self.function(&func, keyword, name, &adt.type_parameters),
+                    docs: docs.of(&ast.cst).map(str::to_string),
+                })
+            })
+            .collect()
+    }
+
+    fn members(&mut self, title: &str, members: Vec<Member>) {
+        if members.is_empty() {
+            return;
+        }
+        self.out.push_str(&format!("<h3>{}</h3>\n", title));
+        for member in members {
+            self.out.push_str(&format!(
+                "<div class=\"member\" id=\"{}\">\n<pre class=\"signature\">{}</pre>\n",
+                member.anchor, member.signature
+            ));
+            self.docs(member.docs.as_deref());
+            self.out.push_str("</div>\n");
+        }
+    }
+
+    /// Lists the interfaces an ADT implements, or the implementors of an interface.
+    fn implementations(&mut self, adt: &MutRc<ADT>) {
+        let is_interface = matches!(adt.borrow().ty, ADTType::Interface);
+        let mut impls = self
+            .gir
+            .iface_impls
+            .values()
+            .flat_map(|impls| {
+                impls
+                    .borrow()
+                    .interfaces
+                    .values()
+                    .filter(|im| match &im.implementor {
+                        _ if is_interface => Rc::ptr_eq(&im.iface.ty, adt),
+                        Type::Adt(implementor) => Rc::ptr_eq(&implementor.ty, adt),
+                        _ => false,
+                    })
+                    .map(|im| {
+                        let iface = Type::Adt(im.iface.clone());
+                        let text = format!("impl {} for {}", iface, im.implementor);
+                        let html =
+                            format!("impl {} for {}", self.ty(&iface), self.ty(&im.implementor));
+                        (text, html)
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        if impls.is_empty() {
+            return;
+        }
+        impls.sort();
+        impls.dedup();
+
+        let title = if is_interface {
+            "Implementors"
+        } else {
+            "Implementations"
+        };
+        self.out.push_str(&format!("<h3>{}</h3>\n", title));
+        for (_, html) in impls {
+            self.out
+                .push_str(&format!("<pre class=\"signature\">{}</pre>\n", html));
+        }
+    }
+
+    /// Returns the signature of a function. Receivers of methods are left out,
+    /// as well as type parameters of their ADT, given as `adt_params`.
+    fn function(
+        &self,
+        func: &Function,
+        keyword: &str,
+        name: &str,
+        adt_params: &[TypeParameter],
+    ) -> String {
+        let mut sig = if keyword.is_empty() {
+            format!("<span class=\"name\">{}</span>", escape(name))
+        } else {
+            format!("{} <span class=\"name\">{}</span>", keyword, escape(name))
+        };
+        let type_params = func
+            .type_parameters
+            .iter()
+            .filter(|param| !adt_params.iter().any(|p| p.name == param.name))
+            .cloned()
+            .collect::<Vec<_>>();
+        sig.push_str(&self.type_params(&type_params));
+        let params = func
+            .parameters
+            .iter()
+            .filter(|param| param.name != "this")
+            .map(|param| format!("{}: {}", escape(&param.name), self.ty(&param.ty)))
+            .collect::<Vec<_>>();
+        sig.push_str(&format!("({})", params.join(", ")));
+        if !func.ret_type.is_none() {
+            sig.push_str(&format!(" -&gt; {}", self.ty(&func.ret_type)));
+        }
+        sig
+    }
+
+    fn adt_signature(&self, adt: &ADT) -> String {
+        // Names of cases include their enum ('Enum:Case')
+        let name = adt.name.rsplit(':').next().unwrap_or(&adt.name);
+        let mut sig = format!(
+            "{} <span class=\"name\">{}</span>",
+            adt_keyword(adt),
+            escape(name)
+        );
+        // Cases share the type parameters of their enum
+        if let ADTType::EnumCase { .. } = adt.ty {
+            if !adt.fields.is_empty() {
+                let fields = adt
+                    .fields
+                    .values()
+                    .map(|field| format!("{}: {}", escape(&field.name), self.ty(&field.ty)))
+                    .collect::<Vec<_>>();
+                sig.push_str(&format!("({})", fields.join(", ")));
+            }
+        } else {
+            sig.push_str(&self.type_params(&adt.type_parameters));
+        }
+        sig
+    }
+
+    fn type_params(&self, params: &[TypeParameter]) -> String {
+        if params.is_empty() {
+            return String::new();
+        }
+        let params = params
+            .iter()
+            .map(|param| match &param.bound {
+                TypeParameterBound::Bound(Bound::Unbounded) => escape(&param.name),
+                TypeParameterBound::Bound(bound) => format!("{}: {:?}", escape(&param.name), bound),
+                TypeParameterBound::Interface(iface) => {
+                    format!("{}: {}", escape(&param.name), self.ty(iface))
+                }
+            })
+            .collect::<Vec<_>>();
+        format!("[{}]", params.join(", "))
+    }
+
+    /// Returns the type, linking to the documentation of all ADTs in it.
+    fn ty(&self, ty: &Type) -> String {
+        match ty {
+            Type::Adt(inst) => {
+                let name = escape(&inst.ty.borrow().name);
+                let mut html = match self.links.adt(&inst.ty) {
+                    Some(target) => format!("<a href=\"{}{}\">{}</a>", self.root, target, name),
+                    None => name,
+                };
+                if !inst.args().is_empty() {
+                    let args = inst.args().iter().map(|arg| self.ty(arg));
+                    html.push_str(&format!("[{}]", args.collect::<Vec<_>>().join(", ")));
+                }
+                html
+            }
+            Type::Nullable(inner) => format!("{}?", self.ty(inner)),
+            Type::RawPtr(inner) => format!("*{}", self.ty(inner)),
+            Type::Variable(var) => escape(&var.name),
+            _ => escape(&ty.to_string()),
+        }
+    }
+
+    fn docs(&mut self, docs: Option<&str>) {
+        if let Some(docs) = docs {
+            self.markdown(docs);
+        }
+    }
+
+    /// Renders markdown of doc comments. Code spans naming
+    /// a documented type, like `String`, link to it.
+    fn markdown(&mut self, text: &str) {
+        let links = self.links;
+        let root = &self.root;
+        let events = Parser::new(text).map(|event| match event {
+            Event::Code(code) => match links.name(&code) {
+                Some(target) => Event::Html(
+                    format!(
+                        "<a href=\"{}{}\"><code>{}</code></a>",
+                        root,
+                        target,
+                        escape(&code)
+                    )
+                    .into(),
+                ),
+                None => Event::Code(code),
+            },
+            event => event,
+        });
+        self.out.push_str("<div class=\"docs\">\n");
+        html::push_html(&mut self.out, events);
+        self.out.push_str("</div>\n");
+    }
+
+    fn header(&mut self, title: &str) {
+        self.out.push_str(&format!(
+            "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+             <title>{}</title>\n<style>{}</style>\n</head>\n<body>\n\
+             <nav><a href=\"{}index.html\">Index</a></nav>\n<main>\n",
+            escape(title),
+            STYLE,
+            self.root
+        ));
+    }
+
+    fn footer(mut self) -> String {
+        self.out.push_str("</main>\n</body>\n</html>\n");
+        self.out
+    }
+}
+
+fn adt_keyword(adt: &ADT) -> &'static str {
+    match adt.ty {
+        ADTType::Class { .. } => "class",
+        ADTType::Interface => "interface",
+        ADTType::Enum { .. } => "enum",
+        ADTType::EnumCase { .. } => "case",
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+const STYLE: &str = "
+body { font-family: sans-serif; max-width: 60em; margin: auto; padding: 1em; line-height: 1.5; }
+pre, code { font-family: monospace; background: #f4f4f4; }
+pre { padding: 0.5em; overflow-x: auto; }
+pre.signature { font-weight: bold; }
+.name { color: #a0522d; }
+section { border-top: 1px solid #ddd; margin-top: 1.5em; }
+.member { margin-left: 2em; }
+a { color: #2a6db0; text-decoration: none; }
+";
@@ -0,0 +1,161 @@
+//! A documentation generator for gelix code.
+//! It renders the doc comments ('///') of all public declarations of
+//! a compiled program to a static HTML site, one page per module.
+
+mod comments;
+mod html;
+
+use crate::{comments::DocComments, html::PageWriter};
+use common::MutRc;
+use gir_generator::CompiledGIR;
+use gir_nodes::{
+    declaration::{ADTType, Visibility},
+    Declaration, Function, Module, ADT,
+};
+use std::{collections::HashMap, rc::Rc};
+
+/// A page of the documentation.
+#[derive(Debug)]
+pub struct Page {
+    /// The path of the page, relative to the root of the documentation.
+    pub path: String,
+    pub html: String,
+}
+
+/// Documents all modules starting with `root`, like 'std'.
+/// Returns the page of every module as well as an index page listing them.
+pub fn document(gir: &CompiledGIR, root: &str) -> Vec<Page> {
+    let mut modules = gir
+        .modules
+        .iter()
+        .filter(|module| module.borrow().path.index(0).map(|m| m.as_str()) == Some(root))
+        .map(|module| {
+            let docs = match &module.borrow().ast {
+                Some(ast) => DocComments::new(&ast.cst),
+                None => DocComments::default(),
+            };
+            (Rc::clone(module), docs)
+        })
+        .collect::<Vec<_>>();
+    modules.sort_by_key(|(module, _)| module.borrow().path.to_string());
+
+    let links = Links::new(&modules);
+    let mut pages = modules
+        .iter()
+        .map(|(module, docs)| {
+            let path = module_page(&module.borrow());
+            let html = PageWriter::new(&path, gir, &links).module(module, docs);
+            Page { path, html }
+        })
+        .collect::<Vec<_>>();
+
+    let index = PageWriter::new("index.html", gir, &links).index(root, &modules);
+    pages.push(Page {
+        path: "index.html".to_string(),
+        html: index,
+    });
+    pages
+}
+
+/// The locations of all documented types, used to link to them.
+pub(crate) struct Links {
+    /// Path and anchor of every ADT, by its address.
+    adts: HashMap<usize, String>,
+    /// Path and anchor of every ADT, by its name.
+    /// Used for names in doc comments.
+    names: HashMap<String, String>,
+}
+
+impl Links {
+    fn new(modules: &[(MutRc<Module>, DocComments)]) -> Self {
+        let mut links = Links {
+            adts: HashMap::new(),
+            names: HashMap::new(),
+        };
+        for (module, _) in modules {
+            let page = module_page(&module.borrow());
+            for adt in public_adts(&module.borrow()) {
+                let target = format!("{}#{}", page, anchor(&adt.borrow().name));
+                links.add(&adt, target.clone());
+                if let ADTType::Enum { cases } = &adt.borrow().ty {
+                    for case in cases.values() {
+                        links.add(case, format!("{}#{}", page, anchor(&case.borrow().name)));
+                    }
+                }
+            }
+        }
+        links
+    }
+
+    fn add(&mut self, adt: &MutRc<ADT>, target: String) {
+        self.names
+            .entry(adt.borrow().name.to_string())
+            .or_insert_with(|| target.clone());
+        self.adts.insert(Rc::as_ptr(adt) as usize, target);
+    }
+
+    /// Returns the path and anchor of the ADT, if it is documented.
+    pub(crate) fn adt(&self, adt: &MutRc<ADT>) -> Option<&str> {
+        self.adts
+            .get(&(Rc::as_ptr(adt) as usize))
+            .map(String::as_str)
+    }
+
+    /// Returns the path and anchor of the ADT with the given name, if it is documented.
+    /// The name can be a module path, like 'std/collections/Array'.
+    pub(crate) fn name(&self, name: &str) -> Option<&str> {
+        let name = name.rsplit('/').next().unwrap_or(name);
+        self.names.get(name).map(String::as_str)
+    }
+}
+
+/// Returns the path of the page of a module.
+fn module_page(module: &Module) -> String {
+    format!("{}.html", module.path)
+}
+
+/// Returns the HTML ID of a declaration with the given name.
+/// Names of enum cases contain their enum, separated by ':'.
+pub(crate) fn anchor(name: &str) -> String {
+    name.replace(':', ".")
+}
+
+/// Returns all public ADTs declared in the module, in order of their declaration.
+pub(crate) fn public_adts(module: &Module) -> Vec<MutRc<ADT>> {
+    let mut adts = module
+        .declarations
+        .values()
+        .filter_map(|decl| match decl {
+            Declaration::Adt(adt) => Some(Rc::clone(adt)),
+            _ => None,
+        })
+        .filter(|adt| {
+            let adt = adt.borrow();
+            adt.visibility == Visibility::Public
+                && adt.module.borrow().path == module.path
+                && !matches!(adt.ty, ADTType::EnumCase { .. })
+        })
+        .collect::<Vec<_>>();
+    adts.sort_by_key(|adt| adt.borrow().ast.cst.text_range().start);
+    adts
+}
+
+/// Returns all public functions declared in the module, in order of their declaration.
+pub(crate) fn public_functions(module: &Module) -> Vec<MutRc<Function>> {
+    let mut functions = module
+        .declarations
+        .values()
+        .filter_map(|decl| match decl {
+            Declaration::Function(func) => Some(Rc::clone(func)),
+            _ => None,
+        })
+        .filter(|func| {
+            let func = func.borrow();
+            func.visibility == Visibility::Public
+                && func.ast.is_some()
+                && func.module.borrow().path == module.path
+        })
+        .collect::<Vec<_>>();
+    functions.sort_by_key(|func| func.borrow().ast.as_ref().unwrap().cst.text_range().start);
+    functions
+}
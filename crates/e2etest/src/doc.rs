@@ -0,0 +1,70 @@
+//! Checks the documentation generated for the standard library.
+
+use gelixrs::{GIRFlags, Page};
+use std::path::PathBuf;
+
+fn page<'p>(pages: &'p [Page], path: &str) -> &'p str {
+    let page = pages.iter().find(|p| p.path == path);
+    &page.unwrap_or_else(|| panic!("Missing page {}", path)).html
+}
+
+fn assert_contains(html: &str, expected: &str) {
+    assert!(
+        html.contains(expected),
+        "Missing '{}' in:\n{}",
+        expected,
+        html
+    );
+}
+
+#[test]
+fn doc() {
+    let std = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../std");
+    let (code, errors) = gelixrs::parse_source(vec![std]);
+    assert!(errors.is_empty(), "Failed to parse");
+    let flags = GIRFlags {
+        library: true,
+        ..GIRFlags::default()
+    };
+    let gir = gelixrs::compile_gir(code, flags).expect("Failed to compile");
+    let pages = gelixrs::document(&gir, "std");
+
+    let index = page(&pages, "index.html");
+    assert_contains(index, "<a href=\"std/iter.html\">std/iter</a>");
+    // Module docs are separated from the code after them by an empty line
+    assert_contains(
+        index,
+        "All of the functions in this module are part of the LLVM IR",
+    );
+
+    let iter = page(&pages, "std/iter.html");
+    assert_contains(iter, "interface <span class=\"name\">Iter</span>[E]");
+    assert_contains(iter, "This interface allows a type to be iterated over");
+    assert_contains(iter, "class <span class=\"name\">Range</span>[T: Integer]");
+    // Type parameters of the ADT are not repeated on its members
+    assert_contains(iter, "func <span class=\"name\">next</span>() -&gt; E?");
+    assert_contains(
+        iter,
+        "<a href=\"../std/iter.html#Iter\"><code>Iter</code></a> for integers",
+    );
+
+    let string = page(&pages, "std/string.html");
+    let to_string = "<a href=\"../std/string.html#ToString\">ToString</a>";
+    assert_contains(string, &format!("impl {} for I64", to_string));
+    assert_contains(
+        string,
+        "impl <a href=\"../std/memory.html#Free\">Free</a> for <a href=\"../std/string.html#String\">String</a>",
+    );
+
+    let prelude = page(&pages, "std/prelude.html");
+    assert_contains(
+        prelude,
+        &format!(
+            "func <span class=\"name\">print</span>[S: {}](a: S)",
+            to_string
+        ),
+    );
+    assert_contains(prelude, "case <span class=\"name\">Ok</span>(ok: T)");
+    // Functions not visible outside of std are not documented
+    assert!(!page(&pages, "std/intrinsics.html").contains("gelixrs_get_type_size"));
+}
@@ -0,0 +1,27 @@
+//! Ensures that every explanation for `--explain` is complete.
+//! That every error code has an explanation is already ensured
+//! when compiling, see `crates/error/src/explain.rs`.
+
+#[test]
+fn every_explanation_has_examples() {
+    assert!(
+        gelixrs::EXPLANATIONS.len() > 50,
+        "Failed to find explanations"
+    );
+
+    for (code, explanation) in gelixrs::EXPLANATIONS {
+        assert_eq!(
+            explanation.matches("```gelix").count(),
+            2,
+            "Explanation of {} needs an erroneous and a fixed example.",
+            code
+        );
+    }
+}
+
+#[test]
+fn explain_ignores_case() {
+    assert_eq!(gelixrs::explain("e214"), gelixrs::explain("E214"));
+    assert!(gelixrs::explain("E214").is_some());
+    assert!(gelixrs::explain("E999").is_none());
+}
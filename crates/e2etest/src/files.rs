@@ -0,0 +1,24 @@
+//! Helpers shared by the tests running over the gelix corpus.
+
+use std::path::PathBuf;
+
+/// Returns all gelix files in the given directory of the repository, recursively.
+pub fn gelix_files(dir: &str) -> Vec<PathBuf> {
+    let mut root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    root.push("../..");
+    root.push(dir);
+
+    let mut files = Vec::new();
+    let mut dirs = vec![root];
+    while let Some(dir) = dirs.pop() {
+        for entry in dir.read_dir().expect("Failed to read directory") {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                dirs.push(path);
+            } else if path.extension() == Some("gel".as_ref()) {
+                files.push(path);
+            }
+        }
+    }
+    files
+}
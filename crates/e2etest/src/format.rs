@@ -0,0 +1,89 @@
+//! Runs the formatter over the whole corpus, ensuring that
+//! it is idempotent and never changes the meaning of code.
+
+mod files;
+
+use files::gelix_files;
+use gelixrs::{format, FormatConfig};
+use parser::{Node, NodeOrToken};
+use std::{fs, path::Path};
+use syntax::kind::SyntaxKind;
+
+/// Files with syntax errors that are not parser tests: benchmarks, which are
+/// not part of the test suite, and known failures of the test suite.
+static BROKEN_FILES: [&str; 8] = [
+    "benchmark/class_alloc.gel",
+    "benchmark/class_generic_instantiation.gel",
+    "benchmark/class_set.gel",
+    "closures/no_loop_leak.gel",
+    "closures/no_return_leak.gel",
+    "closures/no_weak.gel",
+    "generics/interface_with_class.gel",
+    "value/enum.gel",
+];
+
+/// Returns if the file is expected to contain syntax errors.
+fn has_syntax_errors(file: &Path, src: &str) -> bool {
+    src.starts_with("// P-ERR") || BROKEN_FILES.iter().any(|broken| file.ends_with(broken))
+}
+
+/// Returns all tokens of the given source except whitespace, sorted.
+/// Formatting may only move tokens around, never add, remove or change one.
+fn tokens(src: &str) -> Vec<String> {
+    fn collect(node: &Node, out: &mut Vec<String>) {
+        for child in node.children_with_tokens() {
+            match child {
+                NodeOrToken::Node(node) => collect(&node, out),
+                NodeOrToken::Token(token) if token.kind() != SyntaxKind::Whitespace => {
+                    out.push(token.text().trim_end().to_string())
+                }
+                _ => (),
+            }
+        }
+    }
+
+    let mut out = Vec::new();
+    collect(&parser::parse(src).root(), &mut out);
+    out.sort();
+    out
+}
+
+fn assert_formats(config: &FormatConfig) {
+    let files = [gelix_files("tests"), gelix_files("std")].concat();
+    assert!(files.len() > 100, "Failed to find test files");
+
+    for file in files {
+        let src = fs::read_to_string(&file).unwrap();
+        let name = file.display();
+        // Files with syntax errors cannot be formatted
+        let once = match (format(&src, config), has_syntax_errors(&file, &src)) {
+            (Ok(once), false) => once,
+            (Err(_), true) => continue,
+            (Ok(_), true) => panic!("Expected syntax errors when formatting {}.", name),
+            (Err(_), false) => panic!("Formatting {} failed with syntax errors.", name),
+        };
+
+        assert_eq!(
+            tokens(&src),
+            tokens(&once),
+            "Formatting {} changed its tokens.",
+            name
+        );
+        let twice = format(&once, config)
+            .unwrap_or_else(|_| panic!("Formatting {} produced syntax errors.", name));
+        assert_eq!(once, twice, "Formatting {} is not idempotent.", name);
+    }
+}
+
+#[test]
+fn format_is_idempotent() {
+    assert_formats(&FormatConfig::default());
+}
+
+#[test]
+fn format_is_idempotent_with_short_lines() {
+    assert_formats(&FormatConfig {
+        indent_width: 2,
+        line_length: 40,
+    });
+}
@@ -0,0 +1,57 @@
+//! Checks the symbol index of a multi-module program of the test corpus.
+
+use gelixrs::{GIRFlags, Symbol};
+use std::path::PathBuf;
+
+fn find<'s>(symbols: &'s [Symbol], name: &str, access: &str) -> Vec<&'s Symbol> {
+    symbols
+        .iter()
+        .filter(|s| s.name == name && s.access == access)
+        .collect()
+}
+
+#[test]
+fn index() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../..");
+    let modules = vec![root.join("tests/modules/mod_"), root.join("std")];
+    let (code, errors) = gelixrs::parse_source(modules.clone());
+    assert!(errors.is_empty(), "Failed to parse");
+    let flags = GIRFlags {
+        references: true,
+        ..GIRFlags::default()
+    };
+    let gir = gelixrs::compile_gir(code, flags).expect("Failed to compile");
+    let file = |path: &str| gelixrs::module_file(&modules, path).map(|f| f.display().to_string());
+    let symbols = gelixrs::index(&gir, "mod_", &file);
+
+    assert!(symbols.iter().all(|s| s.module.starts_with("mod_")));
+    for symbol in &symbols {
+        let file = symbol.file.as_ref().expect("Missing file");
+        assert!(file.ends_with(".gel"), "{}", file);
+    }
+
+    let sub = find(&symbols, "sub", "definition");
+    assert_eq!(sub.len(), 1);
+    assert_eq!(sub[0].kind, "function");
+    assert!(sub[0].ty.contains("func sub(a: I64, b: I64) -> I64"));
+    let calls = find(&symbols, "sub", "read");
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls[0].definition, sub[0].definition);
+    assert_eq!(calls[0].module, "mod_/main");
+    assert_eq!(calls[0].span.line_start, 5);
+
+    assert_eq!(find(&symbols, "Sub", "definition")[0].kind, "class");
+    assert_eq!(find(&symbols, "sup", "definition")[0].kind, "method");
+    assert_eq!(find(&symbols, "s", "definition")[0].kind, "local");
+
+    // 'print(s.a)' reads the field and 's.a = 4' writes it
+    let field = find(&symbols, "a", "definition");
+    let field = field.iter().find(|s| s.kind == "field").unwrap();
+    assert_eq!(field.ty, "I64");
+    let reads = find(&symbols, "a", "read");
+    let writes = find(&symbols, "a", "write");
+    assert!(reads.iter().any(|s| s.definition == field.definition));
+    assert_eq!(writes.len(), 1);
+    assert_eq!(writes[0].definition, field.definition);
+    assert_eq!(writes[0].span.line_start, 7);
+}
@@ -0,0 +1,103 @@
+//! Drives the language server over a multi-module program
+//! of the test corpus, checking the replies to common requests.
+
+use gelixrs_lsp::Server;
+use serde_json::{json, Value};
+use std::{fs, path::PathBuf};
+
+/// The root of the repository, which is opened as the workspace.
+fn root() -> PathBuf {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../..");
+    fs::canonicalize(root).expect("Failed to find repository")
+}
+
+fn uri(path: &PathBuf) -> String {
+    format!("file://{}", path.display())
+}
+
+/// Returns the position of the end of `needle` in the source.
+fn position_after(src: &str, needle: &str) -> Value {
+    let offset = src.find(needle).expect("Needle not in source") + needle.len();
+    let line = src[..offset].matches('\n').count();
+    let character = offset - src[..offset].rfind('\n').map_or(0, |i| i + 1);
+    json!({ "line": line, "character": character })
+}
+
+/// Sends a request and returns its result.
+fn request(server: &mut Server, method: &str, params: Value) -> Value {
+    let mut replies = server.handle(json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": method,
+        "params": params,
+    }));
+    assert_eq!(replies.len(), 1, "Expected a single reply to {}", method);
+    replies.remove(0)
+}
+
+#[test]
+fn lsp() {
+    let root = root();
+    let main = root.join("tests/modules/mod_/main.gel");
+    let src = fs::read_to_string(&main).unwrap();
+    let mut server = Server::default();
+
+    let init = request(&mut server, "initialize", json!({ "rootUri": uri(&root) }));
+    assert_eq!(init["result"]["capabilities"]["hoverProvider"], true);
+
+    let diagnostics = server.handle(json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/didOpen",
+        "params": {
+            "textDocument": { "uri": uri(&main), "languageId": "gelix", "version": 1, "text": src },
+        },
+    }));
+    assert!(!diagnostics.is_empty());
+    for notification in &diagnostics {
+        assert_eq!(notification["params"]["diagnostics"], json!([]));
+    }
+
+    let at = |needle| {
+        json!({
+            "textDocument": { "uri": uri(&main) },
+            "position": position_after(&src, needle),
+        })
+    };
+
+    let definition = request(&mut server, "textDocument/definition", at("val s = Su"));
+    let target = definition["result"]["uri"].as_str().unwrap();
+    assert!(target.ends_with("tests/modules/mod_/sub.gel"), "{}", target);
+
+    let hover = request(&mut server, "textDocument/hover", at("print(su"));
+    let contents = hover["result"]["contents"]["value"].as_str().unwrap();
+    assert!(
+        contents.contains("func sub(a: I64, b: I64) -> I64"),
+        "{}",
+        contents
+    );
+
+    let completion = request(&mut server, "textDocument/completion", at("    s."));
+    let labels = completion["result"].as_array().unwrap();
+    let labels: Vec<_> = labels
+        .iter()
+        .map(|i| i["label"].as_str().unwrap())
+        .collect();
+    assert!(
+        labels.contains(&"a") && labels.contains(&"sup"),
+        "{:?}",
+        labels
+    );
+
+    let symbols = request(
+        &mut server,
+        "textDocument/documentSymbol",
+        json!({ "textDocument": { "uri": uri(&main) } }),
+    );
+    assert_eq!(symbols["result"][0]["name"], "main");
+
+    let unknown = request(&mut server, "textDocument/unknown", json!({}));
+    assert_eq!(unknown["error"]["code"], -32601);
+
+    let shutdown = request(&mut server, "shutdown", Value::Null);
+    assert_eq!(shutdown["result"], Value::Null);
+}
@@ -0,0 +1,114 @@
+//! Ensures that the AST of code with syntax errors can be read,
+//! with missing parts of the tree replaced by placeholders.
+
+mod files;
+
+use ast::{walk_type, Expression, Module, Type, TypeE, Visitor};
+use common::ModPath;
+use files::gelix_files;
+use std::{fs, rc::Rc};
+use syntax::kind::SyntaxKind;
+
+fn module(src: &str) -> Module {
+    let parse = parser::parse(src);
+    assert!(parse.has_errors(), "Expected syntax errors in:\n{}", src);
+    Module::new(&ModPath::new(), &Rc::new(src.to_string()), parse)
+}
+
+/// Returns the types of all parameters of the first function.
+fn parameter_types(module: &Module) -> Vec<TypeE> {
+    let sig = module.functions().next().unwrap().sig();
+    let types = sig.parameters().map(|param| param._type().get());
+    types.collect()
+}
+
+fn assert_empty_ident(ty: TypeE) {
+    match ty {
+        TypeE::Ident(name) => assert_eq!(name, ""),
+        _ => panic!("Expected empty identifier"),
+    }
+}
+
+/// Reads every type of the tree, including nested ones.
+struct Types;
+
+impl Visitor for Types {
+    fn visit_type(&mut self, node: &Type) {
+        node.get();
+        walk_type(self, node)
+    }
+}
+
+#[test]
+fn visitor_walks_code_with_syntax_errors() {
+    let files = gelix_files("tests");
+    let mut broken = 0;
+    for file in files {
+        let src = fs::read_to_string(&file).unwrap();
+        if !parser::parse(&src).has_errors() {
+            continue;
+        }
+        broken += 1;
+        Types.visit_module(&module(&src));
+    }
+    assert!(broken > 10, "Failed to find test files with syntax errors");
+}
+
+#[test]
+fn missing_expression_is_empty_block() {
+    let module = module("func main() {\n    val a = 1 +\n}\n");
+    let body = module.functions().next().unwrap().body().unwrap();
+    let variable = match body {
+        Expression::Block(block) => block.expressions().next().unwrap(),
+        _ => panic!("Expected block"),
+    };
+    let binary = match variable {
+        Expression::VarDef(var) => var.initializer(),
+        _ => panic!("Expected variable"),
+    };
+    let binary = match binary {
+        Expression::Binary(binary) => binary,
+        _ => panic!("Expected binary expression"),
+    };
+
+    match binary.right() {
+        Expression::Block(block) => {
+            assert_eq!(block.cst.kind(), SyntaxKind::ErrorNode);
+            assert_eq!(block.expressions().count(), 0);
+            // Placed at the end of its parent
+            let end = binary.cst.text_range().end;
+            assert_eq!(block.cst.text_range(), end..end);
+        }
+        _ => panic!("Expected missing expression to be a block"),
+    }
+}
+
+#[test]
+fn missing_type_is_empty_identifier() {
+    let module = module("func f(a: , b: i64) {}");
+    let mut types = parameter_types(&module).into_iter();
+    assert_empty_ident(types.next().unwrap());
+    match types.next().unwrap() {
+        TypeE::Ident(name) => assert_eq!(name, "i64"),
+        _ => panic!("Expected identifier"),
+    }
+}
+
+#[test]
+fn malformed_types_contain_empty_identifiers() {
+    let module = module("func f(a: Box[], b: *, c: ?) {}");
+    let mut types = parameter_types(&module).into_iter();
+    match types.next().unwrap() {
+        TypeE::Generic { ident, types } => {
+            assert_eq!(ident, "Box");
+            assert_eq!(types.len(), 1);
+            assert_empty_ident(types[0].get());
+        }
+        _ => panic!("Expected generic type"),
+    }
+    match types.next().unwrap() {
+        TypeE::RawPtr(inner) => assert_empty_ident(inner.get()),
+        _ => panic!("Expected raw pointer"),
+    }
+    assert_empty_ident(types.next().unwrap());
+}
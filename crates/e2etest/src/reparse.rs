@@ -0,0 +1,142 @@
+//! Ensures that incremental reparsing produces the same tree and errors
+//! as parsing the edited source from scratch.
+
+mod files;
+
+use files::gelix_files;
+use parser::{reparse, ParseResult, TextEdit};
+use std::fs;
+
+/// Snippets inserted by random edits, chosen to open and close
+/// all kinds of constructs that span multiple tokens.
+static SNIPPETS: [&str; 24] = [
+    "{",
+    "}",
+    "(",
+    ")",
+    "[",
+    "]",
+    "\"",
+    "/*",
+    "*/",
+    "//",
+    "\n",
+    " ",
+    ".",
+    "..",
+    "=",
+    "1",
+    "1.5",
+    "a",
+    "func f() ",
+    "val x = ",
+    "class C {",
+    "if (a) ",
+    "when (a) { ",
+    "-> ",
+];
+
+/// A small xorshift generator, keeping the edits the same on every run.
+struct Random(u64);
+
+impl Random {
+    fn below(&mut self, max: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % max.max(1) as u64) as usize
+    }
+}
+
+fn random_edit(src: &str, random: &mut Random) -> TextEdit {
+    let mut start = random.below(src.len() + 1);
+    while !src.is_char_boundary(start) {
+        start -= 1;
+    }
+    let mut end = (start + random.below(8)).min(src.len());
+    while !src.is_char_boundary(end) {
+        end -= 1;
+    }
+
+    let text = if random.below(4) == 0 {
+        // Text from elsewhere in the file
+        let from = random.below(src.len());
+        src.get(from..(from + random.below(12)).min(src.len()))
+            .unwrap_or_default()
+            .to_string()
+    } else {
+        SNIPPETS[random.below(SNIPPETS.len())].to_string()
+    };
+    TextEdit {
+        range: start..end,
+        text,
+    }
+}
+
+fn assert_same(reparsed: &ParseResult, src: &str, name: &str) {
+    let full = parser::parse(src);
+    assert_eq!(
+        format!("{:?}", reparsed),
+        format!("{:?}", full),
+        "Reparse of {} differs from a full parse of:\n{}",
+        name,
+        src
+    );
+}
+
+#[test]
+fn reparse_equals_full_parse_on_random_edits() {
+    let files = [gelix_files("tests"), gelix_files("std")].concat();
+    assert!(files.len() > 100, "Failed to find test files");
+
+    let mut random = Random(0x2545_f491_4f6c_dd1d);
+    for file in files {
+        let mut src = fs::read_to_string(&file).unwrap();
+        let mut result = parser::parse(&src);
+        // Edits are applied one after another, so later ones
+        // also run on trees that were reparsed before
+        for step in 0..20 {
+            let edit = random_edit(&src, &mut random);
+            src.replace_range(edit.range.clone(), &edit.text);
+            result = reparse(result, &edit);
+            let name = format!("{} (edit {}: {:?})", file.display(), step, edit);
+            assert_same(&result, &src, &name);
+        }
+    }
+}
+
+#[test]
+fn reparse_reuses_unaffected_declarations() {
+    let src = "func a() {\n    print(1)\n}\n\nfunc b() {\n    print(2)\n}\n";
+    let previous = parser::parse(src);
+    let old: Vec<_> = previous.tree().children().collect();
+
+    // Edits inside a block of the second function leave the first one untouched
+    let offset = src.find("2").unwrap();
+    let edit = TextEdit {
+        range: offset..offset + 1,
+        text: "2 + 3".to_string(),
+    };
+    let result = reparse(previous, &edit);
+    let mut edited = src.to_string();
+    edited.replace_range(edit.range.clone(), &edit.text);
+    assert_same(&result, &edited, "two functions");
+
+    let new: Vec<_> = result.tree().children().collect();
+    assert!(new[0] == old[0], "First function was not reused");
+    assert!(new[1] != old[1], "Second function was not reparsed");
+}
+
+#[test]
+fn reparse_ignores_edits_outside_of_source() {
+    let src = "func a() {\n    print(\"é\")\n}\n";
+    let inside_char = src.find('é').unwrap() + 1;
+    for range in [40..42, 11..10, inside_char..inside_char + 1].iter() {
+        let edit = TextEdit {
+            range: range.clone(),
+            text: "1".to_string(),
+        };
+        let result = reparse(parser::parse(src), &edit);
+        assert_same(&result, src, &format!("invalid edit {:?}", range));
+    }
+}
@@ -0,0 +1,42 @@
+//! Ensures that the CST is lossless: printing the tree of any
+//! source file must reproduce it exactly, even if it contains syntax errors.
+
+mod files;
+
+use files::gelix_files;
+use std::fs;
+
+fn assert_roundtrip(src: &str, name: &str) {
+    let root = parser::parse(src).root();
+    assert_eq!(root.text(), src, "Tree of {} is not lossless.", name);
+    assert_eq!(
+        root.text_range(),
+        0..src.len() as u32,
+        "Tree of {} does not span the whole source.",
+        name
+    );
+}
+
+#[test]
+fn parse_then_print_is_identity() {
+    let files = [gelix_files("tests"), gelix_files("std")].concat();
+    assert!(files.len() > 100, "Failed to find test files");
+
+    for file in files {
+        let src = fs::read_to_string(&file).unwrap();
+        assert_roundtrip(&src, &file.display().to_string());
+    }
+}
+
+#[test]
+fn parse_then_print_is_identity_on_broken_code() {
+    for file in gelix_files("tests") {
+        let src = fs::read_to_string(&file).unwrap();
+        // Cutting off the source at every line produces
+        // all kinds of unclosed constructs
+        for (cut, _) in src.match_indices('\n') {
+            let name = format!("{} (cut at {})", file.display(), cut);
+            assert_roundtrip(&src[..cut], &name);
+        }
+    }
+}
@@ -11,7 +11,10 @@ use std::{
 
 use ansi_term::{Color, Style};
 use common::bench;
-use gelixrs::{ir_context, CompiledGIR, CompiledIR, Context, Errors, GIRFlags, BENCH};
+use error::{ErrorSpan, SourceMap};
+use gelixrs::{
+    ir_context, CompiledGIR, CompiledIR, Context, Errors, GIRFlags, Lint, LintLevel, BENCH,
+};
 use lazy_static::lazy_static;
 use std::{io::Write, panic::AssertUnwindSafe};
 use structopt::StructOpt;
@@ -43,6 +46,8 @@ enum Failure {
     Subprocess,
     Segfault,
     Leak(usize),
+    /// Errors were reported at other locations than expected.
+    Locations(Vec<String>),
 }
 
 impl PartialEq for Failure {
@@ -213,6 +218,7 @@ fn run_test(path: PathBuf, run: &mut TestRun) {
 
     let expected = get_expected_result(path.clone());
     let result = catch_unwind_silent(|| exec(path.clone(), run)).unwrap_or(Err(Failure::Panic));
+    let result = check_error_locations(&path, result);
 
     if result == expected {
         print!("{}", GREEN_BOLD.paint("."));
@@ -232,17 +238,26 @@ fn run_test(path: PathBuf, run: &mut TestRun) {
 fn exec(path: PathBuf, run: &mut TestRun) -> TestRes {
     clear_state();
 
-    let gir = if run.options.no_cache {
-        let code = gelixrs::parse_source(vec![path, std_mod()]).map_err(Failure::Parse)?;
-        gelixrs::compile_gir(code, GIRFlags::default())
+    let flags = get_flags(&path);
+    // Code with syntax errors is still compiled to ensure
+    // the compiler does not crash on the partial tree
+    let (gir, syntax_errors) = if run.options.no_cache {
+        let (code, syntax_errors) = gelixrs::parse_source(vec![path, std_mod()]);
+        (gelixrs::compile_gir(code, flags), syntax_errors)
     } else {
         maybe_compile_stdlib(run)?;
         let std = run.gir_stdlib.as_ref().unwrap();
 
-        let code = gelixrs::parse_source(vec![path]).map_err(Failure::Parse)?;
-        gelixrs::compile_gir_cached_std(code, std, GIRFlags::default())
+        let (code, syntax_errors) = gelixrs::parse_source(vec![path]);
+        (
+            gelixrs::compile_gir_cached_std(code, std, flags),
+            syntax_errors,
+        )
+    };
+    if !syntax_errors.is_empty() {
+        return Err(Failure::Parse(syntax_errors));
     }
-    .map_err(Failure::Compile)?;
+    let gir = gir.map_err(Failure::Compile)?;
     let module = gelixrs::compile_ir(run.ir_context.clone(), gir);
 
     if !run.options.no_jit {
@@ -308,9 +323,61 @@ fn get_expected_result(mut path: PathBuf) -> TestRes {
     }
 }
 
+/// Checks that a test failing with errors reported them at exactly the
+/// locations given by lines like '// error: 4:9' (line and column), if it has any.
+fn check_error_locations(path: &PathBuf, result: TestRes) -> TestRes {
+    let code = read_to_string(path).unwrap_or_default();
+    let mut expected = code
+        .lines()
+        .filter_map(|l| l.strip_prefix("// error: "))
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    let errors = match &result {
+        Err(Failure::Parse(errors)) | Err(Failure::Compile(errors)) if !expected.is_empty() => {
+            errors
+        }
+        _ => return result,
+    };
+
+    let mut found = Vec::new();
+    for module in errors {
+        let src = module.src.as_ref().map_or("", |src| src.as_str());
+        let map = SourceMap::new(src);
+        for err in &module.errors {
+            if let ErrorSpan::Span(span) = &err.index {
+                let (line, col) = map.line_col(span.start as usize);
+                found.push(format!("{}:{}", line, col));
+            }
+        }
+    }
+
+    expected.sort();
+    found.sort();
+    if found == expected {
+        result
+    } else {
+        Err(Failure::Locations(found))
+    }
+}
+
+/// Returns the flags to compile a test with. Tests can deny
+/// lints with lines like '// deny: unused_variable'.
+fn get_flags(path: &PathBuf) -> GIRFlags {
+    let mut flags = GIRFlags::default();
+    let code = read_to_string(path).unwrap_or_default();
+    for name in code.lines().filter_map(|l| l.strip_prefix("// deny: ")) {
+        let lint = Lint::find(name).expect("Unknown lint in test");
+        flags.lints.set(lint, LintLevel::Deny);
+    }
+    flags
+}
+
 fn maybe_compile_stdlib(run: &mut TestRun) -> Result<(), Failure> {
     if run.gir_stdlib.is_none() {
-        let code = gelixrs::parse_source(vec![std_mod()]).map_err(Failure::Parse)?;
+        let (code, syntax_errors) = gelixrs::parse_source(vec![std_mod()]);
+        if !syntax_errors.is_empty() {
+            return Err(Failure::Parse(syntax_errors));
+        }
         let flags = GIRFlags {
             library: true,
             ..GIRFlags::default()
@@ -0,0 +1,125 @@
+//! Ensures that the generated AST visitor reaches every expression
+//! of the corpus, and that rewrites produce consistent trees.
+
+mod files;
+
+use ast::{walk_expression, CSTNode, Expression, Literal, Module, Rewriter, Visitor};
+use common::ModPath;
+use files::gelix_files;
+use std::{fs, rc::Rc};
+use syntax::kind::SyntaxKind;
+
+fn module(src: &str) -> Module {
+    let parse = parser::parse(src);
+    assert!(!parse.has_errors(), "Failed to parse:\n{}", src);
+    Module::new(&ModPath::new(), &Rc::new(src.to_string()), parse)
+}
+
+/// Collects all expressions, in the order they are visited.
+#[derive(Default)]
+struct Expressions(Vec<Expression>);
+
+impl Visitor for Expressions {
+    fn visit_expression(&mut self, node: &Expression) {
+        self.0.push(node.clone());
+        walk_expression(self, node)
+    }
+}
+
+/// Returns all nodes of the tree that are expressions, except for identifiers,
+/// which can also be names of declarations.
+fn cst_expressions(node: &CSTNode, out: &mut Vec<CSTNode>) {
+    for child in node.children() {
+        // Members of enum cases ('A(val a: I64)') are variables, but not expressions
+        let is_case_member = node.kind() == SyntaxKind::EnumCase;
+        if child.kind() != SyntaxKind::Ident
+            && !is_case_member
+            && Expression::cast(child.clone()).is_some()
+        {
+            out.push(child.clone());
+        }
+        cst_expressions(&child, out);
+    }
+}
+
+fn spans(nodes: impl Iterator<Item = CSTNode>) -> Vec<(u32, u32, SyntaxKind)> {
+    let mut spans = nodes
+        .map(|node| (node.text_range().start, node.text_range().end, node.kind()))
+        .collect::<Vec<_>>();
+    spans.sort();
+    spans
+}
+
+#[test]
+fn visitor_walks_every_expression_once() {
+    let files = [gelix_files("tests"), gelix_files("std")].concat();
+    assert!(files.len() > 100, "Failed to find test files");
+
+    for file in files {
+        let src = fs::read_to_string(&file).unwrap();
+        if parser::parse(&src).has_errors() {
+            continue;
+        }
+        let module = module(&src);
+
+        let mut visitor = Expressions::default();
+        visitor.visit_module(&module);
+        let visited = visitor
+            .0
+            .iter()
+            .map(Expression::cst)
+            .filter(|node| node.kind() != SyntaxKind::Ident);
+
+        let mut expected = Vec::new();
+        cst_expressions(&module.cst, &mut expected);
+        assert_eq!(
+            spans(visited),
+            spans(expected.into_iter()),
+            "Visitor did not walk all expressions of {}",
+            file.display()
+        );
+    }
+}
+
+/// Replaces all literals '1' with another literal.
+struct ReplaceOnes(Literal);
+
+impl Rewriter for ReplaceOnes {
+    fn rewrite_literal(&mut self, node: Literal) -> Literal {
+        if node.cst.text().trim() == "1" {
+            self.0.clone()
+        } else {
+            node
+        }
+    }
+}
+
+#[test]
+fn rewriter_replaces_nodes_and_moves_spans() {
+    let src =
+        "class A {\n    val a = 1\n}\n\nfunc main() {\n    val b = 1 + 2\n    print(b + 1)\n}\n";
+    let replacement = module("func f() { g(100) }");
+    let mut literals = Expressions::default();
+    literals.visit_module(&replacement);
+    let literal = literals.0.into_iter().find_map(|expr| match expr {
+        Expression::Literal(literal) => Some(literal),
+        _ => None,
+    });
+
+    let mut rewriter = ReplaceOnes(literal.unwrap());
+    let rewritten = rewriter.rewrite_module(module(src));
+    let expected = src.replace("1", "100");
+    assert_eq!(rewritten.cst.text(), expected);
+    // The spans of all nodes must be the same as when parsing the new source
+    assert_eq!(
+        format!("{:?}", rewritten.cst),
+        format!("{:?}", parser::parse(&expected).root())
+    );
+
+    // Trees without any rewritten node are kept as they are
+    struct Unchanged;
+    impl Rewriter for Unchanged {}
+    let original = module(src);
+    let cst = original.cst.clone();
+    assert!(Unchanged.rewrite_module(original).cst == cst);
+}
@@ -0,0 +1,41 @@
+//! Longer explanations of every error code, shown by `--explain`.
+//! Each explanation is a markdown file in the `explanations` directory
+//! with a description, an erroneous example and a fixed example.
+
+use crate::GErr;
+
+macro_rules! explanations {
+    ($($code:ident),* $(,)?) => {
+        /// All error codes with their explanation.
+        pub const EXPLANATIONS: &[(&str, &str)] = &[
+            $((stringify!($code), include_str!(concat!("../explanations/", stringify!($code), ".md"))),)*
+        ];
+
+        /// Only compiles if the codes are exactly the variants of [GErr],
+        /// so new codes cannot be added without an explanation.
+        #[allow(dead_code)]
+        fn explained(err: &GErr) {
+            match err {
+                $(GErr::$code { .. })|* => (),
+            }
+        }
+    };
+}
+
+explanations!(
+    E001, E002, E003, E004, E005, E006, E007, E008, E100, E101, E102, E103, E200, E201, E202, E203,
+    E204, E205, E206, E207, E208, E209, E210, E211, E212, E213, E214, E215, E216, E218, E219, E220,
+    E221, E222, E223, E224, E225, E227, E228, E229, E230, E231, E232, E233, E234, E235, E236, E237,
+    E238, E239, E240, E241, E242, E243, E244, E245, E246, E247, E248, E249, E250, E251, E252, E253,
+    E300, E301, E302, E303, E304, E305, E306, E307, E308, E309, E310, E311, E312, E313, E314, E315,
+    E316, E317, E318, E319, E320, E321, E322, W001, W002, W003, W004, W005,
+);
+
+/// Returns the explanation of the given error code, like 'E214'.
+/// The code is case-insensitive.
+pub fn explain(code: &str) -> Option<&'static str> {
+    EXPLANATIONS
+        .iter()
+        .find(|(c, _)| c.eq_ignore_ascii_case(code))
+        .map(|(_, explanation)| *explanation)
+}
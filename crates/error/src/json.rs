@@ -0,0 +1,272 @@
+//! Machine-readable representation of errors, to be used by tools
+//! instead of parsing the output meant for humans.
+
+use crate::{Error, ErrorSpan, Errors, Note, Severity, SourceMap};
+use serde::Serialize;
+use std::ops::Range;
+
+/// A single error, in a format meant to be serialized.
+/// Errors of all stages of the compiler share this format.
+#[derive(Serialize, Debug)]
+pub struct Diagnostic {
+    /// The code of the error, like 'E100'.
+    pub code: String,
+    /// Either 'error' or 'warning'.
+    pub severity: &'static str,
+    pub message: String,
+    /// Path of the module the error occurred in.
+    pub module: String,
+    /// The file the error occurred in, if known.
+    pub file: Option<String>,
+    /// Location of the error; absent on errors without one,
+    /// like a missing main function.
+    pub span: Option<DiagnosticSpan>,
+    pub labels: Vec<DiagnosticLabel>,
+    pub notes: Vec<DiagnosticNote>,
+}
+
+/// A location in a source. Lines and columns start at 1,
+/// the end is exclusive. Columns count characters, not bytes.
+#[derive(Serialize, Debug)]
+pub struct DiagnosticSpan {
+    pub byte_start: usize,
+    pub byte_end: usize,
+    pub line_start: usize,
+    pub column_start: usize,
+    pub line_end: usize,
+    pub column_end: usize,
+}
+
+/// A secondary location of an error, see [crate::Label].
+#[derive(Serialize, Debug)]
+pub struct DiagnosticLabel {
+    pub message: String,
+    pub module: String,
+    pub file: Option<String>,
+    pub span: DiagnosticSpan,
+}
+
+/// A note of an error, see [Note].
+#[derive(Serialize, Debug)]
+pub struct DiagnosticNote {
+    /// Either 'note' or 'help'.
+    pub kind: &'static str,
+    pub message: String,
+}
+
+impl Diagnostic {
+    /// Creates a diagnostic from an error. `file` is used to
+    /// find the file of a module from its path.
+    fn new(
+        err: &Error,
+        map: Option<&SourceMap>,
+        module: &str,
+        file: &dyn Fn(&str) -> Option<String>,
+    ) -> Self {
+        let labels = err
+            .details
+            .labels
+            .iter()
+            .filter_map(|label| {
+                let (span, module) = match &label.source {
+                    Some((src, origin)) => (
+                        DiagnosticSpan::new(&label.span, &SourceMap::new(src)),
+                        origin.as_str(),
+                    ),
+                    None => (DiagnosticSpan::new(&label.span, map?), module),
+                };
+                Some(DiagnosticLabel {
+                    message: label.message.clone(),
+                    module: module.to_string(),
+                    file: file(module),
+                    span: span?,
+                })
+            })
+            .collect();
+
+        let notes = err
+            .details
+            .notes
+            .iter()
+            .map(|note| match note {
+                Note::Note(message) => DiagnosticNote {
+                    kind: "note",
+                    message: message.clone(),
+                },
+                Note::Help(message) => DiagnosticNote {
+                    kind: "help",
+                    message: message.clone(),
+                },
+            })
+            .collect();
+
+        Self {
+            code: err.kind.as_ref().to_string(),
+            severity: match err.severity {
+                Severity::Error => "error",
+                Severity::Warning => "warning",
+            },
+            message: err.kind.fmt(),
+            module: module.to_string(),
+            file: file(module),
+            span: map.and_then(|map| DiagnosticSpan::new(&err.index, map)),
+            labels,
+            notes,
+        }
+    }
+
+    /// Serializes the diagnostic into a single line of JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+}
+
+impl DiagnosticSpan {
+    /// Creates the span of the given byte range in a source.
+    pub fn from_range(span: Range<usize>, map: &SourceMap) -> Self {
+        let (line_start, column_start) = map.line_col(span.start);
+        let (line_end, column_end) = map.line_col(span.end);
+        Self {
+            byte_start: span.start,
+            byte_end: span.end,
+            line_start,
+            column_start,
+            line_end,
+            column_end,
+        }
+    }
+
+    fn new(span: &ErrorSpan, map: &SourceMap) -> Option<Self> {
+        if let ErrorSpan::None = span {
+            return None;
+        }
+        Some(Self::from_range(span.get_span(), map))
+    }
+}
+
+impl Errors {
+    /// Returns all errors as diagnostics. `file` should return
+    /// the file of the module with the given path, if known.
+    pub fn diagnostics(&self, file: &dyn Fn(&str) -> Option<String>) -> Vec<Diagnostic> {
+        let map = self.src.as_ref().map(|src| SourceMap::new(src));
+        self.errors
+            .iter()
+            .map(|err| Diagnostic::new(err, map.as_ref(), &self.origin, file))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Error, ErrorSpan, Errors, GErr, Severity};
+    use serde_json::{json, Value};
+    use std::rc::Rc;
+
+    fn file(module: &str) -> Option<String> {
+        Some(format!("{}.gel", module))
+    }
+
+    fn to_json(errors: &Errors) -> Vec<Value> {
+        errors
+            .diagnostics(&file)
+            .iter()
+            .map(|diagnostic| {
+                let json = diagnostic.to_json();
+                assert!(!json.contains('\n'), "Diagnostic is not a single line");
+                serde_json::from_str(&json).unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn diagnostic_with_labels_and_notes() {
+        let src = Rc::new("func a() {}\nfunc é() {}\nfunc é() {}\n".to_string());
+        let other = Rc::new("import a\nfunc é() {}\n".to_string());
+        let error = Error {
+            index: ErrorSpan::Span(30..32),
+            kind: GErr::E100("é".into()),
+            severity: Severity::Error,
+            details: Box::default(),
+        }
+        .with_label(ErrorSpan::Span(17..19), "first defined here")
+        .with_label_in(ErrorSpan::Span(14..16), "imported here", &other, "b".into())
+        .with_note("names must be unique")
+        .with_help("rename one of them");
+        let errors = Errors {
+            errors: vec![error],
+            src: Some(src),
+            origin: "a".into(),
+        };
+
+        let expected = json!({
+            "code": "E100",
+            "severity": "error",
+            "message": "Name é already defined in this module",
+            "module": "a",
+            "file": "a.gel",
+            "span": {
+                "byte_start": 30,
+                "byte_end": 32,
+                "line_start": 3,
+                "column_start": 6,
+                "line_end": 3,
+                "column_end": 7
+            },
+            "labels": [
+                {
+                    "message": "first defined here",
+                    "module": "a",
+                    "file": "a.gel",
+                    "span": {
+                        "byte_start": 17,
+                        "byte_end": 19,
+                        "line_start": 2,
+                        "column_start": 6,
+                        "line_end": 2,
+                        "column_end": 7
+                    }
+                },
+                {
+                    "message": "imported here",
+                    "module": "b",
+                    "file": "b.gel",
+                    "span": {
+                        "byte_start": 14,
+                        "byte_end": 16,
+                        "line_start": 2,
+                        "column_start": 6,
+                        "line_end": 2,
+                        "column_end": 7
+                    }
+                }
+            ],
+            "notes": [
+                { "kind": "note", "message": "names must be unique" },
+                { "kind": "help", "message": "rename one of them" }
+            ]
+        });
+        assert_eq!(to_json(&errors), vec![expected]);
+    }
+
+    #[test]
+    fn diagnostic_without_location() {
+        let warning = Error {
+            index: ErrorSpan::None,
+            kind: GErr::E100("a".into()),
+            severity: Severity::Warning,
+            details: Box::default(),
+        }
+        .with_label(ErrorSpan::Span(0..1), "dropped without a source");
+        let errors = Errors {
+            errors: vec![warning],
+            src: None,
+            origin: "a".into(),
+        };
+
+        let json = &to_json(&errors)[0];
+        assert_eq!(json["severity"], "warning");
+        assert_eq!(json["span"], Value::Null);
+        assert_eq!(json["labels"], json!([]));
+        assert_eq!(json["notes"], json!([]));
+    }
+}
@@ -1,3 +1,4 @@
+use crate::{Lint, Suggestion};
 use smol_str::SmolStr;
 use strum_macros::*;
 
@@ -33,9 +34,9 @@ pub enum GErr {
     // Could not find main function
     E101,
     // Unknown module
-    E102,
+    E102 { path: String, outside_root: bool },
     // Unknown declaration
-    E103,
+    E103(Suggestion),
 
     // Cannot assign to
     E200(&'static str),
@@ -50,7 +51,7 @@ pub enum GErr {
     // This variable may not be captured
     E205,
     // Undefined variable
-    E206(SmolStr),
+    E206(SmolStr, Suggestion),
     // Break is only allowed in loops
     E207,
     // Cannot redefine variable in same scope
@@ -61,7 +62,7 @@ pub enum GErr {
         was: String,
     },
     // Unknown field or method
-    E210,
+    E210(SmolStr, Suggestion),
     // Can only call generic methods directly
     E211,
     // Return expr was different than function ret type
@@ -95,7 +96,7 @@ pub enum GErr {
     E222,
     // Unknown enum case
     E223,
-    // Static access is only supported on enum types
+    // Static access is only supported on ADTs
     E224,
     // Static access is not supported on values
     E225,
@@ -133,9 +134,39 @@ pub enum GErr {
     E240,
     // '?.' can only be used with nullable values
     E241,
+    // Method is defined by multiple visible extensions
+    E242 {
+        name: SmolStr,
+        modules: Vec<SmolStr>,
+    },
+    // Unknown static method
+    E243(SmolStr),
+    // Unknown parameter name in named argument
+    E244(SmolStr),
+    // Parameter was given multiple arguments
+    E245(SmolStr),
+    // Range bounds must be integers
+    E246,
+    // Cannot use ranges outside of for loops with no_std enabled
+    E247,
+    // 'is' when branch not followed by a type
+    E248,
+    // Assignment undoing a smart cast made outside of the loop
+    E249(SmolStr),
+    // Path through function ends without a return value
+    E250,
+    // Local variable might be read before being assigned
+    E251(SmolStr),
+    // Parameter default value was the wrong type
+    E252 {
+        expected: String,
+        was: String,
+    },
+    // Positional argument after named argument
+    E253,
 
     // Unknown type
-    E300(String),
+    E300(String, Suggestion),
     // Cannot use function as type
     E301,
     // Nullable cannot be applied multiple times
@@ -187,6 +218,19 @@ pub enum GErr {
     E320,
     // Incorrect amount of type parameters
     E321,
+    // Static method in extension or interface impl
+    E322,
+
+    // Unused local variable
+    W001(SmolStr),
+    // Unused import
+    W002(SmolStr),
+    // Unused private function
+    W003(SmolStr),
+    // Unreachable code
+    W004,
+    // Variable shadows another variable
+    W005(SmolStr),
 }
 
 impl GErr {
@@ -196,14 +240,29 @@ impl GErr {
             E006 { modifier, on } => format!("Cannot have '{:?}' modifier on {}.", modifier, on),
 
             E100(name) => format!("Name {} already defined in this module", name),
+            E102 {
+                path,
+                outside_root: false,
+            } => format!("Unknown module '{}'.", path),
+            E102 {
+                path,
+                outside_root: true,
+            } => format!(
+                "Relative import leads outside of the root module (imported from '{}').",
+                path
+            ),
+            E103(suggestion) => format!("Unresolved import.{}", suggestion),
 
             E200(name) => format!("Cannot assign to {}", name),
-            E206(name) => format!("Variable '{}' is not defined", name),
+            E206(name, suggestion) => format!("Variable '{}' is not defined.{}", name, suggestion),
             E208(name) => format!("Cannot redefine variable '{}' in the same scope.", name),
             E209 { expected, was } => format!(
                 "Break expressions and for body must have same type (Expected {}, was {}).",
                 expected, was
             ),
+            E210(name, suggestion) => {
+                format!("Unknown field or method '{}'.{}", name, suggestion)
+            }
             E212 { expected, was } => format!(
                 "Return expression in function has wrong type (Expected {}, was {}).",
                 expected, was
@@ -229,8 +288,26 @@ impl GErr {
                 argument,
                 bound
             ),
+            E242 { name, modules } => {
+                let mut str = format!("Method '{}' is ambiguous; ", name);
+                str.push_str(&self.fmt_list("defined by extensions in modules ", modules));
+                str.push('.');
+                str
+            }
+            E243(name) => format!("Unknown static method '{}'.", name),
+            E244(name) => format!("Unknown parameter '{}'.", name),
+            E245(name) => format!("Parameter '{}' was given multiple arguments.", name),
+            E249(name) => format!(
+                "Cannot undo smart cast of '{}' here, as it was made outside of this loop.",
+                name
+            ),
+            E251(name) => format!("Variable '{}' might be read before it is assigned.", name),
+            E252 { expected, was } => format!(
+                "Default value is the wrong type (Expected {}, was {}).",
+                expected, was
+            ),
 
-            E300(name) => format!("Unknown type '{}'.", name),
+            E300(name, suggestion) => format!("Unknown type '{}'.{}", name, suggestion),
             E309(names) => {
                 let mut str = self.fmt_list(
                     "Cannot have uninitialized fields after constructor (Missing: ",
@@ -257,10 +334,27 @@ impl GErr {
                 expected, was
             ),
 
+            W001(name) => format!("Variable '{}' is never read.", name),
+            W002(name) => format!("Import '{}' is never used.", name),
+            W003(name) => format!("Private function '{}' is never used.", name),
+            W005(name) => format!("Variable '{}' shadows another variable.", name),
+
             _ => self.msg().to_string(),
         }
     }
 
+    /// The lint that produced this error, if it is a warning.
+    pub fn lint(&self) -> Option<Lint> {
+        Some(match self {
+            W001(_) => Lint::UnusedVariable,
+            W002(_) => Lint::UnusedImport,
+            W003(_) => Lint::UnusedFunction,
+            W004 => Lint::UnreachableCode,
+            W005(_) => Lint::ShadowedVariable,
+            _ => return None,
+        })
+    }
+
     fn fmt_list(&self, start: &str, list: &[SmolStr]) -> String {
         let mut buf = start.to_string();
         buf.push_str(&list[0]);
@@ -280,8 +374,6 @@ impl GErr {
             E008 => "Expected expression.",
 
             E101 => "Could not find main function.",
-            E102 => "Unknown module.",
-            E103 => "Unresolved import.",
 
             E201 => "Value is a different type than assignment target.",
             E202 => "No implementation of operator found for types.",
@@ -289,7 +381,6 @@ impl GErr {
             E204 => "Fields cannot be called.",
             E205 => "This variable may not be captured (weak reference)",
             E207 => "Break is only allowed in loops.",
-            E210 => "Unknown field or method.",
             E211 => "Can only call generic methods directly.",
             E213 => "Cannot use type arguments on local variables.",
             E214 => "Cannot infer types (please specify explicitly).",
@@ -298,7 +389,7 @@ impl GErr {
             E221 => "Cannot get ADT method (must be called).",
             E222 => "Cannot get uninitialized ADT member.",
             E223 => "Unknown enum case.",
-            E224 => "Static access is only supported on enum types.",
+            E224 => "Static access is only supported on classes, interfaces and enums.",
             E225 => "Static access is not supported on values.",
             E227 => "'!' can only be used on boolean values.",
             E228 => "'-' can only be used on signed integers and floats.",
@@ -312,6 +403,11 @@ impl GErr {
             E238 => "Cannot use string literals with no_std enabled.",
             E240 => "Field is not visible.",
             E241 => "'?.' can only be used with nullable values.",
+            E246 => "Range bounds must be integers of the same type.",
+            E247 => "Cannot use ranges outside of for loops with no_std enabled.",
+            E248 => "'is' in when branches must be followed by a type.",
+            E250 => "Missing return value; function can end here without one.",
+            E253 => "Positional arguments cannot follow named arguments.",
 
             E301 => "Functions cannot be used as types.",
             E302 => "Nullable cannot be applied multiple times.",
@@ -329,6 +425,9 @@ impl GErr {
             E319 => "Method with same name already defined.",
             E320 => "Cannot use data cases with enums that have fields.",
             E321 => "Incorrect amount of type parameters.",
+            E322 => "Static methods can only be declared in impls on a type of the same module.",
+
+            W004 => "Unreachable code; the expression before always returns or breaks.",
 
             _ => unreachable!(),
         }
@@ -4,7 +4,13 @@
  * This file is under the Apache 2.0 license. See LICENSE in the root of this repository for details.
  */
 
+mod explain;
+mod json;
 mod kinds;
+mod lints;
+mod render;
+mod source_map;
+mod suggest;
 
 use std::{
     fmt::{Display, Error as FmtErr, Formatter},
@@ -13,13 +19,17 @@ use std::{
 };
 
 use ansi_term::{
-    ANSIString, ANSIStrings,
-    Color::{Blue, Red},
+    Color::{Blue, Red, Yellow},
     Style,
 };
+pub use explain::{explain, EXPLANATIONS};
+pub use json::{Diagnostic, DiagnosticLabel, DiagnosticNote, DiagnosticSpan};
 pub use kinds::GErr;
-use lexer::{Lexer, Span};
+use lexer::Span;
+pub use lints::{Lint, LintLevel, LintLevels};
+pub use source_map::SourceMap;
 use std::fmt::Debug;
+pub use suggest::Suggestion;
 
 pub type Res<T> = Result<T, Error>;
 
@@ -33,11 +43,24 @@ pub struct Errors {
     pub origin: String,
 }
 
+impl Errors {
+    /// Returns if any of the errors is not a warning,
+    /// meaning compilation failed.
+    pub fn has_errors(&self) -> bool {
+        self.errors.iter().any(|e| e.severity == Severity::Error)
+    }
+}
+
 impl Display for Errors {
     fn fmt(&self, f: &mut Formatter) -> Result<(), FmtErr> {
-        writeln!(f, "Errors inside {}:", self.origin)?;
+        if self.has_errors() {
+            writeln!(f, "Errors inside {}:", self.origin)?;
+        } else {
+            writeln!(f, "Warnings inside {}:", self.origin)?;
+        }
+        let map = self.src.as_ref().map(|src| SourceMap::new(src));
         for err in &self.errors {
-            writeln!(f, "{}\n", err.to_string(self.src.as_ref(), &self.origin))?;
+            writeln!(f, "{}\n", err.to_string(map.as_ref(), &self.origin))?;
         }
         Ok(())
     }
@@ -50,107 +73,131 @@ impl Debug for Errors {
 }
 
 /// An error produced by all parts of the compiler.
+/// Errors with warning severity do not fail compilation.
 #[derive(Debug)]
 pub struct Error {
     pub index: ErrorSpan,
     pub kind: GErr,
+    pub severity: Severity,
+    /// Labels and notes of the error. Boxed since most errors
+    /// have none, keeping results containing an error small.
+    pub details: Box<Details>,
+}
+
+/// Secondary information attached to an error.
+#[derive(Debug, Default)]
+pub struct Details {
+    /// Secondary locations relevant to the error,
+    /// like the original definition of a duplicate.
+    pub labels: Vec<Label>,
+    /// Additional messages shown below the error.
+    pub notes: Vec<Note>,
+}
+
+/// The severity of an error.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// A secondary location shown alongside an error, with a message.
+#[derive(Debug)]
+pub struct Label {
+    pub span: ErrorSpan,
+    pub message: String,
+    /// The source and origin of the location,
+    /// if it is not in the same source as the error itself.
+    pub source: Option<(Rc<String>, String)>,
+}
+
+/// A free-form message attached to an error.
+#[derive(Debug)]
+pub enum Note {
+    /// Additional information on the error.
+    Note(String),
+    /// A hint on how to fix the error.
+    Help(String),
 }
 
 impl Error {
+    /// Adds a label in the same source as the error.
+    pub fn with_label(mut self, span: ErrorSpan, message: impl Into<String>) -> Self {
+        self.details.labels.push(Label {
+            span,
+            message: message.into(),
+            source: None,
+        });
+        self
+    }
+
+    /// Adds a label in the given source, which may differ from the error's.
+    pub fn with_label_in(
+        mut self,
+        span: ErrorSpan,
+        message: impl Into<String>,
+        src: &Rc<String>,
+        origin: String,
+    ) -> Self {
+        self.details.labels.push(Label {
+            span,
+            message: message.into(),
+            source: Some((Rc::clone(src), origin)),
+        });
+        self
+    }
+
+    /// Adds a note to the error.
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.details.notes.push(Note::Note(note.into()));
+        self
+    }
+
+    /// Adds a help message to the error.
+    pub fn with_help(mut self, help: impl Into<String>) -> Self {
+        self.details.notes.push(Note::Help(help.into()));
+        self
+    }
+
     /// Produces a nice looking string representation to be shown to the user.
-    pub fn to_string<'a>(&self, source: Option<&'a Rc<String>>, origin: &str) -> String {
-        let regular = Style::new();
-        let bold = regular.bold();
-        let dimmed = regular.dimmed();
-        let italic = regular.italic();
-        let red_ul = Red.underline();
-
-        if let Some(source) = source {
-            let span = self.index.get_span(source);
-            let (line, start, len) = span_to_info(source, span);
-
-            let result = format!(
-                "\n{}: {}\n{} {} L{}:{}",
-                Red.bold().paint(format!("Error[{}]", self.kind.as_ref())),
-                bold.paint(&self.kind.fmt()),
-                Blue.dimmed().paint("-->"),
-                italic.paint(origin),
-                line,
-                start
-            );
-
-            let prev_line = source.lines().nth(line.wrapping_sub(2)).unwrap_or("");
-            let next_line = source.lines().nth(line).unwrap_or("");
-            let line_str = source
-                .lines()
-                .nth(line.wrapping_sub(1))
-                .unwrap_or("<unexpected end of file>");
-
-            let line_start = line_str.chars().take(start - 1).collect::<String>();
-            let line_marked = line_str
-                .chars()
-                .skip(start - 1)
-                .take(len)
-                .collect::<String>();
-            let line_end = line_str.chars().skip(start + len - 1).collect::<String>();
-
-            let formatted: &[ANSIString<'a>] = &[
-                regular.paint(result),
-                dimmed.paint(format!("\n     |\n{:4} | ", line - 1)),
-                regular.paint(prev_line),
-                dimmed.paint(format!("\n{:4} | ", line)),
-                regular.paint(line_start),
-                red_ul.paint(line_marked),
-                regular.paint(line_end),
-                dimmed.paint(format!("\n{:4} | ", line + 1)),
-                regular.paint(next_line),
-                dimmed.paint("\n     |"),
-            ];
-
-            ANSIStrings(formatted).to_string()
-        } else {
-            format!(
-                "\n{}: {}\n{} {}",
-                Red.bold().paint(format!("Error[{}]", self.kind.as_ref())),
-                bold.paint(&self.kind.fmt()),
+    /// `source` is the map of the source the error occurred in, if any.
+    pub fn to_string(&self, source: Option<&SourceMap>, origin: &str) -> String {
+        let (color, label) = match self.severity {
+            Severity::Error => (Red, "Error"),
+            Severity::Warning => (Yellow, "Warning"),
+        };
+        let mut out = format!(
+            "\n{}: {}",
+            color
+                .bold()
+                .paint(format!("{}[{}]", label, self.kind.as_ref())),
+            Style::new().bold().paint(&self.kind.fmt()),
+        );
+
+        match source {
+            Some(source) => render::snippets(&mut out, self, color, source, origin),
+            None => out.push_str(&format!(
+                "\n{} {}",
                 Blue.dimmed().paint("-->"),
-                italic.paint(origin),
-            )
+                Style::new().italic().paint(origin)
+            )),
         }
-    }
-}
 
-fn span_to_info(src: &str, span: Span) -> (usize, usize, usize) {
-    let (line, line_offset) = src[0..span.start]
-        .lines()
-        .rev()
-        .skip(1)
-        .fold((0, 0), |(lc, offs), line| (lc + 1, offs + line.len() + 1));
-    (
-        line + 1,
-        span.start - line_offset + 1,
-        span.end - span.start,
-    )
+        render::notes(&mut out, &self.details.notes);
+        out
+    }
 }
 
+/// The location of an error, as a byte range in its source.
 #[derive(Debug)]
 pub enum ErrorSpan {
-    Token(usize),
     Span(Range<u32>),
     None,
 }
 
 impl ErrorSpan {
-    fn get_span(&self, src: &str) -> Span {
+    pub(crate) fn get_span(&self) -> Span {
         match self {
-            Self::Token(index) => {
-                let mut lex = Lexer::new(src);
-                for _ in 0..(*index + 1) {
-                    lex.next();
-                }
-                lex.span()
-            }
-
             Self::Span(span) => (span.start as usize)..(span.end as usize),
 
             Self::None => panic!("Not supposed to have a source"),
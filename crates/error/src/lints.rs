@@ -0,0 +1,132 @@
+use std::ops::Range;
+
+/// A lint is a check for code that compiles, but is likely a mistake.
+/// Lints produce warnings, which do not fail compilation by default.
+/// Each lint has a stable code (W-prefixed) and a name, either of
+/// which can be used to configure it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Lint {
+    /// A local variable that is never read.
+    UnusedVariable,
+    /// An imported symbol that is never used.
+    UnusedImport,
+    /// A private function that is never used.
+    UnusedFunction,
+    /// Code after an expression that always returns or breaks.
+    UnreachableCode,
+    /// A local variable with the same name as another in scope.
+    ShadowedVariable,
+}
+
+impl Lint {
+    /// All lints, in order of their code.
+    pub const ALL: [Lint; 5] = [
+        Lint::UnusedVariable,
+        Lint::UnusedImport,
+        Lint::UnusedFunction,
+        Lint::UnreachableCode,
+        Lint::ShadowedVariable,
+    ];
+
+    /// The stable code of this lint, also the name of its `GErr` variant.
+    pub fn code(self) -> &'static str {
+        match self {
+            Lint::UnusedVariable => "W001",
+            Lint::UnusedImport => "W002",
+            Lint::UnusedFunction => "W003",
+            Lint::UnreachableCode => "W004",
+            Lint::ShadowedVariable => "W005",
+        }
+    }
+
+    /// The name of this lint, used in `--deny`/`--allow` and allow comments.
+    pub fn name(self) -> &'static str {
+        match self {
+            Lint::UnusedVariable => "unused_variable",
+            Lint::UnusedImport => "unused_import",
+            Lint::UnusedFunction => "unused_function",
+            Lint::UnreachableCode => "unreachable_code",
+            Lint::ShadowedVariable => "shadowed_variable",
+        }
+    }
+
+    /// Finds a lint by either its name or code.
+    pub fn find(name: &str) -> Option<Lint> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|lint| lint.name() == name || lint.code() == name)
+    }
+
+    /// Is this lint allowed inline at the given span of the source?
+    /// Lints are allowed with an `// allow(lint, ...)` comment, either
+    /// at the end of the line the span starts on or on its own line
+    /// directly above it. Lints can be given by name or code.
+    pub fn allowed_at(self, src: &str, span: Range<usize>) -> bool {
+        let line_start = src[..span.start].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = src[span.start..]
+            .find('\n')
+            .map_or(src.len(), |i| span.start + i);
+        let line = &src[line_start..line_end];
+        let prev_line = src[..line_start.saturating_sub(1)]
+            .rsplit('\n')
+            .next()
+            .filter(|line| line.trim_start().starts_with("//"))
+            .unwrap_or("");
+
+        self.allowed_by(line) || self.allowed_by(prev_line)
+    }
+
+    fn allowed_by(self, line: &str) -> bool {
+        let comment = match line.find("//") {
+            Some(index) => line[index + 2..].trim_start(),
+            None => return false,
+        };
+        let list = match comment
+            .strip_prefix("allow(")
+            .and_then(|rest| rest.split(')').next())
+        {
+            Some(list) => list,
+            None => return false,
+        };
+        list.split(',')
+            .map(str::trim)
+            .any(|name| name == self.name() || name == self.code())
+    }
+}
+
+/// The level a lint is reported at.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LintLevel {
+    /// The lint is not reported.
+    Allow,
+    /// The lint is reported as a warning.
+    Warn,
+    /// The lint is reported as an error, failing compilation.
+    Deny,
+}
+
+/// The levels of all lints, warning on every lint by default.
+#[derive(Debug, Copy, Clone)]
+pub struct LintLevels([LintLevel; Lint::ALL.len()]);
+
+impl LintLevels {
+    pub fn get(&self, lint: Lint) -> LintLevel {
+        self.0[lint as usize]
+    }
+
+    pub fn set(&mut self, lint: Lint, level: LintLevel) {
+        self.0[lint as usize] = level
+    }
+
+    /// Sets the level of all lints.
+    pub fn set_all(&mut self, level: LintLevel) {
+        self.0 = [level; Lint::ALL.len()]
+    }
+}
+
+impl Default for LintLevels {
+    fn default() -> Self {
+        Self([LintLevel::Warn; Lint::ALL.len()])
+    }
+}
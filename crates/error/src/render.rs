@@ -0,0 +1,171 @@
+//! Rendering of errors together with the source code
+//! they point at, in a layout similar to rustc.
+
+use crate::{Error, ErrorSpan, Note, SourceMap};
+use ansi_term::{
+    Color::{self, Blue},
+    Style,
+};
+use lexer::Span;
+
+/// A location in a source to be marked in a snippet.
+struct Mark<'a> {
+    /// Line of the location, starting at 1.
+    line: usize,
+    /// Column of the location, starting at 1.
+    col: usize,
+    len: usize,
+    message: Option<&'a str>,
+    /// If this is the location of the error itself instead of a label.
+    primary: bool,
+}
+
+impl<'a> Mark<'a> {
+    fn new(map: &SourceMap, span: Span, message: Option<&'a str>, primary: bool) -> Self {
+        let (line, col) = map.line_col(span.start);
+        let len = map.char_len(span.start, span.end);
+        Self {
+            line,
+            col,
+            len,
+            message,
+            primary,
+        }
+    }
+}
+
+/// Renders the location of the error and all its labels.
+/// Labels in the same source as the error are shown in the same snippet,
+/// all others get a snippet for each source.
+pub(crate) fn snippets(out: &mut String, err: &Error, color: Color, map: &SourceMap, origin: &str) {
+    let mut local = vec![Mark::new(map, err.index.get_span(), None, true)];
+    let mut foreign: Vec<(SourceMap, &str, Vec<Mark>)> = Vec::new();
+
+    for label in err
+        .details
+        .labels
+        .iter()
+        .filter(|l| !matches!(l.span, ErrorSpan::None))
+    {
+        match &label.source {
+            Some((src, label_origin)) if label_origin != origin => {
+                let index = match foreign.iter().position(|(_, o, _)| o == label_origin) {
+                    Some(index) => index,
+                    None => {
+                        foreign.push((SourceMap::new(src), label_origin, vec![]));
+                        foreign.len() - 1
+                    }
+                };
+                let (map, _, marks) = &mut foreign[index];
+                marks.push(Mark::new(
+                    map,
+                    label.span.get_span(),
+                    Some(&label.message),
+                    false,
+                ));
+            }
+
+            _ => local.push(Mark::new(
+                map,
+                label.span.get_span(),
+                Some(&label.message),
+                false,
+            )),
+        }
+    }
+
+    snippet(out, map, origin, "-->", &local, color);
+    for (map, origin, marks) in &foreign {
+        snippet(out, map, origin, ":::", marks, color);
+    }
+}
+
+/// Renders a single snippet of source code, with all given marks
+/// shown below the lines they are on. The first mark is used
+/// for the location in the header; if it is the primary one,
+/// the lines around it are shown for context.
+fn snippet(
+    out: &mut String,
+    map: &SourceMap,
+    origin: &str,
+    arrow: &str,
+    marks: &[Mark],
+    color: Color,
+) {
+    let dimmed = Style::new().dimmed();
+    let first = &marks[0];
+    out.push_str(&format!(
+        "\n{} {} L{}:{}",
+        Blue.dimmed().paint(arrow),
+        Style::new().italic().paint(origin),
+        first.line,
+        first.col
+    ));
+
+    let line_count = map.line_count();
+    let mut lines = marks.iter().map(|m| m.line).collect::<Vec<_>>();
+    if first.primary {
+        lines.push(first.line - 1);
+        lines.push(first.line + 1);
+    }
+    lines.retain(|l| *l > 0 && (*l <= line_count || *l == first.line));
+    lines.sort_unstable();
+    lines.dedup();
+
+    out.push_str(&dimmed.paint("\n     |").to_string());
+    let mut prev_line = None;
+    for line in lines {
+        if prev_line.map_or(false, |prev| line > prev + 1) {
+            out.push_str(&dimmed.paint("\n ...").to_string());
+        }
+        prev_line = Some(line);
+
+        let text = map.line(line).unwrap_or("<unexpected end of file>");
+        out.push_str(&format!(
+            "{}{}",
+            dimmed.paint(format!("\n{:4} | ", line)),
+            text
+        ));
+
+        let mut line_marks = marks.iter().filter(|m| m.line == line).collect::<Vec<_>>();
+        line_marks.sort_by_key(|m| m.col);
+        for mark in line_marks {
+            let len = mark
+                .len
+                .min(text.chars().count().saturating_sub(mark.col - 1))
+                .max(1);
+            let (marker, style) = if mark.primary {
+                ("^", color.bold())
+            } else {
+                ("-", Blue.bold())
+            };
+            let marked = match mark.message {
+                Some(message) => format!("{} {}", marker.repeat(len), message),
+                None => marker.repeat(len),
+            };
+            out.push_str(&format!(
+                "{}{}{}",
+                dimmed.paint("\n     | "),
+                " ".repeat(mark.col - 1),
+                style.paint(marked)
+            ));
+        }
+    }
+    out.push_str(&dimmed.paint("\n     |").to_string());
+}
+
+/// Renders all notes and help messages of an error.
+pub(crate) fn notes(out: &mut String, notes: &[Note]) {
+    for note in notes {
+        let (kind, msg) = match note {
+            Note::Note(msg) => ("note", msg),
+            Note::Help(msg) => ("help", msg),
+        };
+        out.push_str(&format!(
+            "\n{} {}: {}",
+            Blue.dimmed().paint("     ="),
+            Style::new().bold().paint(kind),
+            msg
+        ));
+    }
+}
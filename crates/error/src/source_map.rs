@@ -0,0 +1,148 @@
+//! Mapping of byte offsets in a source to lines and columns.
+
+/// An index of the lines of a source, used to find the line and column
+/// of byte offsets without scanning the whole source every time.
+/// Built once per source for all errors in it.
+pub struct SourceMap<'s> {
+    src: &'s str,
+    /// Byte offset of the start of every line.
+    line_starts: Vec<usize>,
+}
+
+impl<'s> SourceMap<'s> {
+    pub fn new(src: &'s str) -> Self {
+        let line_starts = Some(0)
+            .into_iter()
+            .chain(src.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self { src, line_starts }
+    }
+
+    /// Returns line and column of the given byte offset, both starting at 1.
+    /// Columns count characters, not bytes.
+    pub fn line_col(&self, offset: usize) -> (usize, usize) {
+        let offset = self.clamp(offset);
+        let line = match self.line_starts.binary_search(&offset) {
+            Ok(line) => line,
+            Err(next) => next - 1,
+        };
+        let col = self.src[self.line_starts[line]..offset].chars().count();
+        (line + 1, col + 1)
+    }
+
+    /// Returns the text of the given line, starting at 1,
+    /// without its line ending.
+    pub fn line(&self, line: usize) -> Option<&'s str> {
+        if line > self.line_count() {
+            return None;
+        }
+        let start = *self.line_starts.get(line.checked_sub(1)?)?;
+        let end = self
+            .line_starts
+            .get(line)
+            .map_or(self.src.len(), |next| next - 1);
+        Some(self.src[start..end].trim_end_matches('\r'))
+    }
+
+    /// Returns the amount of lines in the source.
+    pub fn line_count(&self) -> usize {
+        // A trailing newline does not start another line
+        if self.src.ends_with('\n') {
+            self.line_starts.len() - 1
+        } else {
+            self.line_starts.len()
+        }
+    }
+
+    /// Returns the amount of characters between the given byte offsets.
+    pub fn char_len(&self, start: usize, end: usize) -> usize {
+        self.src[self.clamp(start)..self.clamp(end)].chars().count()
+    }
+
+    /// Clamps the offset to the source, moving it back
+    /// to the start of a character if it points inside one.
+    fn clamp(&self, offset: usize) -> usize {
+        let mut offset = offset.min(self.src.len());
+        while !self.src.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        offset
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SourceMap;
+
+    #[test]
+    fn line_starts() {
+        let map = SourceMap::new("ab\ncd\n\nef");
+        assert_eq!(map.line_col(0), (1, 1));
+        assert_eq!(map.line_col(2), (1, 3));
+        assert_eq!(map.line_col(3), (2, 1));
+        assert_eq!(map.line_col(4), (2, 2));
+        assert_eq!(map.line_col(6), (3, 1));
+        assert_eq!(map.line_col(7), (4, 1));
+        assert_eq!(map.line(3), Some(""));
+        assert_eq!(map.line_count(), 4);
+    }
+
+    #[test]
+    fn first_and_last_line() {
+        let map = SourceMap::new("first\nmiddle\nlast");
+        assert_eq!(map.line(0), None);
+        assert_eq!(map.line(1), Some("first"));
+        assert_eq!(map.line(3), Some("last"));
+        assert_eq!(map.line(4), None);
+        assert_eq!(map.line_col(16), (3, 4));
+
+        // A trailing newline does not start another line
+        let map = SourceMap::new("first\nlast\n");
+        assert_eq!(map.line_count(), 2);
+        assert_eq!(map.line(2), Some("last"));
+        assert_eq!(map.line(3), None);
+    }
+
+    #[test]
+    fn offset_at_eof() {
+        let map = SourceMap::new("ab\ncd");
+        assert_eq!(map.line_col(5), (2, 3));
+        // Offsets past the end are clamped to it
+        assert_eq!(map.line_col(100), (2, 3));
+        assert_eq!(map.char_len(3, 100), 2);
+
+        // After a trailing newline, the end is on the line after the last
+        let map = SourceMap::new("ab\n");
+        assert_eq!(map.line_col(3), (2, 1));
+        assert_eq!(map.line(2), None);
+
+        let map = SourceMap::new("");
+        assert_eq!(map.line_col(0), (1, 1));
+        assert_eq!(map.line(1), Some(""));
+    }
+
+    #[test]
+    fn crlf_line_endings() {
+        let map = SourceMap::new("ab\r\ncd\r\n");
+        assert_eq!(map.line_count(), 2);
+        assert_eq!(map.line(1), Some("ab"));
+        assert_eq!(map.line(2), Some("cd"));
+        assert_eq!(map.line_col(2), (1, 3));
+        assert_eq!(map.line_col(4), (2, 1));
+        assert_eq!(map.line_col(5), (2, 2));
+    }
+
+    #[test]
+    fn columns_count_chars() {
+        // 'é' is 2 bytes, '🎉' is 4 bytes
+        let map = SourceMap::new("é🎉x\n🎉y");
+        assert_eq!(map.line_col(2), (1, 2));
+        assert_eq!(map.line_col(6), (1, 3));
+        assert_eq!(map.line_col(12), (2, 2));
+        assert_eq!(map.char_len(0, 7), 3);
+        // Offsets inside of a character are moved to its start
+        assert_eq!(map.line_col(1), (1, 1));
+        assert_eq!(map.line_col(4), (1, 2));
+        assert_eq!(map.char_len(1, 5), 1);
+    }
+}
@@ -0,0 +1,66 @@
+use smol_str::SmolStr;
+use std::fmt::{Display, Error as FmtErr, Formatter};
+
+/// Suggestions attached to an error about an unknown name,
+/// helping the user find the name they were looking for.
+#[derive(Debug, Clone, Default)]
+pub struct Suggestion {
+    /// A similar name that is available.
+    pub similar: Option<SmolStr>,
+    /// An import declaration that would make the name available.
+    pub import: Option<String>,
+}
+
+impl Suggestion {
+    /// Creates a suggestion containing the candidate most similar to the given name,
+    /// if any candidate is close enough to likely be a typo.
+    pub fn similar<T: AsRef<str>, I: IntoIterator<Item = T>>(name: &str, candidates: I) -> Self {
+        let max_distance = (name.chars().count() / 3).max(1);
+        let similar = candidates
+            .into_iter()
+            // Compiler-generated names contain '-' and cannot be used by the user
+            .filter(|c| !c.as_ref().contains('-') && c.as_ref() != name)
+            .map(|c| (distance(name, c.as_ref()), SmolStr::new(c.as_ref())))
+            .filter(|(dist, _)| *dist <= max_distance)
+            .min_by(|(dist1, name1), (dist2, name2)| dist1.cmp(dist2).then(name1.cmp(name2)))
+            .map(|(_, name)| name);
+        Self {
+            similar,
+            import: None,
+        }
+    }
+}
+
+impl Display for Suggestion {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FmtErr> {
+        if let Some(similar) = &self.similar {
+            write!(f, " Did you mean '{}'?", similar)?;
+        }
+        if let Some(import) = &self.import {
+            write!(f, " It can be imported with '{}'.", import)?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the edit distance between the two strings; the number of
+/// characters that need to be inserted, removed or changed to turn
+/// one into the other.
+fn distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut prev_row = (0..=b.len()).collect::<Vec<_>>();
+    let mut row = vec![0; b.len() + 1];
+
+    for (i, a_char) in a.chars().enumerate() {
+        row[0] = i + 1;
+        for (j, b_char) in b.iter().enumerate() {
+            let change = (a_char != *b_char) as usize;
+            row[j + 1] = (prev_row[j] + change)
+                .min(prev_row[j + 1] + 1)
+                .min(row[j] + 1);
+        }
+        prev_row.copy_from_slice(&row);
+    }
+
+    prev_row[b.len()]
+}
@@ -0,0 +1,38 @@
+//! A formatter for gelix source code.
+//! It prints the lossless CST produced by the parser in a
+//! canonical style, keeping all comments.
+
+mod printer;
+
+use crate::printer::Printer;
+use error::Error;
+
+/// Options of the formatter.
+#[derive(Debug, Clone)]
+pub struct FormatConfig {
+    /// The amount of spaces per level of indentation.
+    pub indent_width: usize,
+    /// The preferred maximum line length. Argument and parameter
+    /// lists that do not fit get one line per element.
+    pub line_length: usize,
+}
+
+impl Default for FormatConfig {
+    fn default() -> Self {
+        Self {
+            indent_width: 4,
+            line_length: 100,
+        }
+    }
+}
+
+/// Formats the given source code.
+/// Fails with the syntax errors of the source if there are any,
+/// since broken code cannot be formatted without changing it.
+pub fn format(src: &str, config: &FormatConfig) -> Result<String, Vec<Error>> {
+    let parse = parser::parse(src);
+    if parse.has_errors() {
+        return Err(parse.into_errors());
+    }
+    Ok(Printer::new(config).root(&parse.root()))
+}
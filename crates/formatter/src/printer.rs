@@ -0,0 +1,539 @@
+use crate::FormatConfig;
+use parser::{Node, NodeOrToken, Token};
+use std::cmp::max;
+use syntax::kind::SyntaxKind;
+
+/// What to write between the last token and the next one.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Sep {
+    None,
+    Space,
+    Newline,
+    BlankLine,
+}
+
+/// How the children of a node are currently laid out.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Layout {
+    /// All on the same line
+    Inline,
+    /// Inside braces; every child node on its own line
+    Items,
+    /// Inside a list that did not fit on one line; every element on its own line
+    Broken,
+}
+
+/// The last token written, along with some context of it.
+#[derive(Debug, Copy, Clone)]
+struct Last {
+    kind: SyntaxKind,
+    parent: SyntaxKind,
+    grandparent: SyntaxKind,
+    /// If the next token must follow without a space ('Type:name')
+    tight: bool,
+}
+
+/// A top-level declaration or comment, printed on its own line(s).
+struct Entry {
+    text: String,
+    blank_before: bool,
+    /// The key to sort by if this is an import declaration
+    import: Option<String>,
+}
+
+pub(crate) struct Printer<'c> {
+    config: &'c FormatConfig,
+    out: String,
+    /// The column `out` starts at; only used when measuring.
+    start_column: usize,
+    indent: usize,
+    sep: Sep,
+    /// Amount of newlines in the source since the last token.
+    newlines: usize,
+    last: Option<Last>,
+    /// The kinds of all nodes currently being printed.
+    stack: Vec<SyntaxKind>,
+    /// If the current item already continued on a new line, which
+    /// indents all following lines of the item by another level.
+    continued: bool,
+    /// If lists should stay on one line no matter their length.
+    /// Used when measuring if a list fits.
+    flat: bool,
+}
+
+impl<'c> Printer<'c> {
+    /// Prints a whole file. Top-level declarations are printed separately,
+    /// to allow sorting import declarations.
+    pub fn root(mut self, root: &Node) -> String {
+        let mut entries: Vec<Entry> = Vec::new();
+        for child in root.children_with_tokens() {
+            match child {
+                NodeOrToken::Node(node) => {
+                    let mut printer = Printer::new(self.config);
+                    printer.stack.push(SyntaxKind::Root);
+                    printer.node(&node);
+
+                    let import = Some(printer.out.clone())
+                        .filter(|_| node.kind() == SyntaxKind::ImportDecl)
+                        .map(|text| {
+                            text.split_once(' ')
+                                .map_or("", |(_, path)| path)
+                                .to_string()
+                        });
+                    entries.push(Entry {
+                        text: printer.out,
+                        blank_before: self.newlines > 1,
+                        import,
+                    });
+                    self.newlines = printer.newlines;
+                }
+
+                NodeOrToken::Token(token) if token.kind() == SyntaxKind::Whitespace => {
+                    self.whitespace(&token)
+                }
+
+                NodeOrToken::Token(token) => {
+                    let text = comment_text(&token);
+                    match entries.last_mut() {
+                        // Trailing comment after a declaration
+                        Some(entry) if self.newlines == 0 => {
+                            entry.text.push(' ');
+                            entry.text.push_str(text);
+                        }
+                        _ => entries.push(Entry {
+                            text: text.to_string(),
+                            blank_before: self.newlines > 1,
+                            import: None,
+                        }),
+                    }
+                    self.newlines = 0;
+                }
+            }
+        }
+
+        sort_imports(&mut entries);
+        let mut out = String::with_capacity(self.out.capacity());
+        for (i, entry) in entries.iter().enumerate() {
+            if i != 0 {
+                out.push('\n');
+                if entry.blank_before {
+                    out.push('\n');
+                }
+            }
+            out.push_str(&entry.text);
+        }
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        out
+    }
+
+    fn node(&mut self, node: &Node) {
+        self.stack.push(node.kind());
+        let mut children: Vec<_> = node.children_with_tokens().collect();
+        if node.kind() == SyntaxKind::ImportDecl {
+            sort_import_list(&mut children);
+        }
+        self.children(node.kind(), &children);
+        self.stack.pop();
+    }
+
+    fn children(&mut self, kind: SyntaxKind, children: &[NodeOrToken]) {
+        let mut layout = Layout::Inline;
+        for (i, child) in children.iter().enumerate() {
+            let token = match child {
+                NodeOrToken::Node(node) => {
+                    match layout {
+                        // The else branch of 'when' follows its 'else' token
+                        Layout::Items if node.kind() != SyntaxKind::ExprElse => self.item(node),
+                        Layout::Broken => self.element(node),
+                        _ => self.node(node),
+                    }
+                    continue;
+                }
+                NodeOrToken::Token(token) => token,
+            };
+
+            match token.kind() {
+                SyntaxKind::Whitespace => self.whitespace(token),
+
+                SyntaxKind::Comment => self.comment(token, layout == Layout::Items),
+
+                SyntaxKind::LeftBrace if has_items(kind) => {
+                    self.token(token);
+                    self.indent += 1;
+                    layout = Layout::Items;
+                }
+
+                SyntaxKind::RightBrace if layout == Layout::Items => {
+                    self.indent -= 1;
+                    if !self.last_is(SyntaxKind::LeftBrace) {
+                        self.sep = max(self.sep, Sep::Newline);
+                    }
+                    self.token(token);
+                    layout = Layout::Inline;
+                }
+
+                // The else branch of 'when'
+                SyntaxKind::Else if layout == Layout::Items => {
+                    self.item_start();
+                    self.token(token);
+                }
+
+                SyntaxKind::LeftParen if is_list(kind) && !self.flat => {
+                    let broken = !self.fits(kind, &children[i..]);
+                    self.token(token);
+                    if broken {
+                        self.indent += 1;
+                        layout = Layout::Broken;
+                    }
+                }
+
+                SyntaxKind::RightParen if layout == Layout::Broken => {
+                    self.indent -= 1;
+                    self.sep = max(self.sep, Sep::Newline);
+                    self.token(token);
+                    layout = Layout::Inline;
+                }
+
+                _ => self.token(token),
+            }
+        }
+    }
+
+    /// Prints a node on its own line, like a statement or class member.
+    fn item(&mut self, node: &Node) {
+        self.item_start();
+        self.isolated(node);
+    }
+
+    /// Starts a new item, keeping a blank line before it if there was one.
+    fn item_start(&mut self) {
+        let sep = if self.newlines > 1 && !self.last_is(SyntaxKind::LeftBrace) {
+            Sep::BlankLine
+        } else {
+            Sep::Newline
+        };
+        self.sep = max(self.sep, sep);
+    }
+
+    /// Prints an element of a list that was broken up.
+    fn element(&mut self, node: &Node) {
+        self.sep = max(self.sep, Sep::Newline);
+        self.isolated(node);
+    }
+
+    /// Prints a node that is on its own line; continuation
+    /// lines inside it do not affect the lines after it.
+    fn isolated(&mut self, node: &Node) {
+        let (indent, continued) = (self.indent, self.continued);
+        self.continued = false;
+        self.node(node);
+        self.indent = indent;
+        self.continued = continued;
+    }
+
+    /// Returns if the given list, starting at its opening parenthesis,
+    /// fits into the rest of the line. Lists with comments never fit,
+    /// since the comments might need their own lines.
+    fn fits(&self, kind: SyntaxKind, list: &[NodeOrToken]) -> bool {
+        if list.iter().any(|c| c.kind() == SyntaxKind::Comment) {
+            return false;
+        }
+
+        let mut printer = Printer {
+            config: self.config,
+            out: String::new(),
+            start_column: self.column(),
+            indent: self.indent,
+            sep: self.sep,
+            newlines: self.newlines,
+            last: self.last,
+            stack: self.stack.clone(),
+            continued: self.continued,
+            flat: true,
+        };
+        printer.children(kind, list);
+        let first_line = printer.out.lines().next().unwrap_or("");
+        printer.start_column + first_line.chars().count() <= self.config.line_length
+    }
+
+    fn whitespace(&mut self, token: &Token) {
+        self.newlines += token.text().matches('\n').count();
+    }
+
+    /// Prints a comment. Comments stay on their own line if they were,
+    /// and all other tokens stay off the line of a line comment.
+    fn comment(&mut self, token: &Token, in_items: bool) {
+        let sep = match self.newlines {
+            0 if self.last_is(SyntaxKind::LeftParen) => Sep::None,
+            0 => Sep::Space,
+            1 => Sep::Newline,
+            _ if in_items && !self.last_is(SyntaxKind::LeftBrace) => Sep::BlankLine,
+            _ => Sep::Newline,
+        };
+        self.sep = max(self.sep, sep);
+        self.write(comment_text(token));
+        if token.text().starts_with("//") {
+            self.sep = Sep::Newline;
+        }
+        self.last = Some(self.context(SyntaxKind::Comment));
+    }
+
+    fn token(&mut self, token: &Token) {
+        let kind = token.kind();
+        let last = self.context(kind);
+
+        if self.newlines > 0 && self.last_is(SyntaxKind::Comment) {
+            self.sep = max(self.sep, Sep::Newline);
+        } else if self.newlines > 0 && self.keeps_newline(last) {
+            if kind != SyntaxKind::Else {
+                if !self.continued {
+                    self.indent += 1;
+                    self.continued = true;
+                }
+                self.sep = max(self.sep, Sep::Newline);
+            } else if !self.last_is(SyntaxKind::RightBrace) {
+                self.sep = max(self.sep, Sep::Newline);
+            }
+        }
+
+        if self.space_before(last) {
+            self.sep = max(self.sep, Sep::Space);
+        }
+        let tight = self.is_tight(last);
+        self.write(token.text());
+        self.last = Some(Last { tight, ..last });
+    }
+
+    /// Returns if a newline in the source before the given token
+    /// should be kept; this allows breaking up long expressions.
+    fn keeps_newline(&self, next: Last) -> bool {
+        let is_binary_op =
+            |t: Last| t.parent == SyntaxKind::Operator && t.grandparent == SyntaxKind::BinaryExpr;
+        match next.kind {
+            SyntaxKind::Else => matches!(next.parent, SyntaxKind::IfExpr | SyntaxKind::ForExpr),
+            SyntaxKind::Dot | SyntaxKind::QuestionDot => matches!(
+                next.parent,
+                SyntaxKind::GetExpr | SyntaxKind::GetNullableExpr
+            ),
+            _ => is_binary_op(next) || matches!(self.last, Some(last) if is_binary_op(last)),
+        }
+    }
+
+    /// Returns if there should be a space between the last token and the given one.
+    fn space_before(&self, next: Last) -> bool {
+        let last = match self.last {
+            Some(last) if !last.tight => last,
+            _ => return false,
+        };
+
+        match next.kind {
+            SyntaxKind::Comma
+            | SyntaxKind::Semicolon
+            | SyntaxKind::Colon
+            | SyntaxKind::QuestionMark
+            | SyntaxKind::LeftBracket
+            | SyntaxKind::RightBracket
+            | SyntaxKind::RightParen => false,
+
+            SyntaxKind::Dot | SyntaxKind::QuestionDot | SyntaxKind::DotDot => {
+                next.parent == SyntaxKind::ImportDecl
+            }
+
+            // Calls and parameter lists
+            SyntaxKind::LeftParen => !matches!(
+                next.parent,
+                SyntaxKind::CallExpr | SyntaxKind::FunctionSignature | SyntaxKind::EnumCase
+            ),
+
+            SyntaxKind::RightBrace => {
+                last.kind != SyntaxKind::LeftBrace && next.parent != SyntaxKind::ImportDecl
+            }
+
+            SyntaxKind::Slash => next.parent != SyntaxKind::ImportDecl,
+
+            _ => !is_range(next),
+        }
+    }
+
+    /// Returns if the given token must be followed without a space.
+    fn is_tight(&self, token: Last) -> bool {
+        match (token.kind, token.parent) {
+            (SyntaxKind::LeftParen, _)
+            | (SyntaxKind::LeftBracket, _)
+            | (SyntaxKind::Dot, _)
+            | (SyntaxKind::QuestionDot, _)
+            | (SyntaxKind::DotDot, SyntaxKind::ImportDecl)
+            | (SyntaxKind::Slash, SyntaxKind::ImportDecl)
+            | (SyntaxKind::LeftBrace, SyntaxKind::ImportDecl)
+            | (SyntaxKind::Star, SyntaxKind::Type)
+            | (SyntaxKind::Tilde, SyntaxKind::Type)
+            | (SyntaxKind::Colon, SyntaxKind::GetStaticExpr) => true,
+
+            // Enum cases as types ('Shape:Circle'), not closure return types
+            (SyntaxKind::Colon, SyntaxKind::Type) => self.last_is(SyntaxKind::Identifier),
+
+            // Prefix operators
+            (_, SyntaxKind::Operator) if token.grandparent == SyntaxKind::PrefixExpr => true,
+
+            _ => is_range(token),
+        }
+    }
+
+    fn context(&self, kind: SyntaxKind) -> Last {
+        let mut stack = self.stack.iter().rev().copied();
+        Last {
+            kind,
+            parent: stack.next().unwrap_or(SyntaxKind::Root),
+            grandparent: stack.next().unwrap_or(SyntaxKind::Root),
+            tight: false,
+        }
+    }
+
+    fn last_is(&self, kind: SyntaxKind) -> bool {
+        matches!(self.last, Some(last) if last.kind == kind)
+    }
+
+    fn write(&mut self, text: &str) {
+        let at_start = self.out.is_empty() && self.start_column == 0;
+        match self.sep {
+            _ if at_start => (),
+            Sep::None => (),
+            Sep::Space => self.out.push(' '),
+            Sep::Newline | Sep::BlankLine => {
+                if self.sep == Sep::BlankLine {
+                    self.out.push('\n');
+                }
+                self.out.push('\n');
+                for _ in 0..(self.indent * self.config.indent_width) {
+                    self.out.push(' ');
+                }
+            }
+        }
+
+        self.out.push_str(text);
+        self.sep = Sep::None;
+        self.newlines = 0;
+    }
+
+    fn column(&self) -> usize {
+        match self.out.rfind('\n') {
+            Some(newline) => self.out[newline + 1..].chars().count(),
+            None => self.start_column + self.out.chars().count(),
+        }
+    }
+
+    pub fn new(config: &'c FormatConfig) -> Self {
+        Self {
+            config,
+            out: String::new(),
+            start_column: 0,
+            indent: 0,
+            sep: Sep::None,
+            newlines: 0,
+            last: None,
+            stack: Vec::with_capacity(16),
+            continued: false,
+            flat: false,
+        }
+    }
+}
+
+/// Returns if the children of the given node that are
+/// inside its braces go on their own lines.
+fn has_items(kind: SyntaxKind) -> bool {
+    matches!(
+        kind,
+        SyntaxKind::Block
+            | SyntaxKind::AdtDecl
+            | SyntaxKind::EnumCase
+            | SyntaxKind::ImplDecl
+            | SyntaxKind::WhenExpr
+    )
+}
+
+/// Returns if the parentheses of the given node contain
+/// a list that gets broken up if it is too long.
+fn is_list(kind: SyntaxKind) -> bool {
+    matches!(kind, SyntaxKind::CallExpr | SyntaxKind::FunctionSignature)
+}
+
+/// Range operators are written without spaces ('0..5').
+fn is_range(token: Last) -> bool {
+    token.parent == SyntaxKind::Operator
+        && matches!(token.kind, SyntaxKind::DotDot | SyntaxKind::DotDotEqual)
+}
+
+fn comment_text(token: &Token) -> &str {
+    if token.text().starts_with("//") {
+        token.text().trim_end()
+    } else {
+        token.text()
+    }
+}
+
+/// Sorts all groups of import declarations by their path.
+/// A group ends at a blank line or any other declaration or comment.
+fn sort_imports(entries: &mut [Entry]) {
+    let mut start = 0;
+    while start < entries.len() {
+        let mut end = start + 1;
+        if entries[start].import.is_some() {
+            while end < entries.len() && entries[end].import.is_some() && !entries[end].blank_before
+            {
+                end += 1;
+            }
+
+            let blank_before = entries[start].blank_before;
+            entries[start].blank_before = false;
+            entries[start..end].sort_by(|a, b| a.import.cmp(&b.import));
+            entries[start].blank_before = blank_before;
+        }
+        start = end;
+    }
+}
+
+/// Sorts the symbols of an import list ('import a/{c, b}') by name.
+fn sort_import_list(children: &mut Vec<NodeOrToken>) {
+    let open = children
+        .iter()
+        .position(|c| c.kind() == SyntaxKind::LeftBrace);
+    let close = children
+        .iter()
+        .position(|c| c.kind() == SyntaxKind::RightBrace);
+    let (open, close) = match (open, close) {
+        (Some(open), Some(close)) => (open, close),
+        _ => return,
+    };
+    let list = &children[(open + 1)..close];
+    if list.iter().any(|c| c.kind() == SyntaxKind::Comment) {
+        return;
+    }
+
+    let mut symbols: Vec<_> = list
+        .iter()
+        .filter_map(NodeOrToken::as_node)
+        .cloned()
+        .collect();
+    symbols.sort_by_cached_key(|symbol| {
+        symbol
+            .children_with_tokens()
+            .filter_map(NodeOrToken::into_token)
+            .filter(|t| t.kind() != SyntaxKind::Whitespace)
+            .map(|t| t.text().to_string())
+            .collect::<Vec<_>>()
+    });
+
+    let mut sorted = Vec::with_capacity(list.len());
+    for symbol in symbols {
+        if !sorted.is_empty() {
+            sorted.push(NodeOrToken::Token(Token::new(
+                SyntaxKind::Comma,
+                ",".into(),
+            )));
+        }
+        sorted.push(NodeOrToken::Node(symbol));
+    }
+    children.splice((open + 1)..close, sorted);
+}
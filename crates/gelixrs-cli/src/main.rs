@@ -4,12 +4,19 @@
  * This file is under the Apache 2.0 license. See LICENSE in the root of this repository for details.
  */
 
-use gelixrs::{stem_to_smol, GIRFlags};
-use std::path::PathBuf;
+use gelixrs::{
+    module_file, stem_to_smol, CompiledGIR, Errors, FormatConfig, GIRFlags, Lint, LintLevel,
+    LintLevels, ParsedModules,
+};
+use std::{fs, path::PathBuf, rc::Rc, str::FromStr};
 use structopt::StructOpt;
 
 #[derive(StructOpt, Debug, Default)]
-#[structopt(name = "gelixrs", about = "A compiler for the gelix language.")]
+#[structopt(
+    name = "gelixrs",
+    about = "A compiler for the gelix language.",
+    raw(setting = "structopt::clap::AppSettings::SubcommandsNegateReqs")
+)]
 struct Opt {
     /// Run in-place instead of compiling
     #[structopt(short, long)]
@@ -43,14 +50,126 @@ struct Opt {
     #[structopt(short = "O", default_value = "3")]
     optimize_level: usize,
 
+    /// Report the given lint (name or code) as an error; 'warnings' denies all lints
+    #[structopt(long, number_of_values = 1)]
+    deny: Vec<String>,
+
+    /// Do not report the given lint (name or code); 'warnings' allows all lints.
+    /// Takes precedence over --deny
+    #[structopt(long, number_of_values = 1)]
+    allow: Vec<String>,
+
+    /// The format to print errors and warnings in, either 'human' or 'json'.
+    /// With 'json', every diagnostic is printed as a JSON object on its own line
+    #[structopt(long = "error-format", default_value = "human")]
+    error_format: ErrorFormat,
+
+    /// Print a detailed explanation of the given error code, like 'E214', and exit
+    #[structopt(long)]
+    explain: Option<String>,
+
     /// File to compile
-    #[structopt(parse(from_os_str))]
-    file: PathBuf,
+    #[structopt(parse(from_os_str), required_unless = "explain")]
+    file: Option<PathBuf>,
+
+    #[structopt(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(StructOpt, Debug)]
+enum Command {
+    /// Format gelix source files in place
+    #[structopt(name = "fmt")]
+    Fmt {
+        /// Do not write any files; list the ones that are not formatted and
+        /// exit with status 1 if there are any
+        #[structopt(long)]
+        check: bool,
+
+        /// The amount of spaces per level of indentation
+        #[structopt(long, default_value = "4")]
+        indent: usize,
+
+        /// The preferred maximum line length
+        #[structopt(long = "line-length", default_value = "100")]
+        line_length: usize,
+
+        /// Files or directories to format; directories are searched for .gel files recursively
+        #[structopt(parse(from_os_str), required = true)]
+        paths: Vec<PathBuf>,
+    },
+
+    /// Print every name in a program along with the definition it refers to,
+    /// as JSON objects on their own line
+    #[structopt(name = "index")]
+    Index {
+        /// Do not include the standard library
+        #[structopt(long = "no-std")]
+        no_std: bool,
+
+        /// File or directory of the program to index
+        #[structopt(parse(from_os_str))]
+        file: PathBuf,
+    },
+
+    /// Generate HTML documentation from the doc comments of a program or library
+    #[structopt(name = "doc")]
+    Doc {
+        /// Do not include the standard library
+        #[structopt(long = "no-std")]
+        no_std: bool,
+
+        /// The directory to write the documentation to
+        #[structopt(short, long, parse(from_os_str), default_value = "doc")]
+        output: PathBuf,
+
+        /// File or directory of the program to document; the standard library
+        /// is documented when given its directory
+        #[structopt(parse(from_os_str))]
+        file: PathBuf,
+    },
+}
+
+/// The format errors are printed in.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum ErrorFormat {
+    Human,
+    Json,
+}
+
+impl Default for ErrorFormat {
+    fn default() -> Self {
+        ErrorFormat::Human
+    }
+}
+
+impl FromStr for ErrorFormat {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "human" => Ok(ErrorFormat::Human),
+            "json" => Ok(ErrorFormat::Json),
+            _ => Err("Unknown error format, expected 'human' or 'json'."),
+        }
+    }
 }
 
 fn main() {
-    run(Opt::from_args()).map_err(|e| println!("{}", e)).ok();
-    if cfg!(debug_assertions) {
+    let args = Opt::from_args();
+    let json = args.error_format == ErrorFormat::Json;
+    let subcommand = args.command.is_some();
+    // Keep stdout clean of anything but diagnostics when printing JSON
+    run(args)
+        .map_err(|e| {
+            if json {
+                eprintln!("{}", e)
+            } else {
+                println!("{}", e)
+            }
+        })
+        .ok();
+    if cfg!(debug_assertions) && !json && !subcommand {
         println!(
             "\nCompiler benchmark results:\n{}",
             gelixrs::BENCH.lock().unwrap()
@@ -59,47 +178,63 @@ fn main() {
 }
 
 fn run(args: Opt) -> Result<(), &'static str> {
-    if !args.file.exists() {
-        return Err("Given path does not exist.");
+    if let Some(code) = &args.explain {
+        println!("{}", gelixrs::explain(code).ok_or("Unknown error code.")?);
+        return Ok(());
     }
 
-    let modules = if !args.no_std {
-        let std_mod = gelixrs::find_std_module()?;
-        vec![args.file.clone(), std_mod]
-    } else {
-        vec![args.file.clone()]
-    };
+    if let Some(Command::Fmt {
+        check,
+        indent,
+        line_length,
+        paths,
+    }) = &args.command
+    {
+        let config = FormatConfig {
+            indent_width: *indent,
+            line_length: *line_length,
+        };
+        return format_files(paths, &config, *check);
+    }
 
-    let code = gelixrs::parse_source(modules).map_err(|errors| {
-        for file in errors {
-            println!("{} error(s):\n{}", file.errors.len(), file);
-            println!();
-        }
-        "Parser encountered errors. Exiting."
-    })?;
+    if let Some(Command::Index { no_std, file }) = &args.command {
+        return index(&args, file, *no_std);
+    }
+
+    if let Some(Command::Doc {
+        no_std,
+        output,
+        file,
+    }) = &args.command
+    {
+        return document(&args, file, output, *no_std);
+    }
+
+    let file = args.file.clone().ok_or("Missing file to compile.")?;
+    let modules = input_modules(&file, args.no_std)?;
+    let (code, syntax_errors) = parse(&args, &modules);
 
     if args.parse {
-        let stem = stem_to_smol(&args.file);
+        let stem = stem_to_smol(&file);
         for module in code.iter().filter(|m| m.path.index(0).unwrap() == &stem) {
             println!("{:#?}\n\n", module);
         }
-        return Ok(());
+        return check_syntax(&syntax_errors);
     }
 
     let gir_flags = GIRFlags {
         no_std: args.no_std,
         no_prelude: args.no_std,
+        lints: lint_levels(&args)?,
         ..GIRFlags::default()
     };
-    let gir = gelixrs::compile_gir(code, gir_flags).map_err(|errors| {
-        for error in errors {
-            println!("{}\n", error);
-        }
-        "GIR generator encountered errors. Exiting."
-    })?;
+    let gir = compile(&args, code, &syntax_errors, &modules, gir_flags)?;
+    for warnings in &gir.warnings {
+        print_errors(&args, warnings, &modules);
+    }
 
     if args.gir || args.gir_all {
-        let stem = stem_to_smol(&args.file);
+        let stem = stem_to_smol(&file);
         for module in gir
             .modules
             .iter()
@@ -123,10 +258,7 @@ fn run(args: Opt) -> Result<(), &'static str> {
     }
 
     if args.run {
-        println!(
-            "Compiled successfully, running '{}'...",
-            args.file.display()
-        );
+        println!("Compiled successfully, running '{}'...", file.display());
         let mut engine = gelixrs::JIT::new(module);
         unsafe {
             engine.call("main");
@@ -148,3 +280,217 @@ fn run(args: Opt) -> Result<(), &'static str> {
     }
     Ok(())
 }
+
+/// Returns the paths to give to the parser to compile the given file.
+fn input_modules(file: &PathBuf, no_std: bool) -> Result<Vec<PathBuf>, &'static str> {
+    if !file.exists() {
+        return Err("Given path does not exist.");
+    }
+    if !no_std {
+        let std_mod = gelixrs::find_std_module()?;
+        Ok(vec![file.clone(), std_mod])
+    } else {
+        Ok(vec![file.clone()])
+    }
+}
+
+/// Parses the given modules and prints all syntax errors.
+/// Modules with syntax errors are returned as well, with the
+/// part of their tree the parser was able to recover.
+fn parse(args: &Opt, modules: &[PathBuf]) -> (ParsedModules, Vec<Errors>) {
+    let (code, errors) = gelixrs::parse_source(modules.to_vec());
+    for file in &errors {
+        match args.error_format {
+            ErrorFormat::Human => println!("{} error(s):\n{}\n", file.errors.len(), file),
+            ErrorFormat::Json => print_json(file, modules),
+        }
+    }
+    (code, errors)
+}
+
+/// Returns an error if there were any syntax errors.
+fn check_syntax(syntax_errors: &[Errors]) -> Result<(), &'static str> {
+    if syntax_errors.is_empty() {
+        Ok(())
+    } else {
+        Err("Parser encountered errors. Exiting.")
+    }
+}
+
+/// Compiles the parsed modules and prints all errors.
+/// Modules with syntax errors are compiled as well to find errors in
+/// the rest of the program, but errors inside of them are not printed,
+/// since they are most likely caused by the syntax errors.
+fn compile(
+    args: &Opt,
+    code: ParsedModules,
+    syntax_errors: &[Errors],
+    modules: &[PathBuf],
+    gir_flags: GIRFlags,
+) -> Result<CompiledGIR, &'static str> {
+    let gir = gelixrs::compile_gir(code, gir_flags);
+    if let Err(errors) = &gir {
+        for error in errors
+            .iter()
+            .filter(|errors| syntax_errors.iter().all(|e| e.origin != errors.origin))
+        {
+            print_errors(args, error, modules);
+        }
+    }
+    check_syntax(syntax_errors)?;
+    gir.map_err(|_| "GIR generator encountered errors. Exiting.")
+}
+
+/// Compiles the program in the given file and prints its index,
+/// one symbol per line.
+fn index(args: &Opt, file: &PathBuf, no_std: bool) -> Result<(), &'static str> {
+    let modules = input_modules(file, no_std)?;
+    let (code, syntax_errors) = parse(args, &modules);
+    let gir_flags = GIRFlags {
+        no_std,
+        no_prelude: no_std,
+        references: true,
+        ..GIRFlags::default()
+    };
+    let gir = compile(args, code, &syntax_errors, &modules, gir_flags)?;
+
+    let module_file = |path: &str| module_file(&modules, path).map(|f| f.display().to_string());
+    for symbol in gelixrs::index(&gir, &stem_to_smol(file), &module_file) {
+        println!("{}", symbol.to_json());
+    }
+    Ok(())
+}
+
+/// Compiles the program or library in the given file as a library
+/// and writes its documentation to `output`.
+fn document(
+    args: &Opt,
+    file: &PathBuf,
+    output: &PathBuf,
+    no_std: bool,
+) -> Result<(), &'static str> {
+    let stem = stem_to_smol(file);
+    // The standard library is always compiled along with the program,
+    // unless it is the one being documented
+    let modules = if stem == "std" {
+        input_modules(file, true)?
+    } else {
+        input_modules(file, no_std)?
+    };
+    let (code, syntax_errors) = parse(args, &modules);
+    let gir_flags = GIRFlags {
+        library: true,
+        no_std,
+        no_prelude: no_std,
+        ..GIRFlags::default()
+    };
+    let gir = compile(args, code, &syntax_errors, &modules, gir_flags)?;
+
+    let pages = gelixrs::document(&gir, &stem);
+    for page in &pages {
+        let path = output.join(&page.path);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|_| "Failed to create directory.")?;
+        }
+        fs::write(&path, &page.html).map_err(|_| "Failed to write file.")?;
+    }
+    println!(
+        "Documented {} module(s) in '{}'.",
+        pages.len() - 1,
+        output.display()
+    );
+    Ok(())
+}
+
+/// Formats all given files, writing them in place.
+/// In check mode, only lists unformatted files and exits with status 1 if there are any.
+fn format_files(paths: &[PathBuf], config: &FormatConfig, check: bool) -> Result<(), &'static str> {
+    let mut files = Vec::new();
+    for path in paths {
+        collect_gelix_files(path.clone(), &mut files)?;
+    }
+
+    let mut unformatted = 0;
+    for file in files {
+        let src = fs::read_to_string(&file).map_err(|_| "Failed to read file.")?;
+        let formatted = match gelixrs::format(&src, config) {
+            Ok(formatted) => formatted,
+            Err(errors) => {
+                let errors = Errors {
+                    errors,
+                    src: Some(Rc::new(src)),
+                    origin: file.display().to_string(),
+                };
+                println!("{}", errors);
+                return Err("Cannot format files with syntax errors. Exiting.");
+            }
+        };
+
+        if formatted != src {
+            unformatted += 1;
+            if check {
+                println!("{}", file.display());
+            } else {
+                fs::write(&file, formatted).map_err(|_| "Failed to write file.")?;
+            }
+        }
+    }
+
+    if check && unformatted > 0 {
+        println!("{} file(s) are not formatted.", unformatted);
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Adds the given path to `files` if it is a file,
+/// or all gelix files inside it if it is a directory.
+fn collect_gelix_files(path: PathBuf, files: &mut Vec<PathBuf>) -> Result<(), &'static str> {
+    if path.is_dir() {
+        let entries = path.read_dir().map_err(|_| "Failed to read directory.")?;
+        for entry in entries {
+            let entry = entry.map_err(|_| "Failed to read directory.")?.path();
+            if entry.is_dir() || entry.extension() == Some("gel".as_ref()) {
+                collect_gelix_files(entry, files)?;
+            }
+        }
+    } else if path.exists() {
+        files.push(path);
+    } else {
+        return Err("Given path does not exist.");
+    }
+    Ok(())
+}
+
+fn print_errors(args: &Opt, errors: &Errors, modules: &[PathBuf]) {
+    match args.error_format {
+        ErrorFormat::Human => println!("{}\n", errors),
+        ErrorFormat::Json => print_json(errors, modules),
+    }
+}
+
+/// Prints all errors as JSON, one line per error.
+/// `modules` are the paths given to the parser.
+fn print_json(errors: &Errors, modules: &[PathBuf]) {
+    let file = |path: &str| module_file(modules, path).map(|file| file.display().to_string());
+    for diagnostic in errors.diagnostics(&file) {
+        println!("{}", diagnostic.to_json());
+    }
+}
+
+fn lint_levels(args: &Opt) -> Result<LintLevels, &'static str> {
+    let mut levels = LintLevels::default();
+    for (names, level) in &[
+        (&args.deny, LintLevel::Deny),
+        (&args.allow, LintLevel::Allow),
+    ] {
+        for name in names.iter() {
+            if name == "warnings" {
+                levels.set_all(*level);
+            } else {
+                levels.set(Lint::find(name).ok_or("Unknown lint given.")?, *level);
+            }
+        }
+    }
+    Ok(levels)
+}
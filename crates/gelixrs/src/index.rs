@@ -0,0 +1,132 @@
+//! The symbol index of a program: Every name in its code along with
+//! the definition it resolved to, for code search and highlighting tools.
+
+use error::{DiagnosticSpan, SourceMap};
+use gir_generator::{CompiledGIR, Definition, Reference};
+use gir_nodes::{declaration::ADTType, Declaration};
+use serde::Serialize;
+use std::{collections::HashMap, rc::Rc};
+use syntax::kind::SyntaxKind;
+
+/// A name in the code of a program.
+#[derive(Serialize, Debug)]
+pub struct Symbol {
+    pub name: String,
+    /// One of 'function', 'method', 'class', 'interface', 'enum', 'case', 'field' or 'local'.
+    pub kind: &'static str,
+    /// The type of the definition; the signature for functions and ADTs.
+    #[serde(rename = "type")]
+    pub ty: String,
+    /// The ID of the definition the name refers to, unique inside the index.
+    pub definition: usize,
+    /// Either 'definition', 'read' or 'write'.
+    pub access: &'static str,
+    pub module: String,
+    pub file: Option<String>,
+    pub span: DiagnosticSpan,
+}
+
+impl Symbol {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+}
+
+/// Returns all names in the modules starting with `root`, ordered by their position.
+/// The GIR must have been compiled with [gir_generator::GIRFlags::references].
+/// `file` returns the file of a module, given its path.
+pub fn index(gir: &CompiledGIR, root: &str, file: &dyn Fn(&str) -> Option<String>) -> Vec<Symbol> {
+    let sources = gir
+        .modules
+        .iter()
+        .map(|module| {
+            let module = module.borrow();
+            (module.path.to_string(), Rc::clone(&module.src))
+        })
+        .collect::<HashMap<_, _>>();
+    let maps = sources
+        .iter()
+        .map(|(path, src)| (path.as_str(), SourceMap::new(src)))
+        .collect::<HashMap<_, _>>();
+
+    let mut references = gir
+        .references
+        .iter()
+        .filter(|reference| reference.module.index(0).map(|m| m.as_str()) == Some(root))
+        .map(|reference| (reference.module.to_string(), reference))
+        .collect::<Vec<_>>();
+    references.sort_by_key(|(module, reference)| (module.clone(), reference.span.start));
+
+    // Definitions are numbered in order of their first reference
+    let mut ids = HashMap::new();
+    references
+        .into_iter()
+        .filter_map(|(module, reference)| {
+            let next_id = ids.len();
+            let definition = *ids.entry(address(&reference.definition)).or_insert(next_id);
+            let span = reference.span.start as usize..reference.span.end as usize;
+            let span = DiagnosticSpan::from_range(span, maps.get(module.as_str())?);
+            let (name, kind, ty) = describe(&reference.definition);
+            Some(Symbol {
+                name,
+                kind,
+                ty,
+                definition,
+                access: access(reference),
+                file: file(&module),
+                module,
+                span,
+            })
+        })
+        .collect()
+}
+
+/// Returns the name, kind and type of a definition.
+fn describe(definition: &Definition) -> (String, &'static str, String) {
+    match definition {
+        Definition::Local(var) => (var.name.to_string(), "local", var.ty.to_string()),
+        Definition::Field(field) => (field.name.to_string(), "field", field.ty.to_string()),
+        Definition::Function(func) => {
+            let is_method = func
+                .borrow()
+                .ast
+                .as_ref()
+                .map(|ast| ast.cst.kind() == SyntaxKind::Method)
+                == Some(true);
+            let kind = if is_method { "method" } else { "function" };
+            let signature = Declaration::Function(Rc::clone(func)).to_string();
+            (func.borrow().name.to_string(), kind, signature)
+        }
+        Definition::Adt(adt) => {
+            let kind = match adt.borrow().ty {
+                ADTType::Class { .. } => "class",
+                ADTType::Interface => "interface",
+                ADTType::Enum { .. } => "enum",
+                ADTType::EnumCase { .. } => "case",
+            };
+            let signature = Declaration::Adt(Rc::clone(adt)).to_string();
+            (adt.borrow().name.to_string(), kind, signature)
+        }
+    }
+}
+
+fn access(reference: &Reference) -> &'static str {
+    if reference.is_definition {
+        "definition"
+    } else if reference.is_write {
+        "write"
+    } else {
+        "read"
+    }
+}
+
+/// Returns the address of a definition, which is
+/// the same for all references to it.
+fn address(definition: &Definition) -> usize {
+    match definition {
+        Definition::Local(var) => Rc::as_ptr(var) as usize,
+        Definition::Field(field) => Rc::as_ptr(field) as usize,
+        Definition::Function(func) => Rc::as_ptr(func) as usize,
+        Definition::Adt(adt) => Rc::as_ptr(adt) as usize,
+    }
+}
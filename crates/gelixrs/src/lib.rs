@@ -1,14 +1,17 @@
+mod index;
 mod parse_stage;
 
 use ir::IRGenerator;
 
 pub use common::{Benches, BENCH};
-pub use error::Errors;
+pub use docgen::{document, Page};
+pub use error::{explain, Diagnostic, Errors, Lint, LintLevel, LintLevels, EXPLANATIONS};
+pub use formatter::{format, FormatConfig};
 pub use gir_generator::{CompiledGIR, GIRFlags};
+pub use index::{index, Symbol};
 pub use ir::{ir_context, jit::JIT, produce_binary, CompiledIR, Context};
-pub use parse_stage::{find_std_module, parse_source, stem_to_smol};
+pub use parse_stage::{find_std_module, module_file, parse_source, stem_to_smol, ParsedModules};
 
-use crate::parse_stage::ParsedModules;
 use gir_generator::GIRGenerator;
 
 pub fn compile_gir(ast: ParsedModules, flags: GIRFlags) -> Result<CompiledGIR, Vec<Errors>> {
@@ -6,66 +6,90 @@ use std::{env, fs, path::PathBuf, rc::Rc};
 
 pub type ParsedModules = Vec<Module>;
 
-pub fn parse_source(input: Vec<PathBuf>) -> Result<ParsedModules, Vec<Errors>> {
+/// Parses all modules in the given paths. Modules with syntax errors
+/// are still included with the partial tree the parser recovered,
+/// so that later stages can check their valid parts;
+/// the syntax errors are returned alongside them.
+pub fn parse_source(input: Vec<PathBuf>) -> (ParsedModules, Vec<Errors>) {
     let mut modules = Vec::new();
+    let mut errors = Vec::new();
     for path in input {
-        make_modules(path, &mut ModPath::new(), &mut modules)?;
+        make_modules(path, &mut ModPath::new(), &mut modules, &mut errors);
     }
-    Ok(modules)
+    (modules, errors)
 }
 
 fn make_modules(
     input: PathBuf,
     path: &mut ModPath,
     modules: &mut ParsedModules,
-) -> Result<(), Vec<Errors>> {
+    errors: &mut Vec<Errors>,
+) {
     path.push(stem_to_smol(&input));
 
     if let Ok(dir) = input.read_dir() {
-        let mut errors = Vec::new();
         for file in dir {
             let file = file.expect("Failed to read file").path();
 
             // If the file is named 'module.gel', it should have the
             // containing directory as its module path.
-            let result = if file.file_name().unwrap() == "module.gel" {
-                parse_module(file, path)
-                    .map(|m| modules.push(m))
-                    .map_err(|e| vec![e])
+            if file.file_name().unwrap() == "module.gel" {
+                parse_module(file, path, modules, errors)
             } else {
-                make_modules(file, path, modules)
-            };
-
-            if let Err(mut errs) = result {
-                errors.append(&mut errs);
+                make_modules(file, path, modules, errors)
             }
         }
-
-        if !errors.is_empty() {
-            return Err(errors);
-        }
     } else if *input
         .extension()
         .map(|ext| ext == "gel")
         .get_or_insert(false)
     {
         // If 'input' is a .gel file; parse it if true
-        modules.push(parse_module(input, path).map_err(|e| vec![e])?);
+        parse_module(input, path, modules, errors);
     }
 
     path.pop();
-    Ok(())
 }
 
-fn parse_module(input: PathBuf, path: &mut ModPath) -> Result<Module, Errors> {
+fn parse_module(
+    input: PathBuf,
+    path: &mut ModPath,
+    modules: &mut ParsedModules,
+    errors: &mut Vec<Errors>,
+) {
     let code = Rc::new(fs::read_to_string(&input).expect("Failed to read file."));
-    let parse = parser::parse(&code);
-    let cst = parse.map_err(|errors| Errors {
-        errors,
-        src: Some(Rc::clone(&code)),
-        origin: format!("{}", path),
-    })?;
-    Ok(Module::new(&path, &code, cst))
+    let (cst, parse_errors) = parser::parse(&code).into_parts();
+    if !parse_errors.is_empty() {
+        errors.push(Errors {
+            errors: parse_errors,
+            src: Some(Rc::clone(&code)),
+            origin: format!("{}", path),
+        });
+    }
+    modules.push(Module {
+        cst,
+        path: Rc::new(path.clone()),
+        src: code,
+    });
+}
+
+/// Returns the file a module with the given path was parsed from,
+/// given the same input paths as [parse_source].
+pub fn module_file(input: &[PathBuf], path: &str) -> Option<PathBuf> {
+    let mut parts = path.split('/');
+    let root_name = parts.next()?;
+    let root = input.iter().find(|root| stem_to_smol(root) == root_name)?;
+    if !root.is_dir() {
+        return Some(root.clone());
+    }
+
+    let dir = parts.fold(root.clone(), |dir, part| dir.join(part));
+    let file = dir.with_extension("gel");
+    if file.is_file() {
+        Some(file)
+    } else {
+        Some(dir.join("module.gel")).filter(|file| file.is_file())
+    }
 }
 
 pub fn stem_to_smol(path: &PathBuf) -> SmolStr {
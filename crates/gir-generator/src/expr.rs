@@ -1,20 +1,23 @@
-use crate::{passes::FnSig, result::EmitGIRError, FieldOrMethod, GIRGenerator};
+use crate::{
+    passes::FnSig, result::EmitGIRError, smart_casts::Narrowing, Definition, FieldOrMethod,
+    GIRGenerator,
+};
 use ast::{
     Binary, Block, Break, CSTNode, Call, Expression as AExpr, ForIterCond, GenericIdent, Get,
-    GetStatic, LiteralType, Return, When, WhenBranch,
+    GetStatic, IfExpr, LiteralType, Return, When, WhenBranch,
 };
 use common::MutRc;
-use error::{GErr, Res};
+use error::{Error, ErrorSpan, GErr, Res};
 use gir_nodes::{
     declaration::{ADTType, LocalVariable, Variable, Visibility},
     expression::{CastType, Intrinsic},
     gir_err,
     types::{TypeArguments, TypeParameter},
-    Expr, Function, IFaceImpls, Instance, Literal, Type, ADT,
+    Expr, Function, IFaceImpl, IFaceImpls, Instance, Literal, Type, ADT,
 };
 use num_traits::Num;
 use smol_str::SmolStr;
-use std::{convert::TryInto, iter::FromIterator, rc::Rc};
+use std::{collections::HashMap, convert::TryInto, iter::FromIterator, mem, rc::Rc};
 use syntax::kind::SyntaxKind;
 
 /// This impl contains all code of the generator that directly
@@ -32,11 +35,18 @@ impl GIRGenerator {
 
             AExpr::Call(call) => self.call(call),
 
-            AExpr::For(expr) if expr.iter_cond().is_some() => {
-                self.for_iter(expr.iter_cond().unwrap(), expr.body(), expr.else_branch())
-            }
             AExpr::For(expr) => {
-                Ok(self.for_cond(expr.condition().unwrap(), expr.body(), expr.else_branch()))
+                self.loop_depth += 1;
+                let expr = match expr.iter_cond() {
+                    Some(cond) => self.for_iter(cond, expr.body(), expr.else_branch()),
+                    None => Ok(self.for_cond(
+                        expr.condition().unwrap(),
+                        expr.body(),
+                        expr.else_branch(),
+                    )),
+                };
+                self.loop_depth -= 1;
+                expr
             }
 
             AExpr::Get(get) => {
@@ -59,9 +69,7 @@ impl GIRGenerator {
 
             AExpr::Grouping(inner) => Ok(self.expression(&inner.inner())),
 
-            AExpr::If(expr) => {
-                Ok(self.if_(expr.condition(), expr.then_branch(), expr.else_branch()))
-            }
+            AExpr::If(expr) => Ok(self.if_(expr).0),
 
             AExpr::Literal(literal) => self.literal(literal),
 
@@ -82,12 +90,22 @@ impl GIRGenerator {
         self.eat(expr).unwrap_or(Expr::Literal(Literal::Any))
     }
 
+    /// Generates an expression, keeping its location for passes over GIR.
+    /// Used for statements and branches.
+    fn located(&mut self, expression: &AExpr) -> Expr {
+        Expr::located(self.expression(expression), expression.cst())
+    }
+
     fn binary(&mut self, expr: &Binary) -> Res<Expr> {
         let op = expr.operator();
         if op == SyntaxKind::Equal {
             return self.assignment(expr.left(), expr.right());
         }
+        if Self::is_range(op) {
+            return self.range(expr);
+        }
 
+        let assign_mark = self.assigned_vars.len();
         let left = self.expression(&expr.left());
         let ast_right = expr.right();
 
@@ -95,22 +113,108 @@ impl GIRGenerator {
         // turned into a regular value instead of a type get.
         let right = match ast_right {
             AExpr::GetStatic(get) if op == SyntaxKind::Is => self.get_static(&get, false)?,
+
+            // The right side of 'and' only runs if the left side is true,
+            // the one of 'or' if it is false
+            _ if op == SyntaxKind::And || op == SyntaxKind::Or => {
+                self.begin_scope();
+                let mut block = self.smart_casts(&left, op == SyntaxKind::And, assign_mark);
+                let right = self.expression(&ast_right);
+                self.end_scope();
+                if block.is_empty() {
+                    right
+                } else {
+                    block.push(right);
+                    Expr::Block(block)
+                }
+            }
+
             _ => self.expression(&ast_right),
         };
 
         self.binary_gir(&expr.cst, left, op, right)
     }
 
+    fn is_range(op: SyntaxKind) -> bool {
+        op == SyntaxKind::DotDot || op == SyntaxKind::DotDotEqual
+    }
+
+    /// Generates a range expression ('a..b' or 'a..=b'),
+    /// which constructs a `std/iter/Range`. Only `Range[i64]` implements
+    /// `Iter`, so other integer types are only allowed in for loops.
+    fn range(&mut self, range: &Binary) -> Res<Expr> {
+        let (ty, start, end) = self.range_bounds(range)?;
+        let range_adt = self
+            .intrinsics
+            .range_proto
+            .clone()
+            .or_err(&range.cst, GErr::E247)?;
+
+        let inclusive = range.operator() == SyntaxKind::DotDotEqual;
+        let constructor = range_adt
+            .borrow()
+            .constructors
+            .iter()
+            .find(|c| {
+                let params = &c.borrow().parameters;
+                match params.get(3) {
+                    Some(param) => inclusive && param.name == "inclusive",
+                    None => !inclusive && params.len() == 3, // this, start, end
+                }
+            })
+            .cloned()
+            .unwrap();
+        let mut args = vec![start, end];
+        if inclusive {
+            args.push(Expr::Literal(Literal::Bool(true)));
+        }
+
+        Ok(Expr::Allocate {
+            ty: Type::Adt(Instance::new(range_adt, Rc::new(vec![ty]))),
+            constructor,
+            args,
+        })
+    }
+
+    /// Generates the bounds of a range, unified to the same integer type.
+    /// Whether the end bound is inclusive is left to the caller.
+    fn range_bounds(&mut self, range: &Binary) -> Res<(Type, Expr, Expr)> {
+        let start = self.expression(&range.left());
+        let end = self.expression(&range.right());
+        let (ty, start, end) = self.try_unify_type(start, end);
+        let ty = ty.filter(Type::is_int).or_err(&range.cst, GErr::E246)?;
+        Ok((ty, start, end))
+    }
+
+    /// Returns the literal 1 of the given integer type.
+    fn int_one(&mut self, ty: &Type) -> Expr {
+        let mut one = Expr::Literal(Literal::I64(1));
+        self.try_cast_in_place(&mut one, ty);
+        one
+    }
+
     fn assignment(&mut self, to: AExpr, value: AExpr) -> Res<Expr> {
         // Account for edge case where it is illegal to get an
         // uninitialized ADT member; it's fine here since it's being written
         // and not read
-        let (lvalue, was_uninit) = if let AExpr::Get(get) = &to {
-            self.get(get, true)?
-        } else {
-            (self.expression(&to), false)
+        let (lvalue, was_uninit) = match &to {
+            AExpr::Get(get) => {
+                let lvalue = self.get(get, true)?;
+                self.written(&get.property().cst);
+                lvalue
+            }
+            // Assigning to a local variable does not count as reading it
+            AExpr::Variable(var) if var.type_args().next().is_none() => {
+                let lvalue = Expr::var(self.find_var(&var.name(), &var.cst)?);
+                self.written(&var.cst);
+                (lvalue, false)
+            }
+            _ => (self.expression(&to), false),
         };
         let rvalue = self.expression(&value);
+        if let Expr::Variable(Variable::Local(var)) = &lvalue {
+            return self.assign_local(var, rvalue, &to.cst(), &value.cst());
+        }
         let (rvalue, matching_types) = self.try_cast(rvalue, &lvalue.get_type());
 
         if !was_uninit && !lvalue.assignable() {
@@ -206,7 +310,27 @@ impl GIRGenerator {
 
     fn block(&mut self, block: &Block) -> Expr {
         self.begin_scope();
-        let exprs: Vec<_> = block.expressions().map(|e| self.expression(&e)).collect();
+        let mut exprs = Vec::new();
+        let mut unreachable = false;
+        for expr in block.expressions() {
+            // Only the first unreachable expression is reported
+            if !unreachable && exprs.iter().any(Self::diverges) {
+                self.lint(expr.cst(), GErr::W004);
+                unreachable = true;
+            }
+
+            match &expr {
+                // Smart casts of if expressions with a branch that never finishes
+                // apply to the rest of the block ('if (a == null) return')
+                AExpr::If(if_) => {
+                    let (expr, narrowings) = self.if_(if_);
+                    exprs.push(Expr::located(expr, if_.cst()));
+                    let casts = self.apply_narrowings(narrowings);
+                    exprs.extend(casts);
+                }
+                _ => exprs.push(self.located(&expr)),
+            }
+        }
         self.end_scope();
 
         if exprs.is_empty() {
@@ -238,16 +362,11 @@ impl GIRGenerator {
 
         let ast_callee = call.callee();
         match &ast_callee {
-            // Method call while a `this` member is still uninitialized
-            AExpr::Get(get) if !self.uninitialized_this_fields.is_empty() => {
-                Err(gir_err(get.cst(), GErr::E203))
-            }
-
             // Method call
             AExpr::Get(get) => {
                 let object = self.expression(&get.callee());
                 let ty = object.get_type();
-                self.get_call(object, &ty, args, get, call.args())
+                self.get_call(object, &ty, args, get, call)
             }
 
             // Nullable method call
@@ -257,7 +376,7 @@ impl GIRGenerator {
                     &inner,
                     args,
                     get,
-                    call.args(),
+                    call,
                 )
             }),
 
@@ -267,53 +386,77 @@ impl GIRGenerator {
                 let mut callee_type = callee.get_type();
 
                 if let Type::Type(box Type::Adt(ty)) = &callee_type {
+                    let names = call.arg_names().collect::<Vec<_>>();
+                    let arg_csts = call.args().map(|a| a.cst()).collect::<Vec<_>>();
                     let mut ty_vars = callee_type.type_args().unwrap();
-                    let constructor = {
+                    let mut slot_err = None;
+                    let (constructor, slots) = {
                         let constructors = &ty.ty.borrow().constructors;
-                        let constructor = constructors
-                            .iter()
-                            .try_find(|constructor| {
-                                let constructor = constructor.borrow();
-
-                                // Different args count
-                                if constructor.parameters.len() - 1 != args.len() {
-                                    return Ok(false);
+                        let constructor = constructors.iter().find_map(|constructor| {
+                            let constructor_ref = constructor.borrow();
+
+                            // Arguments do not fit the parameters
+                            let slots = match Self::arg_slots(
+                                &constructor_ref,
+                                &names,
+                                &arg_csts,
+                                1,
+                                &call.cst,
+                            ) {
+                                Ok(slots) => slots,
+                                Err(err) => {
+                                    slot_err = Some(err);
+                                    return None;
                                 }
-
-                                // If there's no type args yet try inferring them
-                                if ty_vars.is_empty() {
-                                    args.insert(0, Expr::none_const());
-                                    let res = self.maybe_infer_ty_args(
-                                        &constructor.parameters,
-                                        &constructor.type_parameters,
-                                        &args,
-                                        0,
-                                        &call.cst,
-                                    );
-                                    if let Ok(args) = res {
-                                        ty_vars = Rc::new(args);
-                                    }
-                                    args.remove(0);
-                                };
-
-                                // Now check if the args are the correct type
-                                let correct_args_types =
-                                    constructor.parameters.iter().skip(1).zip(args.iter()).all(
-                                        |(param, arg)| {
-                                            let ty1 = arg.get_type();
-                                            let ty2 = param.ty.resolve(&ty_vars);
-                                            ty1.equal(&ty2, false)
-                                                || self.can_cast_type(&ty1, &ty2).is_some()
-                                        },
-                                    );
-
-                                Ok(correct_args_types)
-                            })?
-                            .or_err(&call.cst, GErr::E219)?;
-
-                        Rc::clone(constructor)
+                            };
+                            let (param_tys, arg_tys): (Vec<_>, Vec<_>) = constructor_ref
+                                .parameters
+                                .iter()
+                                .skip(1)
+                                .zip(slots.iter())
+                                .filter_map(|(param, slot)| {
+                                    Some((param.ty.clone(), args[(*slot)?].get_type()))
+                                })
+                                .unzip();
+
+                            // If there's no type args yet try inferring them
+                            if ty_vars.is_empty() {
+                                let res = self.infer_ty_args(
+                                    &param_tys,
+                                    &constructor_ref.type_parameters,
+                                    &arg_tys,
+                                    0,
+                                    &call.cst,
+                                );
+                                if let Ok(args) = res {
+                                    ty_vars = Rc::new(args);
+                                }
+                            };
+
+                            // Now check if the args are the correct type
+                            let correct_args_types =
+                                param_tys.iter().zip(arg_tys.iter()).all(|(param, arg)| {
+                                    let param = param.resolve(&ty_vars);
+                                    arg.equal(&param, false)
+                                        || self.can_cast_type(arg, &param).is_some()
+                                });
+
+                            if correct_args_types {
+                                Some((Rc::clone(constructor), slots))
+                            } else {
+                                None
+                            }
+                        });
+
+                        // With only one constructor, report why the arguments did not fit it
+                        match (constructor, slot_err) {
+                            (Some(found), _) => found,
+                            (None, Some(err)) if constructors.len() == 1 => return Err(err),
+                            (None, _) => return Err(gir_err(call.cst.clone(), GErr::E219)),
+                        }
                     };
 
+                    self.fill_args(&constructor, &mut args, arg_csts, &slots, &call.cst);
                     {
                         // Cast/convert all arguments to fit
                         for (param, arg) in constructor
@@ -335,18 +478,23 @@ impl GIRGenerator {
                     })
                 } else {
                     // If this is a function call, check it has
-                    // its type arguments inferred should it have any
-                    if let Type::Function(mut func) = callee_type {
-                        let ty_args = if !func.args().is_empty() {
+                    // its type arguments inferred should it have any.
+                    // Static methods on generic ADTs may only be given
+                    // the ADT's type arguments, infer the rest
+                    let arg_csts = if let Type::Function(mut func) = callee_type {
+                        let arg_csts = self.arrange_args(&func.ty, &mut args, call, 0)?;
+                        let given = func.args().len();
+                        let ty_args = if given >= func.ty.borrow().type_parameters.len() {
                             Rc::clone(func.args())
                         } else {
-                            Rc::new(self.maybe_infer_ty_args(
+                            let inferred = self.maybe_infer_ty_args(
                                 &func.ty.borrow().parameters,
                                 &func.ty.borrow().type_parameters,
                                 &args,
-                                0,
+                                given,
                                 &call.cst,
-                            )?)
+                            )?;
+                            Rc::new(func.args().iter().cloned().chain(inferred).collect())
                         };
                         self.validate_type_args(
                             &ty_args,
@@ -355,12 +503,15 @@ impl GIRGenerator {
                         );
                         func.set_args(ty_args);
                         callee = Expr::Variable(Variable::Function(func));
-                    }
+                        arg_csts
+                    } else {
+                        Self::positional_args(call)?
+                    };
 
                     self.check_func_args_(
                         &callee.get_type(),
                         &mut args,
-                        call.args(),
+                        arg_csts,
                         &call.cst,
                         false,
                     )?;
@@ -376,7 +527,7 @@ impl GIRGenerator {
         ty: &Type,
         mut args: Vec<Expr>,
         get: &Get,
-        ast_args: impl Iterator<Item = ast::Expression>,
+        call: &Call,
     ) -> Res<Expr> {
         let field = self.get_field(ty, &get)?;
         let func = match &field {
@@ -384,6 +535,7 @@ impl GIRGenerator {
             FieldOrMethod::Method(method) => method,
             FieldOrMethod::VirtMethod(method) => &method.iface_method,
         };
+        let arg_csts = self.arrange_args(func, &mut args, call, 1)?;
 
         let obj_ty = object.get_type();
         let parent_ty_args = obj_ty.type_args().unwrap_or_else(|| Rc::new(vec![]));
@@ -418,7 +570,7 @@ impl GIRGenerator {
         self.check_func_args_(
             &Type::Function(func.clone()),
             &mut args,
-            ast_args,
+            arg_csts,
             &get.cst,
             true,
         )?;
@@ -433,24 +585,27 @@ impl GIRGenerator {
 
     /// Check a function call's arguments for correctness,
     /// possibly adding a cast if required.
+    /// `func` is the function called, if it is not a closure.
     #[allow(clippy::too_many_arguments)] // Not ideal, but no real way of fixing this
     fn check_func_args(
         &mut self,
         mut parameters: impl Iterator<Item = Type>,
         type_args: Option<&Rc<TypeArguments>>,
         args: &mut Vec<Expr>,
-        ast_args: impl Iterator<Item = AExpr>,
-        allow_variadic: bool,
+        arg_csts: Vec<CSTNode>,
+        func: Option<&MutRc<Function>>,
         err_cst: &CSTNode,
         is_method: bool,
     ) -> Res<()> {
+        let allow_variadic = func.map_or(false, |f| f.borrow().variadic);
         let para_len = parameters.size_hint().0;
         if para_len > args.len() || (para_len < args.len() && !allow_variadic) {
+            // The 'this' argument of methods is not counted
             return Err(gir_err(
                 err_cst.clone(),
                 GErr::E216 {
-                    expected: parameters.size_hint().0,
-                    was: args.len(),
+                    expected: para_len - is_method as usize,
+                    was: args.len() - is_method as usize,
                 },
             ));
         }
@@ -473,35 +628,54 @@ impl GIRGenerator {
             // (This is done since it does not need any copying)
         }
 
-        for ((argument, parameter), ast) in args
+        for (index, ((argument, parameter), cst)) in args
             .iter_mut()
             .skip(is_method as usize)
             .zip(parameters)
-            .zip(ast_args)
+            .zip(arg_csts)
+            .enumerate()
         {
             let arg_type = argument.get_type();
             let success = self.try_cast_in_place(argument, &parameter);
             if !success {
-                self.error(gir_err(
-                    ast.cst(),
+                let err = gir_err(
+                    cst,
                     GErr::E218 {
                         expected: parameter.to_string(),
                         was: arg_type.to_string(),
                     },
-                ))
+                );
+                self.error(Self::label_parameter(err, func, index))
             }
         }
 
         Ok(())
     }
 
+    /// Adds a label pointing at the declaration of the given parameter
+    /// of the function to the error, if the function has one.
+    fn label_parameter(err: Error, func: Option<&MutRc<Function>>, index: usize) -> Error {
+        let func = match func {
+            Some(func) => func.borrow(),
+            None => return err,
+        };
+        let param = func
+            .ast
+            .as_ref()
+            .and_then(|ast| ast.sig().parameters().nth(index));
+        match param {
+            Some(param) => Self::label_in(err, &param.cst, &func.module, "parameter declared here"),
+            None => err,
+        }
+    }
+
     /// Same as above, but takes a function instead.
     /// `func` should be `Type::Function` or `Type::Closure`, otherwise returns an error.
     fn check_func_args_(
         &mut self,
         func: &Type,
         args: &mut Vec<Expr>,
-        ast_args: impl Iterator<Item = AExpr>,
+        arg_csts: Vec<CSTNode>,
         err_cst: &CSTNode,
         is_method: bool,
     ) -> Res<()> {
@@ -514,8 +688,8 @@ impl GIRGenerator {
                     .map(|p| p.ty.resolve(func.args())),
                 Some(func.args()),
                 args,
-                ast_args,
-                func.ty.borrow().variadic,
+                arg_csts,
+                Some(&func.ty),
                 err_cst,
                 is_method,
             ),
@@ -524,8 +698,8 @@ impl GIRGenerator {
                 closure.parameters.iter().cloned(),
                 None,
                 args,
-                ast_args,
-                false,
+                arg_csts,
+                None,
                 err_cst,
                 is_method,
             ),
@@ -534,6 +708,168 @@ impl GIRGenerator {
         }
     }
 
+    /// Reorders the arguments of a call to match the parameters of the function called,
+    /// resolving named arguments and evaluating the defaults of omitted parameters.
+    /// `skip` is the amount of parameters not given by the call ('this' on methods).
+    /// Returns the CST of all arguments after reordering, to be used for errors.
+    fn arrange_args(
+        &mut self,
+        func: &MutRc<Function>,
+        args: &mut Vec<Expr>,
+        call: &Call,
+        skip: usize,
+    ) -> Res<Vec<CSTNode>> {
+        let names = call.arg_names().collect::<Vec<_>>();
+        let arg_csts = call.args().map(|a| a.cst()).collect::<Vec<_>>();
+
+        // Only positional arguments with none omitted do not need any changes;
+        // if there are too many, check_func_args will report it
+        if names.iter().all(Option::is_none) && args.len() + skip >= func.borrow().parameters.len()
+        {
+            return Ok(arg_csts);
+        }
+
+        let slots = Self::arg_slots(&func.borrow(), &names, &arg_csts, skip, &call.cst)?;
+        Ok(self.fill_args(func, args, arg_csts, &slots, &call.cst))
+    }
+
+    /// Assigns all arguments of a call to the parameters of the function called.
+    /// Returns the index of the argument given for every parameter,
+    /// or `None` if the parameter was omitted and has a default value.
+    fn arg_slots(
+        func: &Function,
+        names: &[Option<SmolStr>],
+        arg_csts: &[CSTNode],
+        skip: usize,
+        err_cst: &CSTNode,
+    ) -> Res<Vec<Option<usize>>> {
+        let params = &func.parameters[skip..];
+        let count_err = || {
+            gir_err(
+                err_cst.clone(),
+                GErr::E216 {
+                    expected: params.len(),
+                    was: names.len(),
+                },
+            )
+        };
+
+        let mut slots = vec![None; params.len()];
+        let mut positional = 0;
+        for (index, name) in names.iter().enumerate() {
+            let slot = match name {
+                Some(name) => params
+                    .iter()
+                    .position(|p| &p.name == name)
+                    .or_err(&arg_csts[index], GErr::E244(name.clone()))?,
+                None if names[..index].iter().any(Option::is_some) => {
+                    return Err(gir_err(arg_csts[index].clone(), GErr::E253))
+                }
+                None => {
+                    positional += 1;
+                    positional - 1
+                }
+            };
+
+            match slots.get_mut(slot) {
+                Some(Some(_)) => {
+                    return Err(gir_err(
+                        arg_csts[index].clone(),
+                        GErr::E245(params[slot].name.clone()),
+                    ))
+                }
+                Some(arg) => *arg = Some(index),
+                None if func.variadic => slots.push(Some(index)),
+                None => return Err(count_err()),
+            }
+        }
+
+        let defaults = Self::param_defaults(func);
+        let missing = slots
+            .iter()
+            .enumerate()
+            .any(|(i, slot)| slot.is_none() && defaults.get(i).map_or(true, Option::is_none));
+        if missing {
+            Err(count_err())
+        } else {
+            Ok(slots)
+        }
+    }
+
+    /// Reorders the arguments according to the given slots (see `arg_slots`),
+    /// inserting default values for omitted parameters.
+    /// Returns the CST of all arguments after reordering.
+    fn fill_args(
+        &mut self,
+        func: &MutRc<Function>,
+        args: &mut Vec<Expr>,
+        arg_csts: Vec<CSTNode>,
+        slots: &[Option<usize>],
+        err_cst: &CSTNode,
+    ) -> Vec<CSTNode> {
+        let defaults = Self::param_defaults(&func.borrow());
+        let mut given = mem::replace(args, Vec::with_capacity(slots.len()))
+            .into_iter()
+            .map(Some)
+            .collect::<Vec<_>>();
+        let mut csts = Vec::with_capacity(slots.len());
+
+        for (index, slot) in slots.iter().enumerate() {
+            match slot {
+                Some(arg) => {
+                    args.push(given[*arg].take().unwrap());
+                    csts.push(arg_csts[*arg].clone());
+                }
+                None => {
+                    let default = defaults[index].as_ref().unwrap();
+                    args.push(self.default_arg(func, default));
+                    csts.push(err_cst.clone());
+                }
+            }
+        }
+        csts
+    }
+
+    /// Returns the default values of all parameters of a function
+    /// that are given by the caller (no 'this' parameter).
+    pub(crate) fn param_defaults(func: &Function) -> Vec<Option<AExpr>> {
+        func.ast
+            .as_ref()
+            .map(|ast| ast.sig().parameters().map(|p| p.default()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Generates the default value of a parameter at the call site.
+    /// It is generated inside the module of the function called,
+    /// with no access to any local variables.
+    pub(crate) fn default_arg(&mut self, func: &MutRc<Function>, default: &AExpr) -> Expr {
+        let module = Rc::clone(&func.borrow().module);
+        let path = Rc::clone(&module.borrow().path);
+        let prev_module = mem::replace(&mut self.module, module);
+        let prev_path = mem::replace(&mut self.path, path);
+        let prev_environments = mem::replace(&mut self.environments, vec![HashMap::new()]);
+        let prev_closure_data = self.closure_data.take();
+
+        let expr = self.expression(default);
+
+        self.closure_data = prev_closure_data;
+        self.environments = prev_environments;
+        self.path = prev_path;
+        self.module = prev_module;
+        expr
+    }
+
+    /// Returns the CST of all arguments of a call to something that is not
+    /// a function, like a closure. These do not support named arguments.
+    fn positional_args(call: &Call) -> Res<Vec<CSTNode>> {
+        for (name, arg) in call.arg_names().zip(call.args()) {
+            if let Some(name) = name {
+                return Err(gir_err(arg.cst(), GErr::E244(name)));
+            }
+        }
+        Ok(call.args().map(|a| a.cst()).collect())
+    }
+
     /// Try inferring a set of type arguments from a call.
     fn maybe_infer_ty_args(
         &mut self,
@@ -542,18 +878,30 @@ impl GIRGenerator {
         arguments: &[Expr],
         skip: usize,
         err_cst: &CSTNode,
+    ) -> Res<TypeArguments> {
+        let param_tys = parameters.iter().map(|p| p.ty.clone()).collect::<Vec<_>>();
+        let arg_tys = arguments.iter().map(|a| a.get_type()).collect::<Vec<_>>();
+        self.infer_ty_args(&param_tys, type_params, &arg_tys, skip, err_cst)
+    }
+
+    /// Try inferring a set of type arguments from the types of parameters
+    /// and the arguments given to them.
+    fn infer_ty_args(
+        &mut self,
+        param_tys: &[Type],
+        type_params: &[TypeParameter],
+        arg_tys: &[Type],
+        skip: usize,
+        err_cst: &CSTNode,
     ) -> Res<TypeArguments> {
         if type_params.is_empty() {
             return Ok(vec![]);
         }
 
-        let arg_tys = arguments.iter().map(|a| a.get_type()).collect::<Vec<_>>();
         let search_res = type_params
             .iter()
             .skip(skip)
-            .map(|param| {
-                self.resolve_type_param(param, parameters.iter().map(|p| &p.ty), arg_tys.iter())
-            })
+            .map(|param| self.resolve_type_param(param, param_tys.iter(), arg_tys.iter()))
             .collect::<Option<Vec<_>>>();
 
         search_res.or_err(err_cst, GErr::E214)
@@ -584,13 +932,14 @@ impl GIRGenerator {
     }
 
     fn for_cond(&mut self, condition: AExpr, body: AExpr, else_b: Option<AExpr>) -> Expr {
+        let assign_mark = self.assigned_vars.len();
         let cond = self.expression(&condition);
         if cond.get_type() != Type::Bool {
             self.err(condition.cst(), GErr::E220);
         }
 
         self.begin_scope();
-        let mut cast_block = self.smart_casts(&cond);
+        let mut cast_block = self.smart_casts(&cond, true, assign_mark);
         let (body, else_, phi_ty) = self.for_body(body, else_b);
         self.end_scope();
         cast_block.push(body);
@@ -638,9 +987,19 @@ impl GIRGenerator {
     }
     */
     fn for_iter(&mut self, cond: ForIterCond, body: AExpr, else_b: Option<AExpr>) -> Res<Expr> {
-        self.begin_scope();
+        if let AExpr::Binary(range) = cond.iterator() {
+            if Self::is_range(range.operator()) {
+                return self.for_range(&cond, &range, body, else_b);
+            }
+        }
 
         let iter_gir = self.expression(&cond.iterator());
+        if self.is_indexed_iter(&iter_gir.get_type()) {
+            return Ok(self.for_indexed(&cond, iter_gir, body, else_b));
+        }
+        let iter_gir = self.maybe_to_iter(iter_gir);
+
+        self.begin_scope();
         let (iter_store_expr, iter_gir) = self.temp_variable(iter_gir, "iter".into());
         let iter_gir = Expr::lvar(&iter_gir);
         let impls = self.get_iface_impls(&iter_gir.get_type());
@@ -651,7 +1010,7 @@ impl GIRGenerator {
         let (inital_store_expr, loop_var) = self.temp_variable(next_call.clone(), cond.name());
         let next_call_store = Expr::store(Expr::lvar(&loop_var), next_call, false);
 
-        let cond = Expr::binary(
+        let loop_cond = Expr::binary(
             SyntaxKind::BangEqual,
             Expr::lvar(&loop_var),
             Expr::Literal(Literal::Null),
@@ -661,6 +1020,7 @@ impl GIRGenerator {
         let mut clone = (*loop_var).clone();
         clone.ty = elem_ty[0].clone();
         let loop_inner_var = self.define_variable_(clone, None);
+        self.definition(&cond.cst, Definition::Local(Rc::clone(&loop_inner_var)));
         let loop_cast_store = Expr::store(
             Expr::lvar(&loop_inner_var),
             Expr::cast(
@@ -680,25 +1040,218 @@ impl GIRGenerator {
         let loop_block = vec![body_store, next_call_store, Expr::lvar(&body_var)];
 
         self.end_scope();
-        let loop_expr = Expr::loop_(cond, Expr::Block(loop_block), else_, phi_ty);
+        let loop_expr = Expr::loop_(loop_cond, Expr::Block(loop_block), else_, phi_ty);
         let block = vec![iter_store_expr, inital_store_expr, loop_expr];
         Ok(Expr::Block(block))
     }
 
+    /// Generates a for loop over a range literal ('for (i in 0..n)').
+    /// It is compiled into a plain counting loop instead of iterating
+    /// a `Range`, which would require allocating it.
+    fn for_range(
+        &mut self,
+        cond: &ForIterCond,
+        range: &Binary,
+        body: AExpr,
+        else_b: Option<AExpr>,
+    ) -> Res<Expr> {
+        let (ty, start, end) = self.range_bounds(range)?;
+        self.begin_scope();
+
+        let (counter_store, counter) = self.temp_variable(start, cond.name());
+        let (end_store, end) = self.temp_variable(end, "range-end".into());
+        let mut stores = vec![counter_store, end_store];
+
+        // Inclusive ranges cannot compare against the end plus one, which might
+        // overflow; instead, the loop stops after the iteration on the end itself
+        let inclusive = range.operator() == SyntaxKind::DotDotEqual;
+        let (loop_cond, running) = if inclusive {
+            let first = Expr::binary(
+                SyntaxKind::LessEqual,
+                Expr::lvar(&counter),
+                Expr::lvar(&end),
+            );
+            let (running_store, running) = self.temp_variable(first, "range-running".into());
+            stores.push(running_store);
+            (Expr::lvar(&running), Some(running))
+        } else {
+            let cond = Expr::binary(SyntaxKind::Less, Expr::lvar(&counter), Expr::lvar(&end));
+            (cond, None)
+        };
+
+        // The loop variable is a copy of the counter, so that
+        // modifying it does not change the iteration
+        self.begin_scope();
+        let loop_inner_var = self.define_variable_((*counter).clone(), None);
+        self.definition(&cond.cst, Definition::Local(Rc::clone(&loop_inner_var)));
+        let loop_var_store = Expr::store(Expr::lvar(&loop_inner_var), Expr::lvar(&counter), true);
+
+        let (body, else_, phi_ty) = self.for_body(body, else_b);
+        let body_block = vec![loop_var_store, body];
+        self.end_scope();
+
+        let (body_store, body_var) =
+            self.temp_variable(Expr::Block(body_block), "body-value".into());
+        let mut loop_block = vec![body_store];
+        if let Some(running) = running {
+            let not_last = Expr::binary(SyntaxKind::Less, Expr::lvar(&counter), Expr::lvar(&end));
+            loop_block.push(Expr::store(Expr::lvar(&running), not_last, false));
+        }
+        let increment = Expr::store(
+            Expr::lvar(&counter),
+            Expr::binary(SyntaxKind::Plus, Expr::lvar(&counter), self.int_one(&ty)),
+            false,
+        );
+        loop_block.push(increment);
+        loop_block.push(Expr::lvar(&body_var));
+
+        self.end_scope();
+        let loop_expr = Expr::loop_(loop_cond, Expr::Block(loop_block), else_, phi_ty);
+        stores.push(loop_expr);
+        Ok(Expr::Block(stores))
+    }
+
+    /// Returns if values of this type are iterated by index in for loops
+    /// instead of through `Iter`, which is the case for arrays and strings.
+    fn is_indexed_iter(&self, ty: &Type) -> bool {
+        let is_array = match (ty, &self.intrinsics.array_proto) {
+            (Type::Adt(adt), Some(array)) => Rc::ptr_eq(&adt.ty, array),
+            _ => false,
+        };
+        is_array || Some(ty) == self.intrinsics.string_type.as_ref()
+    }
+
+    /* Generates a for loop over an array or string, which
+    is compiled into an index-based loop over its elements:
+
+    for (elem in array) { ... }
+
+    Into:
+
+    {
+        val iter = array
+        var index = 0
+        for (index < iter.len) {
+            val elem = iter.get(index)
+            val loop_res = { ... } // USER CODE
+            index = index + 1
+            loop_res
+        }
+    }
+
+    This avoids allocating an iterator and the nullable returned by
+    `Iter.next`. The length is read on every iteration, so the loop behaves
+    the same as iterating the array's iterator would. On strings, the
+    length is `len()` and the elements are its bytes.
+    */
+    fn for_indexed(
+        &mut self,
+        cond: &ForIterCond,
+        value: Expr,
+        body: AExpr,
+        else_b: Option<AExpr>,
+    ) -> Expr {
+        self.begin_scope();
+        let (value_store, value) = self.temp_variable(value, "iter-value".into());
+
+        let len = if Some(&value.ty) == self.intrinsics.string_type.as_ref() {
+            self.call_known_method(Expr::lvar(&value), "len", vec![])
+        } else {
+            let field = Rc::clone(&value.ty.as_adt().ty.borrow().fields["len"]);
+            Expr::load(Expr::lvar(&value), &field)
+        };
+        let len_ty = len.get_type();
+
+        let mut zero = Expr::Literal(Literal::I64(0));
+        self.try_cast_in_place(&mut zero, &len_ty);
+        let (index_store, index) = self.temp_variable(zero, "iter-index".into());
+        let loop_cond = Expr::binary(SyntaxKind::Less, Expr::lvar(&index), len);
+
+        self.begin_scope();
+        let element = self.call_known_method(Expr::lvar(&value), "get", vec![Expr::lvar(&index)]);
+        let (element_store, element) = self.temp_variable(element, cond.name());
+        self.definition(&cond.cst, Definition::Local(element));
+
+        let (body, else_, phi_ty) = self.for_body(body, else_b);
+        let body_block = vec![element_store, body];
+        self.end_scope();
+
+        let (body_store, body_var) =
+            self.temp_variable(Expr::Block(body_block), "body-value".into());
+        let increment = Expr::store(
+            Expr::lvar(&index),
+            Expr::binary(SyntaxKind::Plus, Expr::lvar(&index), self.int_one(&len_ty)),
+            false,
+        );
+        let loop_block = vec![body_store, increment, Expr::lvar(&body_var)];
+
+        self.end_scope();
+        let loop_expr = Expr::loop_(loop_cond, Expr::Block(loop_block), else_, phi_ty);
+        Expr::Block(vec![value_store, index_store, loop_expr])
+    }
+
+    /// Calls a method of a standard library class that the compiler relies on,
+    /// casting the arguments to the parameter types.
+    fn call_known_method(&mut self, object: Expr, name: &str, args: Vec<Expr>) -> Expr {
+        let ty = object.get_type();
+        let adt = ty.as_adt();
+        let method = Rc::clone(&adt.ty.borrow().methods[name]);
+        let params = method
+            .borrow()
+            .parameters
+            .iter()
+            .skip(1)
+            .map(|p| p.ty.clone())
+            .collect::<Vec<_>>();
+
+        let mut arguments = vec![object];
+        for (mut arg, param) in args.into_iter().zip(params.iter()) {
+            self.try_cast_in_place(&mut arg, param);
+            arguments.push(arg);
+        }
+        let method = Instance::new(method, Rc::clone(adt.args()));
+        Expr::call(Expr::var(Variable::Function(method)), arguments)
+    }
+
+    /// If the value does not implement `Iter` but `ToIter`, returns a
+    /// call to its `iter` method. Since the implementation is known
+    /// at compile time, it is called directly.
+    fn maybe_to_iter(&mut self, value: Expr) -> Expr {
+        let ty = value.get_type();
+        let impls = self.get_iface_impls(&ty);
+        let impls = impls.borrow();
+        if Self::find_iter_iface(&impls, "Iter").is_some() {
+            return value;
+        }
+
+        let iter = Self::find_iter_iface(&impls, "ToIter")
+            .and_then(|(_, impl_)| impl_.methods.get("iter"));
+        match iter {
+            Some(func) => {
+                // Like other methods, it is instanced with the implementor's type arguments
+                let ty_args = ty.type_args().unwrap_or_default();
+                let iter_fn = Instance::new(Rc::clone(func), ty_args);
+                Expr::call(Expr::var(Variable::Function(iter_fn)), vec![value])
+            }
+            None => value,
+        }
+    }
+
+    /// Finds the implementation of the given `std/iter` interface, if any.
+    fn find_iter_iface<'i>(impls: &'i IFaceImpls, name: &str) -> Option<(&'i Type, &'i IFaceImpl)> {
+        impls.interfaces.iter().find(|(iface, _)| {
+            let iface = iface.as_adt().ty.borrow();
+            iface.name == name && iface.module.borrow().path.is(&["std", "iter"])
+        })
+    }
+
     fn get_iterator_value(
         &mut self,
         value: Expr,
         impls: &IFaceImpls,
         cst: &CSTNode,
     ) -> Res<(Expr, Instance<Function>, Rc<TypeArguments>)> {
-        let find_iface = |name: &str| {
-            impls.interfaces.iter().find(|(iface, _)| {
-                let iface = iface.as_adt().ty.borrow();
-                iface.name == name && iface.module.borrow().path.is(&["std", "iter"])
-            })
-        };
-        // TODO: ToIter.
-        let iter_impl = find_iface("Iter");
+        let iter_impl = Self::find_iter_iface(impls, "Iter");
 
         if let Some((iface, impl_)) = iter_impl {
             let elem_ty = iface.type_args().unwrap();
@@ -710,10 +1263,11 @@ impl GIRGenerator {
         }
     }
 
-    // Handle a get expression. allow_uninit controls if uninitialized ADT
-    // members can be accessed; they will be removed if they are.
-    // Return value also specifies if the value was uninitialized if allow_uninit is true.
+    // Handle a get expression. allow_uninit controls if ADT members the current
+    // constructor has to initialize can be assigned to despite being immutable.
+    // Return value also specifies if the value needs initializing if allow_uninit is true.
     // This is special behavior is used for assignment.
+    // Reading members before they are initialized is reported by the flow pass.
     fn get(&mut self, get: &Get, allow_uninit: bool) -> Res<(Expr, bool)> {
         let object = self.expression(&get.callee());
         let ty = object.get_type();
@@ -730,14 +1284,10 @@ impl GIRGenerator {
                 true
             };
 
-        if !is_this || allow_uninit || !self.uninitialized_this_fields.contains(&field) {
-            Ok((
-                Expr::load(object, &field),
-                !is_this || self.uninitialized_this_fields.remove(&field),
-            ))
-        } else {
-            Err(gir_err(get.property().cst, GErr::E222))
-        }
+        Ok((
+            Expr::load(object, &field),
+            !is_this || self.uninitialized_this_fields.contains(&field),
+        ))
     }
 
     fn get_nullable(&mut self, get: &Get) -> Res<Expr> {
@@ -803,9 +1353,21 @@ impl GIRGenerator {
         let name = get.property();
 
         if let Type::Type(ty) = obj.get_type() {
-            let ty = ty.try_adt().unwrap();
+            let ty = ty.try_adt().or_err(&get.callee().cst(), GErr::E224)?;
+            let static_method = ty.ty.borrow().static_methods.get(&name).cloned();
+            if let Some(method) = static_method {
+                let visibility = method.borrow().visibility;
+                self.check_visibility(visibility, &Type::Adt(ty.clone()), get.cst())?;
+                self.reference(&get.cst, Definition::Function(Rc::clone(&method)));
+                return Ok(Expr::Variable(Variable::Function(Instance::new(
+                    method,
+                    Rc::clone(ty.args()),
+                ))));
+            }
+
             if let ADTType::Enum { cases, .. } = &ty.ty.borrow().ty {
                 if let Some(case) = cases.get(&name) {
+                    self.reference(&get.cst, Definition::Adt(Rc::clone(case)));
                     match ADT::get_singleton_inst(case, ty.args()) {
                         Some(inst) if allow_simple => Ok(inst),
                         _ => Ok(Expr::TypeGet(Type::Adt(Instance::new(
@@ -817,94 +1379,52 @@ impl GIRGenerator {
                     Err(gir_err(get.callee().cst(), GErr::E223))
                 }
             } else {
-                Err(gir_err(get.callee().cst(), GErr::E224))
+                Err(gir_err(get.cst(), GErr::E243(name)))
             }
         } else {
             Err(gir_err(get.callee().cst(), GErr::E225))
         }
     }
 
-    fn if_(&mut self, condition: AExpr, then_branch: AExpr, else_branch: Option<AExpr>) -> Expr {
+    /// Generates an if expression. Also returns the smart casts that
+    /// apply to code after it, which is the case when one of the
+    /// branches never finishes evaluating.
+    fn if_(&mut self, if_: &IfExpr) -> (Expr, Vec<Narrowing>) {
+        let condition = if_.condition();
+        let assign_mark = self.assigned_vars.len();
         let cond = self.expression(&condition);
         if cond.get_type() != Type::Bool {
             self.err(condition.cst(), GErr::E220);
         }
+        let then_narrowings = self.narrowings(&cond, true, assign_mark);
+        let else_narrowings = self.narrowings(&cond, false, assign_mark);
 
+        let then_mark = self.assigned_vars.len();
         self.begin_scope(); // scope for smart casts if applicable
-        let mut then_block = self.smart_casts(&cond);
-        then_block.push(self.expression(&then_branch));
+        let mut then_block = self.apply_narrowings(then_narrowings.clone());
+        then_block.push(self.located(&if_.then_branch()));
         let then_val = Expr::Block(then_block);
         self.end_scope();
 
-        let else_val = else_branch
-            .as_ref()
-            .map_or(Expr::none_const(), |else_branch| {
-                self.expression(&else_branch)
-            });
-
-        let (phi_type, then_val, else_val) = self.try_unify_type(then_val, else_val);
-        Expr::if_(cond, then_val, else_val, phi_type)
-    }
-
-    /// Tries finding smart casts, where a type can be downcasted
-    /// based on a user-code condition.
-    /// Will insert variables for downcasts into current scope/function.
-    fn smart_casts(&mut self, condition: &Expr) -> Vec<Expr> {
-        let mut casts = Vec::new();
-        self.find_casts(&mut casts, condition);
-        casts
-    }
-
-    fn find_casts(&mut self, list: &mut Vec<Expr>, expr: &Expr) {
-        if let Expr::Binary {
-            left,
-            operator,
-            right,
-        } = expr
-        {
-            // This closure creates a clone of a local variable,
-            // cast to a different type, see below for usage.
-            let mut clone = |var: &Rc<LocalVariable>, ty: Type, cast: CastType| {
-                let mut clone = (**var).clone();
-                clone.mutable = false;
-                clone.ty = ty.clone();
-                let new_var = self.define_variable_(clone, None);
-
-                list.push(Expr::store(
-                    Expr::lvar(&new_var),
-                    Expr::cast(Expr::lvar(var), ty, cast),
-                    true,
-                ));
-            };
-
-            match (operator, &**left, &**right) {
-                (SyntaxKind::And, _, _) => {
-                    self.find_casts(list, &left);
-                    self.find_casts(list, &right);
-                }
-
-                // Enum parent to case (parent is Enum:Case)
-                (SyntaxKind::Is, Expr::Variable(Variable::Local(var)), _) => {
-                    let ty = *right.get_type().into_type();
-                    clone(var, ty, CastType::Bitcast);
-                }
+        let else_mark = self.assigned_vars.len();
+        let else_val = if_.else_branch().map_or(Expr::none_const(), |else_branch| {
+            self.begin_scope();
+            let mut else_block = self.apply_narrowings(else_narrowings.clone());
+            else_block.push(self.located(&else_branch));
+            self.end_scope();
+            Expr::Block(else_block)
+        });
 
-                // Nullable to non-null (a != null)
-                (
-                    SyntaxKind::BangEqual,
-                    Expr::Variable(Variable::Local(var)),
-                    Expr::Cast {
-                        inner: box Expr::Literal(Literal::Null),
-                        ..
-                    },
-                ) => {
-                    let ty = *left.get_type().into_nullable();
-                    clone(var, ty, CastType::FromNullable);
-                }
+        // Code after the if only runs if the branch that finishes was taken
+        let end = self.assigned_vars.len();
+        let narrowings = match (Self::diverges(&then_val), Self::diverges(&else_val)) {
+            (true, false) => self.unassigned(else_narrowings, else_mark..end),
+            (false, true) => self.unassigned(then_narrowings, then_mark..else_mark),
+            _ => vec![],
+        };
 
-                _ => (),
-            }
-        }
+        let (phi_type, then_val, else_val) = self.try_unify_type(then_val, else_val);
+        (Expr::if_(cond, then_val, else_val, phi_type), narrowings)
     }
 
     fn literal(&mut self, literal: &ast::Literal) -> Res<Expr> {
@@ -1070,12 +1590,28 @@ impl GIRGenerator {
 
         let value_type = value.get_type();
         let ret_type = self.cur_fn().borrow().ret_type.clone();
-        let value = self
-            .cast_or_none(value, &ret_type)
-            .or_error(&ret.cst, || GErr::E212 {
-                expected: ret_type.to_string(),
-                was: value_type.to_string(),
-            })?;
+        let value = self.cast_or_none(value, &ret_type).ok_or_else(|| {
+            let err = gir_err(
+                ret.cst.clone(),
+                GErr::E212 {
+                    expected: ret_type.to_string(),
+                    was: value_type.to_string(),
+                },
+            );
+            let ret_type_ast = self
+                .cur_fn()
+                .borrow()
+                .ast
+                .as_ref()
+                .and_then(|ast| ast.sig().ret_type());
+            match ret_type_ast {
+                Some(ty) => err.with_label(
+                    ErrorSpan::Span(ty.cst.text_range()),
+                    "expected because of this return type",
+                ),
+                None => err,
+            }
+        })?;
 
         Ok(Expr::ret(value))
     }
@@ -1096,17 +1632,39 @@ impl GIRGenerator {
                 Ok(Expr::var(Variable::Function(func)))
             }
 
-            (false, Ok(var)) => Ok(Expr::var(var)),
+            (false, Ok(var)) => {
+                if let Variable::Local(local) = &var {
+                    self.mark_used(local);
+                }
+                Ok(Expr::var(var))
+            }
 
             (_, Err(e)) => self
                 .symbol_with_type_args(&var.name(), var.type_args(), &var.cst)
-                .map(Expr::type_get)
+                .map(|ty| {
+                    self.reference_type(&var.cst, &ty);
+                    Expr::type_get(ty)
+                })
                 .map_err(|_| e),
         }
     }
 
     fn when(&mut self, when: &When) -> Res<Expr> {
         let value = self.expression(&when.condition());
+        if let Expr::Variable(Variable::Local(_)) = value {
+            return self.when_switch(when, value);
+        }
+
+        // Store the value in a variable to only evaluate it once,
+        // which also allows branches to smart cast and bind it
+        self.begin_scope();
+        let (value_store, value) = self.temp_variable(value, "when-value".into());
+        let switch = self.when_switch(when, Expr::lvar(&value));
+        self.end_scope();
+        Ok(Expr::Block(vec![value_store, switch?]))
+    }
+
+    fn when_switch(&mut self, when: &When, value: Expr) -> Res<Expr> {
         let cond_type = value.get_type();
 
         let mut cases = Vec::with_capacity(8);
@@ -1120,11 +1678,14 @@ impl GIRGenerator {
                 .map_or_else(Expr::none_const, |br| self.expression(&br)));
         }
 
+        // Smart casts from branches not matching
+        let mut narrowings = Vec::new();
         let (first_cond, mut first_val) =
-            self.when_branch(value.clone(), &cond_type, first.unwrap())?;
+            self.when_branch(value.clone(), &cond_type, first.unwrap(), &mut narrowings)?;
         let mut first_ty = first_val.get_type();
         for branch in iter {
-            let (cond, mut branch_val) = self.when_branch(value.clone(), &cond_type, branch)?;
+            let (cond, mut branch_val) =
+                self.when_branch(value.clone(), &cond_type, branch, &mut narrowings)?;
 
             if first_ty != Type::None {
                 let result = self.try_unify_type(first_val, branch_val);
@@ -1139,7 +1700,13 @@ impl GIRGenerator {
         }
 
         // TODO: Deduplicate this...
-        let mut else_br = when.else_branch().map(|e| self.expression(&e));
+        let mut else_br = when.else_branch().map(|e| {
+            self.begin_scope();
+            let mut else_block = self.apply_narrowings(mem::take(&mut narrowings));
+            else_block.push(self.located(&e));
+            self.end_scope();
+            Expr::Block(else_block)
+        });
         if let Some(branch_val) = &else_br {
             if first_ty != Type::None {
                 let result = self.try_unify_type(first_val, else_br.unwrap());
@@ -1167,7 +1734,9 @@ impl GIRGenerator {
         value: Expr,
         cond_type: &Type,
         branch: WhenBranch,
+        prev_narrowings: &mut Vec<Narrowing>,
     ) -> Res<(Expr, Expr)> {
+        let assign_mark = self.assigned_vars.len();
         let cond = branch.condition();
         // See note on `binary` about this
         let br_cond = match &cond {
@@ -1176,20 +1745,73 @@ impl GIRGenerator {
         };
 
         let br_type = br_cond.get_type();
-        if &br_type != cond_type && !br_type.is_type() {
+        if branch.is_type_test() && !br_type.is_type() {
+            self.err(branch.condition().cst(), GErr::E248);
+        } else if &br_type != cond_type
+            && !br_type.is_type()
+            && !(br_type.is_null() && cond_type.is_nullable())
+        {
             self.err(branch.cst(), GErr::E229);
         }
 
-        let op = if br_type.is_type() {
-            SyntaxKind::Is
+        let (op, binding_ty) = if br_type.is_type() {
+            (SyntaxKind::Is, *br_type.into_type())
         } else {
-            SyntaxKind::EqualEqual
+            (SyntaxKind::EqualEqual, cond_type.clone())
         };
-        let cond = self.binary_gir(&branch.condition().cst(), value, op, br_cond)?;
+        let cond = self.binary_gir(&branch.condition().cst(), value.clone(), op, br_cond)?;
+
+        // This branch only runs if all previous ones did not match
+        let mut narrowings = self.unassigned(
+            mem::take(prev_narrowings),
+            assign_mark..self.assigned_vars.len(),
+        );
 
         self.begin_scope();
-        let mut branch_list = self.smart_casts(&cond);
-        branch_list.push(self.expression(&branch.branch()));
+        let mut branch_list = self.apply_narrowings(narrowings.clone());
+        branch_list.extend(self.smart_casts(&cond, true, assign_mark));
+        if let Some(name) = branch.binding() {
+            let var = self.define_variable_(
+                LocalVariable {
+                    name,
+                    mutable: false,
+                    ty: binding_ty.clone(),
+                },
+                None,
+            );
+            self.definition(&branch.cst, Definition::Local(Rc::clone(&var)));
+            let value = if op == SyntaxKind::Is {
+                Expr::cast(value, binding_ty, CastType::Bitcast)
+            } else {
+                value
+            };
+            branch_list.push(Expr::store(Expr::lvar(&var), value, true));
+        }
+
+        // The guard needs the casts and binding, so they become part of the
+        // condition; the guard only runs if the value matched the branch.
+        let cond = match branch.guard() {
+            Some(guard) => {
+                let guard_val = self.expression(&guard);
+                if guard_val.get_type() != Type::Bool {
+                    self.err(guard.cst(), GErr::E220);
+                }
+                let mut guard_block = mem::take(&mut branch_list);
+                guard_block.push(guard_val);
+                Expr::if_(
+                    cond,
+                    Expr::Block(guard_block),
+                    Expr::Literal(Literal::Bool(false)),
+                    Some(Type::Bool),
+                )
+            }
+            None => cond,
+        };
+
+        narrowings.extend(self.narrowings(&cond, false, assign_mark));
+        *prev_narrowings = self.unassigned(narrowings, assign_mark..self.assigned_vars.len());
+
+        branch_list.push(self.located(&branch.branch()));
         let branch_val = Expr::Block(branch_list);
         self.end_scope();
 
@@ -1213,13 +1835,13 @@ impl GIRGenerator {
         let mut cases: Vec<&MutRc<ADT>> = cases.values().collect();
 
         for (cond, _) in when_cases.iter() {
-            let (op, right) = if let Expr::Binary {
-                operator, right, ..
-            } = cond
-            {
-                (operator, right)
-            } else {
-                panic!("Invalid when condition")
+            let (op, right) = match cond {
+                Expr::Binary {
+                    operator, right, ..
+                } => (operator, right),
+                // Guarded branches do not cover their case, since the guard can fail
+                Expr::If { .. } => continue,
+                _ => panic!("Invalid when condition"),
             };
 
             if *op != SyntaxKind::Is {
@@ -1236,16 +1858,39 @@ impl GIRGenerator {
     }
 
     fn var_def(&mut self, var: &ast::Variable) -> Res<Expr> {
-        let init = self.expression(&var.initializer());
-        let type_ = init.get_type();
+        let declared_ty = var._type().map(|ty| self.find_type(&ty)).transpose()?;
+        let init = match (var.maybe_initializer(), &declared_ty) {
+            (Some(init), _) => init,
+            (None, Some(ty)) => {
+                // Declaration without initializer, the flow
+                // pass ensures it is assigned before being read
+                self.define_variable(var.clone(), ty.clone());
+                return Ok(Expr::none_const());
+            }
+            // Only possible with a syntax error in the declaration
+            (None, None) => AExpr::missing(&var.cst),
+        };
+
+        let value = self.expression(&init);
+        let (value, type_) = match declared_ty {
+            Some(ty) => {
+                let (value, matching_types) = self.try_cast(value, &ty);
+                if !matching_types {
+                    return Err(gir_err(init.cst(), GErr::E201));
+                }
+                (value, ty)
+            }
+            None => {
+                let ty = value.get_type();
+                (value, ty)
+            }
+        };
+
         if type_.is_assignable() {
             let var = self.define_variable(var.clone(), type_);
-            Ok(Expr::store(Expr::lvar(&var), init, true))
+            Ok(Expr::store(Expr::lvar(&var), value, true))
         } else {
-            Err(gir_err(
-                var.initializer().cst(),
-                GErr::E230(type_.to_string()),
-            ))
+            Err(gir_err(init.cst(), GErr::E230(type_.to_string())))
         }
     }
 }
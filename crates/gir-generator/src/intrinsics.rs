@@ -11,7 +11,7 @@
 //! of the expression.
 
 use common::MutRc;
-use error::{Error, ErrorSpan, GErr, Res};
+use error::{Error, ErrorSpan, GErr, Res, Severity};
 use gir_nodes::{Function, Module, Type, ADT};
 use std::{cell::Ref, collections::HashMap, rc::Rc};
 use syntax::kind::SyntaxKind;
@@ -30,6 +30,10 @@ pub struct Intrinsics {
     pub(crate) iter_proto: Option<MutRc<ADT>>,
     /// `std/iter/ToIter` prototype
     pub(crate) to_iter_proto: Option<MutRc<ADT>>,
+    /// `std/iter/Range` prototype, used for range expressions ('a..b')
+    pub(crate) range_proto: Option<MutRc<ADT>>,
+    /// `std/collections/array/Array` prototype, iterated by index in for loops
+    pub(crate) array_proto: Option<MutRc<ADT>>,
     /// The Free interface, used while compiling a class destructor.
     pub(crate) free_iface: Option<MutRc<ADT>>,
     /// libc free.
@@ -89,6 +93,8 @@ impl Intrinsics {
             return Err(Error {
                 index: ErrorSpan::None,
                 kind: GErr::E101,
+                severity: Severity::Error,
+                details: Box::default(),
             });
         }
         Ok(())
@@ -1,10 +1,9 @@
-#![feature(try_find)]
 #![feature(box_syntax)]
 #![feature(box_patterns)]
 // Often required due to clones; also false positives from type aliases
 #![allow(clippy::ptr_arg)]
 
-use crate::intrinsics::Intrinsics;
+use crate::{intrinsics::Intrinsics, smart_casts::SmartCast};
 use common::{bench, mutrc_new, ModulePath, MutRc};
 use gir_nodes::{
     declaration::Visibility,
@@ -13,8 +12,10 @@ use gir_nodes::{
     types::{ToInstance, TypeParameterBound, TypeVariable},
     Declaration, Expr, Function, IFaceImpls, Instance, Module, Type, ADT,
 };
+pub use references::{Definition, Reference};
 use result::EmitGIRError;
 use std::{
+    cell::RefCell,
     collections::{HashMap, HashSet},
     mem,
     rc::Rc,
@@ -22,7 +23,7 @@ use std::{
 use syntax::kind::SyntaxKind;
 
 use ast::{CSTNode, Get};
-use error::{Error, Errors, GErr, Res};
+use error::{Error, ErrorSpan, Errors, GErr, LintLevels, Res};
 use gir_nodes::{
     declaration::{Field, LocalVariable, Variable},
     types::TypeParameters,
@@ -32,8 +33,11 @@ use smol_str::SmolStr;
 mod expr;
 mod intrinsics;
 mod passes;
+mod references;
 mod resolver;
 mod result;
+mod smart_casts;
+mod suggestions;
 mod types;
 
 /// A struct containing all data produced by GIR compilation.
@@ -41,6 +45,11 @@ pub struct CompiledGIR {
     pub modules: Vec<MutRc<Module>>,
     pub intrinsics: Intrinsics,
     pub iface_impls: HashMap<Type, MutRc<IFaceImpls>>,
+    /// Warnings produced during compilation.
+    /// Contains errors as well if produced by [GIRGenerator::analyze].
+    pub warnings: Vec<Errors>,
+    /// All names resolved, if enabled with [GIRFlags::references].
+    pub references: Vec<Reference>,
 }
 
 /// A struct containing various compiler flags
@@ -63,6 +72,13 @@ pub struct GIRFlags {
 
     /// Do not import the prelude into every module. no_std requires this.
     pub no_prelude: bool,
+
+    /// The level each lint is reported at.
+    pub lints: LintLevels,
+
+    /// Record the definition every name in source code resolves to,
+    /// see [Reference].
+    pub references: bool,
 }
 
 type Environment = HashMap<SmolStr, Rc<LocalVariable>>;
@@ -84,17 +100,30 @@ pub struct GIRGenerator {
 
     /// The current loop's type, if in one.
     current_loop_ty: Option<Type>,
-
-    /// All class members that are not initialized yet.
-    /// This is only used when generating constructors to check
-    /// that all constructors don't access uninitialized fields,
-    /// and initialize all fields when finished.
-    ///
-    /// Because of this, calling is_empty() on this set
-    /// can be used to determine if 'this' is fully
-    /// initialized yet and if methods can be used.
+    /// The amount of loops the current expression is in.
+    loop_depth: usize,
+
+    /// All smart cast variables, keyed by their address.
+    smart_cast_vars: HashMap<*const LocalVariable, SmartCast>,
+    /// All local variables assigned to, in order of assignment.
+    /// Used to prevent smart casts of variables changed since their condition.
+    assigned_vars: Vec<Rc<LocalVariable>>,
+
+    /// All class members the constructor currently generated
+    /// has to initialize. The flow pass uses this to check that
+    /// they are initialized on every path, and that they or
+    /// methods are not used before.
     uninitialized_this_fields: HashSet<Rc<Field>>,
 
+    /// All local variables declared by the user in the current function,
+    /// along with their declaration. Used for the unused variable lint.
+    declared_vars: Vec<(Rc<LocalVariable>, CSTNode)>,
+    /// All local variables that were read, keyed by their address.
+    used_vars: HashSet<*const LocalVariable>,
+    /// All functions that were referenced by name, keyed by their address.
+    /// Used for the unused function lint.
+    used_functions: HashSet<*const RefCell<Function>>,
+
     /// Closure-related data, if compiling a closure.
     closure_data: Option<ClosureData>,
 
@@ -114,14 +143,29 @@ pub struct GIRGenerator {
 
     /// Errors produced
     errors: MutRc<HashMap<ModulePath, Errors>>,
+    /// Names resolved, if recorded
+    references: MutRc<Vec<Reference>>,
 
     flags: GIRFlags,
 }
 
 impl GIRGenerator {
     /// Consumes AST modules given, processing them to GIR.
-    /// Returns errors if any occurred.
-    pub fn consume(mut self) -> Result<CompiledGIR, Vec<Errors>> {
+    /// Returns errors if any occurred, along with all warnings.
+    pub fn consume(self) -> Result<CompiledGIR, Vec<Errors>> {
+        let gir = self.analyze();
+        if !gir.warnings.iter().any(Errors::has_errors) {
+            Ok(gir)
+        } else {
+            Err(gir.warnings)
+        }
+    }
+
+    /// Consumes AST modules given, processing them to GIR.
+    /// Unlike [consume], this also returns the GIR if errors occurred,
+    /// which are then included in the warnings. Meant for tools
+    /// inspecting code that is still being written.
+    pub fn analyze(mut self) -> CompiledGIR {
         self.run_passes();
 
         for module in &self.modules_uncompiled {
@@ -134,14 +178,12 @@ impl GIRGenerator {
             .into_iter()
             .map(|(_, v)| v)
             .collect::<Vec<_>>();
-        if errs.is_empty() {
-            Ok(CompiledGIR {
-                modules: self.modules,
-                intrinsics: self.intrinsics,
-                iface_impls: self.iface_impls,
-            })
-        } else {
-            Err(errs)
+        CompiledGIR {
+            modules: self.modules,
+            intrinsics: self.intrinsics,
+            iface_impls: self.iface_impls,
+            warnings: errs,
+            references: self.references.take(),
         }
     }
 
@@ -161,7 +203,18 @@ impl GIRGenerator {
             mutable: ast.mutable(),
             ty,
         };
-        self.define_variable_(def, Some(&ast.cst))
+        if self
+            .environments
+            .iter()
+            .any(|env| env.contains_key(&def.name))
+        {
+            self.lint(ast.cst(), GErr::W005(def.name.clone()));
+        }
+
+        let var = self.define_variable_(def, Some(&ast.cst));
+        self.definition(&ast.cst, Definition::Local(Rc::clone(&var)));
+        self.declared_vars.push((Rc::clone(&var), ast.cst));
+        var
     }
 
     /// Defines a new variable. It is put into the variable list in the current function
@@ -251,10 +304,18 @@ impl GIRGenerator {
 
     /// Searches all scopes for a variable, starting at the top.
     fn find_var(&mut self, name: &SmolStr, cst: &CSTNode) -> Res<Variable> {
-        self.find_local_var(name, cst)
+        let var = self
+            .find_local_var(name, cst)
             .map(Variable::Local)
             .or_else(|| self.find_global_var(name))
-            .or_err(cst, GErr::E206(name.clone()))
+            .or_error(cst, || GErr::E206(name.clone(), self.suggest_var(name)))?;
+        let definition = match &var {
+            // Smart casts are the variable they are a cast of to the user
+            Variable::Local(local) => Definition::Local(self.cast_root(local)),
+            Variable::Function(func) => Definition::Function(Rc::clone(&func.ty)),
+        };
+        self.reference(cst, definition);
+        Ok(var)
     }
 
     /// Searches for a local variable.
@@ -279,10 +340,13 @@ impl GIRGenerator {
         None
     }
 
-    fn find_global_var(&self, name: &SmolStr) -> Option<Variable> {
+    fn find_global_var(&mut self, name: &SmolStr) -> Option<Variable> {
         let decl = self.module.borrow().find_decl(name)?;
         match decl {
-            Declaration::Function(func) => Some(Variable::Function(Instance::new_(func))),
+            Declaration::Function(func) => {
+                self.used_functions.insert(Rc::as_ptr(&func));
+                Some(Variable::Function(Instance::new_(func)))
+            }
             _ => None,
         }
     }
@@ -309,15 +373,26 @@ impl GIRGenerator {
     /// Does visibility checks.
     fn get_field(&mut self, ty: &Type, get: &Get) -> Res<FieldOrMethod> {
         let field = self.get_field_(ty, get)?;
-        let visibility = match &field {
-            FieldOrMethod::Field(field) => field.visibility,
+        let (visibility, definition) = match &field {
+            FieldOrMethod::Field(field) => (field.visibility, Definition::Field(Rc::clone(field))),
             FieldOrMethod::Method(method)
             | FieldOrMethod::VirtMethod(ConcreteMethodGet {
                 iface_method: method,
                 ..
-            }) => method.borrow().visibility,
+            }) => (
+                method.borrow().visibility,
+                Definition::Function(Rc::clone(method)),
+            ),
         };
 
+        self.check_visibility(visibility, ty, get.cst())?;
+        self.reference(&get.property().cst, definition);
+        Ok(field)
+    }
+
+    /// Ensures that a field or method of the given type
+    /// with the given visibility is visible at this location.
+    fn check_visibility(&self, visibility: Visibility, ty: &Type, cst: CSTNode) -> Res<()> {
         let allowed = match visibility {
             Visibility::Private => {
                 match (&self.ty_position, &ty) {
@@ -335,9 +410,9 @@ impl GIRGenerator {
         };
 
         if allowed {
-            Ok(field)
+            Ok(())
         } else {
-            Err(gir_err(get.cst(), GErr::E240))
+            Err(gir_err(cst, GErr::E240))
         }
     }
 
@@ -350,8 +425,76 @@ impl GIRGenerator {
             }
         }
 
-        self.find_associated_method(&ty, &get.property().name())
-            .or_err(&get.cst, GErr::E210)
+        let name = get.property().name();
+        if let Some(method) = self.find_associated_method(&ty, &name) {
+            return Ok(method);
+        }
+        self.find_extension_method(&ty, &name, &get.cst)?
+            .map(FieldOrMethod::Method)
+            .or_error(&get.cst, || {
+                GErr::E210(name.clone(), self.suggest_member(ty, &name))
+            })
+    }
+
+    /// Searches for an extension method on a type, only considering
+    /// extensions in the current module and modules imported by it.
+    /// Extensions on generic ADTs apply to all of their instances.
+    fn find_extension_method(
+        &self,
+        ty: &Type,
+        name: &SmolStr,
+        cst: &CSTNode,
+    ) -> Res<Option<MutRc<Function>>> {
+        let generic = ty
+            .try_adt()
+            .filter(|adt| !adt.args().is_empty())
+            .map(|adt| adt.ty.to_type());
+        let impls = self
+            .maybe_get_iface_impls(ty)
+            .into_iter()
+            .chain(generic.and_then(|ty| self.maybe_get_iface_impls(&ty)))
+            .collect::<Vec<_>>();
+        let impls = impls.iter().map(|impls| impls.borrow()).collect::<Vec<_>>();
+        let visible = self.extension_sources();
+
+        let candidates = impls
+            .iter()
+            .flat_map(|impls| impls.inherent.iter())
+            .filter(|im| im.is_extension())
+            .filter(|im| visible.iter().any(|src| Rc::ptr_eq(src, &im.module)))
+            .filter_map(|im| im.methods.get(name).map(|method| (im, method)))
+            .collect::<Vec<_>>();
+
+        match &candidates[..] {
+            [] => Ok(None),
+            [(_, method)] => Ok(Some(Rc::clone(*method))),
+            _ => Err(gir_err(
+                cst.clone(),
+                GErr::E242 {
+                    name: name.clone(),
+                    modules: candidates
+                        .iter()
+                        .map(|(im, _)| im.module.borrow().path.to_string().into())
+                        .collect(),
+                },
+            )),
+        }
+    }
+
+    /// Returns all modules whose extensions are visible in the current module:
+    /// the module itself, all modules it imports from, and all modules
+    /// re-exported by those.
+    fn extension_sources(&self) -> Vec<MutRc<Module>> {
+        let mut visible = vec![Rc::clone(&self.module)];
+        let mut queue = self.module.borrow().imports.sources.clone();
+        while let Some(module) = queue.pop() {
+            if visible.iter().any(|m| Rc::ptr_eq(m, &module)) {
+                continue;
+            }
+            queue.extend(module.borrow().exports.sources.iter().cloned());
+            visible.push(module);
+        }
+        visible
     }
 
     /// Searches for an associated method on a type. Can be either an interface
@@ -441,6 +584,17 @@ impl GIRGenerator {
         self.error_(error, &self.module.borrow())
     }
 
+    /// Adds a label pointing at a node of the given module to the error.
+    fn label_in(error: Error, cst: &CSTNode, module: &MutRc<Module>, message: &str) -> Error {
+        let module = module.borrow();
+        error.with_label_in(
+            ErrorSpan::Span(cst.text_range()),
+            message,
+            &module.src,
+            format!("{}", module.path),
+        )
+    }
+
     fn error_(&self, error: Error, module: &Module) {
         let mut errs = self.errors.borrow_mut();
         if let Some(errs) = errs.get_mut(&self.path) {
@@ -514,6 +668,10 @@ impl GIRGenerator {
                 outer_env: mem::replace(&mut outer.environments, vec![]),
                 captured: Vec::with_capacity(3),
             }),
+            module: Rc::clone(&outer.module),
+            path: Rc::clone(&outer.path),
+            errors: Rc::clone(&outer.errors),
+            references: Rc::clone(&outer.references),
             ..Self::from_modules_(modules, modules_uncompiled, outer.flags)
         }
     }
@@ -525,6 +683,10 @@ impl GIRGenerator {
         outer.environments = mem::replace(&mut closure_data.outer_env, vec![]);
         outer.modules = self.modules;
         outer.modules_uncompiled = self.modules_uncompiled;
+        outer.used_functions.extend(self.used_functions);
+        for var in &closure_data.captured {
+            outer.mark_used(var);
+        }
         closure_data
     }
 
@@ -557,9 +719,16 @@ impl GIRGenerator {
             environments: vec![HashMap::with_capacity(3)],
             type_params: None,
             current_loop_ty: None,
+            loop_depth: 0,
+            smart_cast_vars: HashMap::new(),
+            assigned_vars: Vec::new(),
             uninitialized_this_fields: HashSet::with_capacity(5),
+            declared_vars: Vec::new(),
+            used_vars: HashSet::new(),
+            used_functions: HashSet::new(),
             closure_data: None,
             errors: mutrc_new(HashMap::new()),
+            references: mutrc_new(Vec::new()),
             flags,
         }
     }
@@ -583,9 +752,16 @@ impl GIRGenerator {
             environments: vec![HashMap::with_capacity(3)],
             type_params: None,
             current_loop_ty: None,
+            loop_depth: 0,
+            smart_cast_vars: HashMap::new(),
+            assigned_vars: Vec::new(),
             uninitialized_this_fields: HashSet::with_capacity(5),
+            declared_vars: Vec::new(),
+            used_vars: HashSet::new(),
+            used_functions: HashSet::new(),
             closure_data: None,
             errors: mutrc_new(HashMap::new()),
+            references: mutrc_new(Vec::new()),
             flags,
         }
     }
@@ -1,14 +1,14 @@
 use std::{cell::RefCell, collections::HashMap, rc::Rc};
 
-use crate::{eat, eatc, result::EmitGIRError, GIRGenerator};
+use crate::{eat, eatc, result::EmitGIRError, Definition, GIRGenerator};
 use ast::CSTNode;
 use common::{mutrc_new, MutRc};
 use error::{GErr, Res};
 use gir_nodes::{
     declaration::{ADTType, CaseType, IRAdt, IRFunction, LocalVariable, Visibility},
     gir_err,
-    types::{TypeKind, TypeParameter, TypeParameterBound, TypeParameters},
-    Declaration, Function, IFaceImpl, Type, ADT,
+    types::{ToInstance, TypeKind, TypeParameter, TypeParameterBound, TypeParameters},
+    Declaration, Function, IFaceImpl, InherentImpl, Type, ADT,
 };
 use indexmap::IndexMap;
 use smol_str::SmolStr;
@@ -86,6 +86,7 @@ impl GIRGenerator {
             type_kind,
             fields: IndexMap::with_capacity(10),
             methods: IndexMap::with_capacity(10),
+            static_methods: IndexMap::new(),
             constructors: Vec::with_capacity(5),
             ir: IRAdt::new(!type_parameters.is_empty()),
             type_parameters,
@@ -98,6 +99,7 @@ impl GIRGenerator {
             .borrow_mut()
             .declarations
             .insert(adt_name, Declaration::Adt(Rc::clone(&adt)));
+        self.definition(&name.cst, Definition::Adt(Rc::clone(&adt)));
 
         self.maybe_enum_cases(&adt)?;
         Ok(adt)
@@ -161,11 +163,15 @@ impl GIRGenerator {
     }
 
     fn declare_impl(&mut self, iface_impl: ast::IfaceImpl) {
+        let iface_ast = match iface_impl.iface() {
+            Some(iface) => iface,
+            None => return self.declare_inherent_impl(iface_impl),
+        };
         let implementor = eat!(self, self.find_type(&iface_impl.implementor()));
 
-        let iface = eat!(self, self.find_type(&iface_impl.iface()));
+        let iface = eat!(self, self.find_type(&iface_ast));
         if !iface.is_adt() || !iface.as_adt().ty.borrow().ty.is_interface() {
-            self.err(iface_impl.iface().cst(), GErr::E307);
+            self.err(iface_ast.cst(), GErr::E307);
             return;
         }
         let iface_adt = iface.as_adt();
@@ -184,10 +190,50 @@ impl GIRGenerator {
             .insert(iface, gir_impl)
             .is_some();
         if already_defined {
-            self.err(iface_impl.iface().cst, GErr::E306);
+            self.err(iface_ast.cst, GErr::E306);
         }
     }
 
+    /// Declares an inherent impl. Impls on generic ADTs can use the type parameters
+    /// of the ADT (`impl Box[T]`); they apply to all instances of the ADT.
+    fn declare_inherent_impl(&mut self, ast: ast::IfaceImpl) {
+        let implementor_ast = ast.implementor();
+        let adt = match implementor_ast.get() {
+            ast::TypeE::Generic { ident, .. } => {
+                self.symbol(&ident).filter(Type::is_adt).map(Type::into_adt)
+            }
+            _ => None,
+        };
+
+        let implementor = match &adt {
+            Some(adt) => {
+                let outer = self.type_params.take();
+                self.set_context(&Rc::clone(&adt.ty.borrow().type_parameters));
+                let implementor = self.find_type(&implementor_ast);
+                self.type_params = outer;
+                implementor
+            }
+            None => self.find_type(&implementor_ast),
+        };
+        let implementor = eat!(self, implementor);
+
+        let is_generic = implementor
+            .type_args()
+            .map_or(false, |args| args.iter().any(Type::is_variable));
+        let key = match adt {
+            Some(adt) if is_generic => adt.ty.to_type(),
+            _ => implementor.clone(),
+        };
+        let impls = self.get_iface_impls(&key);
+        let gir_impl = InherentImpl {
+            implementor,
+            methods: HashMap::with_capacity(ast.methods().count()),
+            module: Rc::clone(&self.module),
+            ast,
+        };
+        impls.borrow_mut().inherent.push(gir_impl);
+    }
+
     pub(super) fn declare_functions(&mut self, ast: &ast::Module) {
         for ast in ast.functions() {
             eatc!(self, self.declare_function(ast));
@@ -7,7 +7,7 @@ use gir_nodes::{
 };
 use std::{cell::RefCell, rc::Rc};
 
-use crate::{eat, GIRGenerator};
+use crate::{eat, Definition, GIRGenerator};
 
 impl GIRGenerator {
     pub(super) fn insert_adt_fields(&mut self, adt: &MutRc<ADT>) {
@@ -65,6 +65,7 @@ impl GIRGenerator {
                 index,
             });
 
+            self.definition(&field.cst, Definition::Field(Rc::clone(&member)));
             let existing_entry = adt.fields.insert(field.name(), Rc::clone(&member));
             if existing_entry.is_some() {
                 self.err(field.cst(), GErr::E235);
@@ -0,0 +1,399 @@
+//! Control-flow analysis over the GIR of a function.
+//! It follows every path through a function after its body was generated,
+//! finding paths that end without a return value, locals that are read
+//! before being assigned and constructors that do not initialize
+//! all fields on every path.
+
+use crate::GIRGenerator;
+use ast::CSTNode;
+use common::MutRc;
+use error::GErr;
+use gir_nodes::{
+    declaration::{Field, LocalVariable, Variable},
+    expression::Intrinsic,
+    Expr, Function, Literal, Type,
+};
+use std::{collections::HashSet, mem, rc::Rc};
+
+/// The state of a single path through a function.
+#[derive(Clone, Default)]
+struct Flow {
+    /// If the path left through `return` or `break`,
+    /// making the code after it unreachable.
+    ended: bool,
+    /// Locals assigned on this path.
+    locals: HashSet<*const LocalVariable>,
+    /// Fields of `this` initialized on this path.
+    fields: HashSet<*const Field>,
+    /// Fields of `this` initialized on at least one of the paths joined into this one.
+    maybe_fields: HashSet<*const Field>,
+}
+
+impl Flow {
+    /// Joins another path into this one, keeping only what holds on both.
+    fn join(&mut self, other: Flow) {
+        if other.ended {
+            return;
+        }
+        if self.ended {
+            *self = other;
+            return;
+        }
+        self.locals.retain(|l| other.locals.contains(l));
+        self.fields.retain(|f| other.fields.contains(f));
+        self.maybe_fields.extend(other.maybe_fields);
+    }
+}
+
+/// The analysis of a single function.
+struct FlowAnalysis {
+    /// Locals declared in the function that need to be assigned before use.
+    /// Parameters and variables captured by closures are not included.
+    locals: HashSet<*const LocalVariable>,
+    /// The receiver of the constructor being analysed.
+    this: Option<Rc<LocalVariable>>,
+    /// The fields the constructor needs to initialize.
+    fields: Vec<Rc<Field>>,
+    /// Paths leaving the loops currently analysed with `break`, innermost last.
+    breaks: Vec<Vec<Flow>>,
+    /// Paths leaving the function with `return`.
+    returns: Vec<Flow>,
+    /// Location of the innermost statement currently analysed.
+    location: CSTNode,
+    errors: Vec<(CSTNode, GErr)>,
+}
+
+impl FlowAnalysis {
+    fn expr(&mut self, expr: &Expr, flow: &mut Flow) {
+        match expr {
+            Expr::Block(exprs) | Expr::Allocate { args: exprs, .. } => self.exprs(exprs, flow),
+
+            Expr::Literal(_) | Expr::TypeGet(_) | Expr::Variable(Variable::Function(_)) => (),
+
+            Expr::Variable(Variable::Local(var)) => self.read(var, flow),
+
+            Expr::Load { object, field } if self.is_this(object) => self.read_field(field, flow),
+
+            Expr::Load { object, .. } => self.expr(object, flow),
+
+            Expr::Store {
+                location, value, ..
+            } => match &**location {
+                Expr::Variable(Variable::Local(var)) => {
+                    self.expr(value, flow);
+                    flow.locals.insert(Rc::as_ptr(var));
+                }
+
+                Expr::Load { object, field } if self.is_this(object) => {
+                    self.expr(value, flow);
+                    self.init_field(field, flow);
+                }
+
+                _ => {
+                    self.expr(location, flow);
+                    self.expr(value, flow);
+                }
+            },
+
+            Expr::Binary { left, right, .. } => {
+                self.expr(left, flow);
+                self.expr(right, flow);
+            }
+
+            Expr::Unary { right, .. } => self.expr(right, flow),
+
+            Expr::Call { callee, arguments } => {
+                self.expr(callee, flow);
+                self.exprs(arguments, flow);
+            }
+
+            Expr::If {
+                condition,
+                then_branch,
+                else_branch,
+                ..
+            } => {
+                self.expr(condition, flow);
+                let mut else_flow = flow.clone();
+                self.expr(then_branch, flow);
+                self.expr(else_branch, &mut else_flow);
+                flow.join(else_flow);
+            }
+
+            // Conditions are tested in order until one holds,
+            // the else branch runs after all of them were tested
+            Expr::Switch {
+                branches,
+                else_branch,
+                ..
+            } => {
+                let mut branch_flows = Vec::with_capacity(branches.len());
+                for (condition, branch) in branches {
+                    let mut branch_flow = self.condition(condition, flow);
+                    self.expr(branch, &mut branch_flow);
+                    branch_flows.push(branch_flow);
+                }
+                self.expr(else_branch, flow);
+                for branch_flow in branch_flows {
+                    flow.join(branch_flow);
+                }
+            }
+
+            // The loop is left when the condition fails before the first iteration
+            // (running the else branch) or after any other, or through `break`
+            Expr::Loop {
+                condition,
+                body,
+                else_branch,
+                ..
+            } => {
+                self.expr(condition, flow);
+                let mut body_flow = flow.clone();
+                self.breaks.push(Vec::new());
+                self.expr(body, &mut body_flow);
+                let breaks = self.breaks.pop().unwrap();
+
+                self.expr(else_branch, flow);
+                flow.join(body_flow);
+                for break_flow in breaks {
+                    flow.join(break_flow);
+                }
+            }
+
+            Expr::Break(value) => {
+                self.expr(value, flow);
+                if let Some(breaks) = self.breaks.last_mut() {
+                    breaks.push(mem::take(flow));
+                }
+                flow.ended = true;
+            }
+
+            Expr::Return(value) => {
+                self.expr(value, flow);
+                self.returns.push(mem::take(flow));
+                flow.ended = true;
+            }
+
+            Expr::Cast { inner, .. } => self.expr(inner, flow),
+
+            // Captured variables are read when the closure is created
+            Expr::Closure { captured, .. } => {
+                for var in captured.iter() {
+                    self.read(var, flow);
+                }
+            }
+
+            Expr::Intrinsic(intrinsic) => match intrinsic {
+                Intrinsic::IncRc(inner) | Intrinsic::DecRc(inner) | Intrinsic::Free(inner) => {
+                    self.expr(inner, flow)
+                }
+                Intrinsic::IfaceCall {
+                    iface, arguments, ..
+                } => {
+                    self.expr(iface, flow);
+                    self.exprs(arguments, flow);
+                }
+                Intrinsic::ConcreteMethodGet(_) => (),
+            },
+
+            Expr::Located { inner, cst } => {
+                let outer = mem::replace(&mut self.location, cst.clone());
+                self.expr(inner, flow);
+                self.location = outer;
+            }
+        }
+    }
+
+    /// Analyses a condition, returning the path on which it holds;
+    /// `flow` is left at the path after it, whether it held or not.
+    /// Conditions like `if (a) b else false` (guards of when branches)
+    /// only hold if `b` ran, so everything assigned by it can be used.
+    fn condition(&mut self, condition: &Expr, flow: &mut Flow) -> Flow {
+        match condition {
+            Expr::If {
+                condition,
+                then_branch,
+                else_branch: box Expr::Literal(Literal::Bool(false)),
+                ..
+            } => {
+                self.expr(condition, flow);
+                let mut holds = flow.clone();
+                self.expr(then_branch, &mut holds);
+                flow.join(holds.clone());
+                holds
+            }
+
+            _ => {
+                self.expr(condition, flow);
+                flow.clone()
+            }
+        }
+    }
+
+    fn exprs(&mut self, exprs: &[Expr], flow: &mut Flow) {
+        for expr in exprs {
+            self.expr(expr, flow);
+        }
+    }
+
+    /// Reads a local, which has to be assigned on this path.
+    /// Using `this` in a constructor for anything besides its fields
+    /// requires all fields to be initialized.
+    fn read(&mut self, var: &Rc<LocalVariable>, flow: &Flow) {
+        if flow.ended {
+            return;
+        }
+
+        if self.locals.contains(&Rc::as_ptr(var)) && !flow.locals.contains(&Rc::as_ptr(var)) {
+            self.error(GErr::E251(var.name.clone()));
+        } else if self.is_this_var(var) && self.uninitialized(flow).next().is_some() {
+            self.error(GErr::E203);
+        }
+    }
+
+    fn read_field(&mut self, field: &Rc<Field>, flow: &Flow) {
+        if !flow.ended && self.is_required(field) && !flow.fields.contains(&Rc::as_ptr(field)) {
+            self.error(GErr::E222);
+        }
+    }
+
+    fn init_field(&mut self, field: &Rc<Field>, flow: &mut Flow) {
+        if !self.is_required(field) {
+            return;
+        }
+
+        let ptr = Rc::as_ptr(field);
+        if !flow.ended && !field.mutable && flow.maybe_fields.contains(&ptr) {
+            self.error(GErr::E200("immutable field"));
+        }
+        flow.fields.insert(ptr);
+        flow.maybe_fields.insert(ptr);
+    }
+
+    /// Returns all fields the constructor needs to initialize
+    /// that are not initialized on the given path.
+    fn uninitialized<'a>(&'a self, flow: &'a Flow) -> impl Iterator<Item = &'a Rc<Field>> {
+        self.fields
+            .iter()
+            .filter(move |f| !flow.fields.contains(&Rc::as_ptr(f)))
+    }
+
+    fn is_required(&self, field: &Rc<Field>) -> bool {
+        self.fields.iter().any(|f| Rc::ptr_eq(f, field))
+    }
+
+    fn is_this(&self, expr: &Expr) -> bool {
+        matches!(expr, Expr::Variable(Variable::Local(var)) if self.is_this_var(var))
+    }
+
+    fn is_this_var(&self, var: &Rc<LocalVariable>) -> bool {
+        self.this
+            .as_ref()
+            .map_or(false, |this| Rc::ptr_eq(this, var))
+    }
+
+    fn error(&mut self, err: GErr) {
+        self.errors.push((self.location.clone(), err))
+    }
+}
+
+impl GIRGenerator {
+    /// Runs the flow analysis on a function whose body was generated,
+    /// reporting locals that might be read before being assigned.
+    /// On constructors, the fields in `uninitialized_this_fields` must not be
+    /// read before being initialized and must be initialized on every path.
+    pub(crate) fn check_flow(&mut self, function: &MutRc<Function>, ast: &ast::Function) {
+        let func = function.borrow();
+        let fields = self
+            .uninitialized_this_fields
+            .iter()
+            .cloned()
+            .collect::<Vec<_>>();
+        let mut analysis = FlowAnalysis {
+            locals: func.variables.values().map(Rc::as_ptr).collect(),
+            this: func
+                .parameters
+                .first()
+                .filter(|_| !fields.is_empty())
+                .cloned(),
+            fields,
+            breaks: Vec::new(),
+            returns: Vec::new(),
+            location: ast.sig().cst,
+            errors: Vec::new(),
+        };
+
+        let mut flow = Flow::default();
+        analysis.exprs(&func.exprs, &mut flow);
+        drop(func);
+        for ret_flow in mem::take(&mut analysis.returns) {
+            flow.join(ret_flow);
+        }
+
+        let uninitialized = analysis
+            .uninitialized(&flow)
+            .map(|f| f.name.clone())
+            .collect::<Vec<_>>();
+        if !flow.ended && !uninitialized.is_empty() {
+            analysis
+                .errors
+                .push((ast.sig().cst, GErr::E309(uninitialized)));
+        }
+
+        for (cst, err) in analysis.errors {
+            self.err(cst, err);
+        }
+    }
+
+    /// Returns the ends of all paths through the given function body
+    /// that finish without a value, where `None` is the body itself.
+    /// Paths leaving with `return` or `break` are not included.
+    pub(crate) fn missing_values(body: &Expr) -> Vec<Option<CSTNode>> {
+        let mut ends = Vec::new();
+        Self::find_missing_values(body, None, &mut ends);
+        ends
+    }
+
+    fn find_missing_values(
+        expr: &Expr,
+        location: Option<&CSTNode>,
+        ends: &mut Vec<Option<CSTNode>>,
+    ) {
+        if expr.get_type() != Type::None || Self::diverges(expr) {
+            return;
+        }
+
+        match expr {
+            Expr::Located { inner, cst } => Self::find_missing_values(inner, Some(cst), ends),
+
+            Expr::Block(exprs) => Self::find_missing_values(exprs.last().unwrap(), location, ends),
+
+            Expr::If {
+                then_branch,
+                else_branch,
+                ..
+            }
+            | Expr::Loop {
+                body: then_branch,
+                else_branch,
+                ..
+            } => {
+                Self::find_missing_values(then_branch, location, ends);
+                Self::find_missing_values(else_branch, location, ends);
+            }
+
+            Expr::Switch {
+                branches,
+                else_branch,
+                ..
+            } => {
+                for (_, branch) in branches {
+                    Self::find_missing_values(branch, location, ends);
+                }
+                Self::find_missing_values(else_branch, location, ends);
+            }
+
+            _ => ends.push(location.cloned()),
+        }
+    }
+}
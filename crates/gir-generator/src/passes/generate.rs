@@ -1,6 +1,6 @@
 use std::rc::Rc;
 
-use crate::GIRGenerator;
+use crate::{Definition, GIRGenerator};
 use common::MutRc;
 use error::GErr;
 use gir_nodes::{
@@ -22,6 +22,9 @@ impl GIRGenerator {
                 for (index, method) in adt.methods.values().enumerate() {
                     self.generate_function_(method, Some(index));
                 }
+                for method in adt.static_methods.values() {
+                    self.generate_function(method);
+                }
                 self.ty_position = None;
             }
         }
@@ -48,6 +51,18 @@ impl GIRGenerator {
             }
         }
         self.ty_position = None;
+
+        // Extension methods do not get access to private fields
+        for im in impls
+            .inherent
+            .iter()
+            .filter(|im| im.is_extension() && !im.module.borrow().compiled)
+        {
+            self.switch_module(Rc::clone(&im.module));
+            for method in im.methods.values() {
+                self.generate_function(method);
+            }
+        }
     }
 
     pub(crate) fn generate_function(&mut self, function: &MutRc<Function>) {
@@ -62,6 +77,7 @@ impl GIRGenerator {
         }
 
         self.prepare_function(&function);
+        self.check_param_defaults(function);
         let ast = function.borrow().ast.clone();
 
         let body = match (ast.as_ref().map(|a| a.body()).flatten(), method_index) {
@@ -71,23 +87,76 @@ impl GIRGenerator {
         };
 
         let ret_type = function.borrow().ret_type.clone();
-        if ret_type == Type::None {
+        if ret_type == Type::None || Self::diverges(&body) {
+            // A body that returns on every path has no value of its own
             self.insert_at_ptr(body)
         } else {
             let (body, success) = self.try_cast(body, &ret_type);
+            if !success {
+                self.body_type_err(&body, ast.as_ref().unwrap(), &ret_type);
+            }
+            self.insert_at_ptr(Expr::ret(body));
+        }
+
+        self.end_scope();
+        if let Some(ast) = &ast {
+            self.check_flow(function, ast);
+        }
+        self.check_unused_vars();
+    }
+
+    /// Reports a function body not matching the return type. If the body
+    /// is missing a value on some paths, those paths are reported instead.
+    fn body_type_err(&mut self, body: &Expr, ast: &ast::Function, ret_type: &Type) {
+        let ends = Self::missing_values(body);
+        if ends.is_empty() {
+            self.err(
+                ast.sig().name().cst,
+                GErr::E310 {
+                    expected: ret_type.to_string(),
+                    was: body.get_type().to_string(),
+                },
+            );
+        }
+        for end in ends {
+            self.err(end.unwrap_or_else(|| ast.sig().name().cst), GErr::E250);
+        }
+    }
+
+    /// Ensures that the default values of all parameters have the type
+    /// of their parameter, even if they are never used by a call.
+    /// Must be called on the function being generated; code generated for
+    /// the defaults is discarded, as calls generate them again.
+    fn check_param_defaults(&mut self, function: &MutRc<Function>) {
+        let defaults = Self::param_defaults(&function.borrow());
+        let params = function.borrow().parameters.clone();
+        let skip = params.len() - defaults.len();
+        let expr_count = function.borrow().exprs.len();
+        let variables = function.borrow().variables.clone();
+
+        for (param, default) in params[skip..].iter().zip(defaults) {
+            let default = match default {
+                // Generic parameters can only be checked once instantiated at the call site
+                Some(default) if !param.ty.is_variable() => default,
+                _ => continue,
+            };
+            let value = self.default_arg(function, &default);
+            let was = value.get_type();
+            let (_, success) = self.try_cast(value, &param.ty);
             if !success {
                 self.err(
-                    ast.unwrap().sig().name().cst,
-                    GErr::E310 {
-                        expected: ret_type.to_string(),
-                        was: body.get_type().to_string(),
+                    default.cst(),
+                    GErr::E252 {
+                        expected: param.ty.to_string(),
+                        was: was.to_string(),
                     },
                 );
             }
-            self.insert_at_ptr(Expr::ret(body));
         }
 
-        self.end_scope();
+        let mut function = function.borrow_mut();
+        function.exprs.truncate(expr_count);
+        function.variables = variables;
     }
 
     /// This method generates the method body for an iface function.
@@ -117,19 +186,23 @@ impl GIRGenerator {
             .zip(adt.constructors.iter())
         {
             self.prepare_function(constructor);
+            self.check_param_defaults(constructor);
             self.insert_at_ptr(Expr::none_const());
 
             // AST must be optional as the autogenerated default constructor does not have one
-            if let Some(ast) = ast {
-                self.set_uninitialized_members(&ast, &adt.fields);
+            if let Some(ast) = &ast {
+                self.set_uninitialized_members(ast, &adt.fields);
                 if let Some(body) = &ast.body() {
                     let body = self.expression(&body);
                     self.insert_at_ptr(body);
                 }
-                self.check_no_uninitialized(&ast);
             }
 
             self.end_scope();
+            if let Some(ast) = &ast {
+                self.check_flow(constructor, ast);
+            }
+            self.check_unused_vars();
         }
 
         self.uninitialized_this_fields.clear();
@@ -155,21 +228,6 @@ impl GIRGenerator {
         }
     }
 
-    /// Creates an error if any field is still uninitialized after a constructor.
-    fn check_no_uninitialized(&mut self, err_ast: &ast::Function) {
-        if !self.uninitialized_this_fields.is_empty() {
-            self.err(
-                err_ast.sig().cst,
-                GErr::E309(
-                    self.uninitialized_this_fields
-                        .iter()
-                        .map(|f| f.name.clone())
-                        .collect(),
-                ),
-            )
-        }
-    }
-
     /// Will append an 'entry' block to the fn and set the pointer at
     /// that location, then insert all parameters as variables.
     fn prepare_function(&mut self, function: &MutRc<Function>) {
@@ -180,5 +238,14 @@ impl GIRGenerator {
         for param in &func.parameters {
             self.insert_variable(&param, false, err_ast.as_ref());
         }
+
+        if let Some(sig) = func.ast.as_ref().map(ast::Function::sig) {
+            self.definition(&sig.name().cst, Definition::Function(Rc::clone(function)));
+            for ast in sig.parameters() {
+                if let Some(param) = func.parameters.iter().find(|p| p.name == ast.name()) {
+                    self.definition(&ast.cst, Definition::Local(Rc::clone(param)));
+                }
+            }
+        }
     }
 }
@@ -1,8 +1,9 @@
 use std::{mem, rc::Rc};
 
 use common::{ModPath, MutRc};
-use error::{GErr, Res};
+use error::{GErr, Res, Suggestion};
 use gir_nodes::{
+    gir_err,
     module::{Imports, UnresolvedImport},
     Module,
 };
@@ -19,67 +20,114 @@ impl GIRGenerator {
         let ast = &ast_borrow.0;
 
         for import in ast.imports() {
-            let mut path = import.parts().collect::<Vec<_>>();
-            let symbol = path.pop().unwrap();
-            let path = ModPath::from(path);
-
+            let path = eatc!(self, Self::import_path(&module.borrow(), &import));
             let src_module_rc = eatc!(self, self.find_module(&path, &import));
+            {
+                let mut module = module.borrow_mut();
+                module.imports.sources.push(Rc::clone(src_module_rc));
+                if import.is_export() {
+                    module.exports.sources.push(Rc::clone(src_module_rc));
+                }
+            }
             let src_module = src_module_rc.borrow();
 
-            if symbol == "+" {
+            if import.is_wildcard() {
                 Self::get_imports(&mut module.borrow_mut(), import.is_export())
                     .modules
                     .push(src_module_rc.clone());
-            } else {
-                let decl = src_module.find_import(&symbol);
-                if let Some(decl) = decl {
-                    self.try_reserve_name(&import.cst, &symbol);
-                    Self::get_imports(&mut module.borrow_mut(), import.is_export())
-                        .decls
-                        .insert(symbol, decl);
-                    continue;
-                }
+                module
+                    .borrow_mut()
+                    .imports
+                    .unresolved
+                    .push(UnresolvedImport {
+                        ast: import,
+                        module: Rc::clone(&src_module_rc),
+                        symbol: None,
+                    });
+                continue;
             }
 
-            module
-                .borrow_mut()
-                .imports
-                .unresolved
-                .push(UnresolvedImport {
-                    ast: import,
-                    module: Rc::clone(&src_module_rc),
-                    symbol,
-                })
+            for symbol in import.symbols() {
+                if !self.import_symbol(&module, &import, &src_module, &symbol) {
+                    module
+                        .borrow_mut()
+                        .imports
+                        .unresolved
+                        .push(UnresolvedImport {
+                            ast: import.clone(),
+                            module: Rc::clone(&src_module_rc),
+                            symbol: Some(symbol),
+                        })
+                }
+            }
         }
 
         let mut module = module.borrow_mut();
         if !self.flags.no_prelude && !module.path.is(&["std", "prelude"]) {
-            module
-                .imports
-                .modules
-                .push(self.intrinsics.std_prelude.clone().unwrap());
+            let prelude = self.intrinsics.std_prelude.clone().unwrap();
+            module.imports.sources.push(Rc::clone(&prelude));
+            module.imports.modules.push(prelude);
         }
         module.return_ast(ast_borrow);
     }
 
-    pub(super) fn import_stage_2(&mut self, module: MutRc<Module>) {
-        let remaining_imports = mem::replace(&mut module.borrow_mut().imports.unresolved, vec![]);
+    /// Resolves all imports that could not be resolved in stage 1,
+    /// like imports of functions. Re-exports can depend on imports of other
+    /// modules that were not resolved yet, so this is repeated until
+    /// nothing changes; only the imports left after that are errors.
+    pub(super) fn import_stage_2(&mut self) {
+        let mut resolved_any = true;
+        while resolved_any {
+            resolved_any = false;
+            for module in self.modules_uncompiled.clone() {
+                self.switch_module(Rc::clone(&module));
+                resolved_any |= self.resolve_symbol_imports(&module);
+            }
+        }
+        self.run_mod(Self::finish_imports);
+    }
+
+    /// Tries to resolve all unresolved symbol imports of the module.
+    /// Returns if any of them were resolved.
+    fn resolve_symbol_imports(&mut self, module: &MutRc<Module>) -> bool {
+        let unresolved = mem::take(&mut module.borrow_mut().imports.unresolved);
+        let count = unresolved.len();
+        let remaining = unresolved
+            .into_iter()
+            .filter(|import| match &import.symbol {
+                Some(symbol) => {
+                    !self.import_symbol(module, &import.ast, &import.module.borrow(), symbol)
+                }
+                None => true,
+            })
+            .collect::<Vec<_>>();
+        let resolved_any = remaining.len() != count;
+        module.borrow_mut().imports.unresolved = remaining;
+        resolved_any
+    }
+
+    /// Reserves the names of wildcard imports and reports all
+    /// symbol imports that could not be resolved.
+    fn finish_imports(&mut self, module: MutRc<Module>) {
+        let remaining_imports = mem::take(&mut module.borrow_mut().imports.unresolved);
         for import in remaining_imports {
             let src_module = import.module.borrow();
 
-            if import.symbol == "+" {
-                for name in src_module.declarations.keys() {
-                    self.try_reserve_name(&import.ast.cst, name);
+            match &import.symbol {
+                None => {
+                    for name in src_module.declarations.keys() {
+                        self.try_reserve_name(&import.ast.cst, name);
+                    }
                 }
-            } else {
-                let decl = src_module.find_import(&import.symbol);
-                if let Some(decl) = decl {
-                    self.try_reserve_name(&import.ast.cst, &import.symbol);
-                    Self::get_imports(&mut module.borrow_mut(), import.ast.is_export())
-                        .decls
-                        .insert(import.symbol, decl);
-                } else {
-                    self.err(import.ast.cst(), GErr::E103);
+
+                Some(symbol) => {
+                    let names = src_module
+                        .declarations
+                        .keys()
+                        .chain(src_module.exports.decls.keys())
+                        .map(|name| name.as_str());
+                    let suggestion = Suggestion::similar(&symbol.name(), names);
+                    self.err(symbol.cst(), GErr::E103(suggestion));
                 }
             }
         }
@@ -100,6 +148,58 @@ impl GIRGenerator {
         }
     }
 
+    /// Try importing a single symbol, inserting it under its
+    /// alias if one was given. Returns success.
+    fn import_symbol(
+        &self,
+        module: &MutRc<Module>,
+        import: &ast::Import,
+        src_module: &Module,
+        symbol: &ast::ImportSymbol,
+    ) -> bool {
+        if let Some(decl) = src_module.find_import(&symbol.name()) {
+            let name = symbol.binding();
+            self.try_reserve_name(&symbol.cst, &name);
+            Self::get_imports(&mut module.borrow_mut(), import.is_export())
+                .decls
+                .insert(name, decl);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns the path of the module imported from.
+    /// Relative paths are resolved against the importing module's parent.
+    fn import_path(module: &Module, import: &ast::Import) -> Res<ModPath> {
+        let mut path = match import.relative_depth() {
+            Some(depth) => {
+                if module.path.parts().len() <= depth + 1 {
+                    return Err(gir_err(
+                        import.cst(),
+                        GErr::E102 {
+                            path: module.path.to_string(),
+                            outside_root: true,
+                        },
+                    ));
+                }
+
+                let mut path = ModPath::clone(&module.path);
+                for _ in 0..=depth {
+                    path.pop();
+                }
+                path
+            }
+
+            None => ModPath::new(),
+        };
+
+        for part in import.path() {
+            path.push(part);
+        }
+        Ok(path)
+    }
+
     fn find_module(&self, path: &ModPath, import: &ast::Import) -> Res<&MutRc<Module>> {
         self.modules
             .iter()
@@ -109,7 +209,13 @@ impl GIRGenerator {
                     .map(|m| *m.path == *path)
                     .unwrap_or(false)
             })
-            .or_err(&import.cst, GErr::E102)
+            .or_err(
+                &import.cst,
+                GErr::E102 {
+                    path: path.to_string(),
+                    outside_root: false,
+                },
+            )
     }
 
     fn get_imports(module: &mut Module, is_export: bool) -> &mut Imports {
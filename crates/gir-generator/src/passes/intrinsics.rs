@@ -21,6 +21,9 @@ impl GIRGenerator {
         } else if module.path.is(&["std", "iter"]) {
             self.intrinsics.iter_proto = module.find_decl("Iter").map(|d| d.into_adt());
             self.intrinsics.to_iter_proto = module.find_decl("ToIter").map(|d| d.into_adt());
+            self.intrinsics.range_proto = module.find_decl("Range").map(|d| d.into_adt());
+        } else if module.path.is(&["std", "collections", "array"]) {
+            self.intrinsics.array_proto = module.find_decl("Array").map(|d| d.into_adt());
         } else if module.path.is(&["std", "prelude"]) {
             self.intrinsics.std_prelude = Some(Rc::clone(&module_rc))
         }
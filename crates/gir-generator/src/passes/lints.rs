@@ -0,0 +1,88 @@
+//! Lints, reporting code that is valid but likely a mistake.
+//! Lints on function bodies are checked while generating them;
+//! this module contains the reporting and lints that need
+//! all GIR of a module to be generated.
+
+use crate::GIRGenerator;
+use ast::CSTNode;
+use error::{Error, GErr, LintLevel, Severity};
+use gir_nodes::{
+    declaration::{LocalVariable, Visibility},
+    gir_err, Declaration,
+};
+use std::{mem, rc::Rc};
+
+impl GIRGenerator {
+    /// Reports a lint at the given location. Depending on its level,
+    /// the lint is either ignored, a warning or an error.
+    /// Lints are never reported on the standard library.
+    pub(crate) fn lint(&self, cst: CSTNode, err: GErr) {
+        let lint = err.lint().unwrap();
+        let module = self.module.borrow();
+        let span = cst.text_range();
+        let severity = match self.flags.lints.get(lint) {
+            LintLevel::Allow => return,
+            LintLevel::Warn => Severity::Warning,
+            LintLevel::Deny => Severity::Error,
+        };
+
+        if module.path.parts()[0] != "std"
+            && !lint.allowed_at(&module.src, span.start as usize..span.end as usize)
+        {
+            let error = Error {
+                severity,
+                ..gir_err(cst, err)
+            };
+            self.error_(error, &module)
+        }
+    }
+
+    /// Marks a local variable as read. Reading a smart cast
+    /// reads the variable it is a cast of.
+    pub(crate) fn mark_used(&mut self, var: &Rc<LocalVariable>) {
+        let root = self.cast_root(var);
+        self.used_vars.insert(Rc::as_ptr(&root));
+    }
+
+    /// Reports all variables declared in the function just generated
+    /// that were never read. Variables starting with '_' are exempt.
+    pub(super) fn check_unused_vars(&mut self) {
+        for (var, cst) in mem::take(&mut self.declared_vars) {
+            if !self.used_vars.contains(&Rc::as_ptr(&var)) && !var.name.starts_with('_') {
+                self.lint(cst, GErr::W001(var.name.clone()));
+            }
+        }
+        self.used_vars.clear();
+    }
+
+    /// Reports imported symbols and private functions of
+    /// a module that were never used.
+    pub(super) fn check_unused_decls(&mut self, ast: &ast::Module) {
+        let module = self.module.borrow();
+        for symbol in ast
+            .imports()
+            .filter(|import| !import.is_export())
+            .flat_map(|import| import.symbols().collect::<Vec<_>>())
+        {
+            let name = symbol.binding();
+            if !module.imports.used.borrow().contains(&name) {
+                self.lint(symbol.cst(), GErr::W002(name));
+            }
+        }
+
+        for (name, decl) in &module.declarations {
+            let func = match decl {
+                Declaration::Function(func) => func,
+                _ => continue,
+            };
+            let func_ref = func.borrow();
+            if func_ref.visibility == Visibility::Private
+                && !self.used_functions.contains(&Rc::as_ptr(func))
+            {
+                if let Some(ast) = &func_ref.ast {
+                    self.lint(ast.sig().name().cst, GErr::W003(name.clone()));
+                }
+            }
+        }
+    }
+}
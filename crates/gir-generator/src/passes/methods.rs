@@ -7,7 +7,7 @@ use error::{GErr, Res};
 use gir_nodes::{
     declaration::{ADTType, CaseType, LocalVariable},
     gir_err,
-    types::TypeArguments,
+    types::{ToInstance, TypeArguments},
     Expr, Function, IFaceImpls, Instance, Type, ADT,
 };
 use smol_str::SmolStr;
@@ -19,6 +19,7 @@ use std::collections::HashMap;
 impl GIRGenerator {
     pub(super) fn declare_methods(&mut self, adt: &MutRc<ADT>) {
         self.declare_user_methods(&adt);
+        self.declare_inherent_methods(&adt);
 
         if let ADTType::Enum { cases } = &adt.borrow().ty {
             for case in cases.values() {
@@ -49,6 +50,11 @@ impl GIRGenerator {
         );
 
         for method in ast.methods() {
+            if method.is_static() {
+                self.declare_static_method(adt, method);
+                continue;
+            }
+
             let name = method.sig().name();
             let this_type = Type::Adt(this_inst.clone());
 
@@ -70,6 +76,66 @@ impl GIRGenerator {
         self.declare_constructors(adt, &ast, this_inst);
     }
 
+    fn declare_static_method(&mut self, adt: &MutRc<ADT>, method: ast::Function) {
+        let name = method.sig().name();
+        let gir_method = eat!(
+            self,
+            self.function_from_ast(
+                method,
+                None,
+                Some(Rc::clone(&adt.borrow().type_parameters))
+            )
+        );
+
+        let mut adt = adt.borrow_mut();
+        let is_case = matches!(&adt.ty, ADTType::Enum { cases } if cases.contains_key(&name.name()));
+        let existing = adt.static_methods.insert(name.name(), gir_method);
+        if existing.is_some() || is_case {
+            self.err(name.cst, GErr::E319)
+        }
+    }
+
+    /// Declares all methods of inherent impls in the ADT's own
+    /// module; they are regular methods of the ADT.
+    /// Other inherent impls are extensions, see `fill_impls`.
+    fn declare_inherent_methods(&mut self, adt: &MutRc<ADT>) {
+        let impls = match self.maybe_get_iface_impls(&adt.to_type()) {
+            Some(impls) => impls,
+            None => return,
+        };
+        let impls = impls
+            .borrow()
+            .inherent
+            .iter()
+            .filter(|im| !im.is_extension())
+            .map(|im| (im.ast.clone(), im.implementor.clone()))
+            .collect::<Vec<_>>();
+
+        for (ast, this_type) in impls {
+            for method in ast.methods() {
+                if method.is_static() {
+                    self.declare_static_method(adt, method);
+                    continue;
+                }
+
+                let name = method.sig().name();
+                let gir_method = eatc!(
+                    self,
+                    self.function_from_ast(
+                        method,
+                        Some(("this".into(), this_type.clone())),
+                        Some(Rc::clone(&adt.borrow().type_parameters))
+                    )
+                );
+
+                let existing = adt.borrow_mut().methods.insert(name.name(), gir_method);
+                if existing.is_some() {
+                    self.err(name.cst, GErr::E319)
+                }
+            }
+        }
+    }
+
     fn declare_constructors(&mut self, adt: &MutRc<ADT>, ast: &ast::Adt, this_inst: Instance<ADT>) {
         let mut constructor_parameter_list = HashSet::new();
 
@@ -206,6 +272,10 @@ impl GIRGenerator {
             for ast_method in ast.methods() {
                 let iface = iface.borrow();
                 let name = ast_method.sig().name();
+                if ast_method.is_static() {
+                    self.err(name.cst, GErr::E322);
+                    continue;
+                }
                 let iface_method = eatc!(
                     self,
                     iface
@@ -238,7 +308,7 @@ impl GIRGenerator {
             let iface_method_len = iface.borrow().methods.len() - (iface_was_compiled as usize * 3);
             if iface_method_len > iface_impl.methods.len() {
                 self.err(
-                    ast.iface().cst,
+                    ast.iface().unwrap().cst,
                     GErr::E314(
                         iface
                             .borrow()
@@ -253,6 +323,40 @@ impl GIRGenerator {
         }
 
         impls.methods = methods;
+
+        for inherent in impls
+            .inherent
+            .iter_mut()
+            .filter(|im| im.is_extension() && !im.module.borrow().compiled)
+        {
+            self.switch_module(Rc::clone(&inherent.module));
+            // Extensions of generic ADTs use the type parameters of the ADT
+            let type_params = inherent
+                .implementor
+                .try_adt()
+                .map(|adt| Rc::clone(&adt.ty.borrow().type_parameters));
+            for ast_method in inherent.ast.methods() {
+                let name = ast_method.sig().name();
+                if ast_method.is_static() {
+                    self.err(name.cst, GErr::E322);
+                    continue;
+                }
+
+                let this_type = inherent.implementor.clone();
+                let method = eatc!(
+                    self,
+                    self.function_from_ast(
+                        ast_method,
+                        Some(("this".into(), this_type)),
+                        type_params.clone()
+                    )
+                );
+                let existing = inherent.methods.insert(name.name(), method);
+                if existing.is_some() {
+                    self.err(name.cst, GErr::E319)
+                }
+            }
+        }
     }
 
     /// Ensures that the implemented interface method matches the expected signature.
@@ -318,9 +422,13 @@ impl GIRGenerator {
             ty: CaseType::Data, ..
         } = adt.ty
         {
-            // Generate the default constructor for an enum data case
+            // Generate the default constructor for an enum data case,
+            // unless declaring it failed due to an invalid field type
+            let cons = match adt.constructors.first() {
+                Some(cons) => cons,
+                None => return,
+            };
             let mut block = Vec::new();
-            let cons = &adt.constructors[0];
             let mut cons = cons.borrow_mut();
             let adt_param = &cons.parameters[0];
             for (param, field) in cons.parameters.iter().skip(1).zip(adt.fields.values()) {
@@ -7,10 +7,12 @@ pub(crate) use declare::FnSig;
 
 mod declare;
 mod fields;
+mod flow;
 mod generate;
 mod import;
 mod intrinsic_methods;
 mod intrinsics;
+mod lints;
 mod methods;
 
 impl GIRGenerator {
@@ -23,7 +25,7 @@ impl GIRGenerator {
             self.run_ast(Self::declare_functions);
             self.run_mod(Self::populate_intrinsics_fn);
             self.validate_intrinsics();
-            self.run_mod(Self::import_stage_2);
+            self.import_stage_2();
         });
 
         bench!("gir stage 2", {
@@ -39,6 +41,8 @@ impl GIRGenerator {
             self.run_dec(Self::generate);
             self.generate_impls();
         });
+
+        bench!("gir lints", self.run_ast(Self::check_unused_decls));
     }
 
     /// Execute a given module-scope pass.
@@ -0,0 +1,108 @@
+//! Recording of the definitions names in source code resolve to.
+//! This is only done when requested with [GIRFlags::references],
+//! for tools like the language server.
+
+use crate::GIRGenerator;
+use ast::CSTNode;
+use common::{ModulePath, MutRc};
+use gir_nodes::{
+    declaration::{Field, LocalVariable},
+    Function, Type, ADT,
+};
+use std::{ops::Range, rc::Rc};
+use syntax::kind::SyntaxKind;
+
+/// Something a name in source code can refer to.
+#[derive(Debug, Clone)]
+pub enum Definition {
+    /// A local variable or parameter.
+    Local(Rc<LocalVariable>),
+    /// A field of an ADT.
+    Field(Rc<Field>),
+    /// A function or method.
+    Function(MutRc<Function>),
+    /// A class, interface, enum or enum case.
+    Adt(MutRc<ADT>),
+}
+
+impl Definition {
+    /// Returns if both refer to the same definition.
+    pub fn same(&self, other: &Definition) -> bool {
+        match (self, other) {
+            (Self::Local(a), Self::Local(b)) => Rc::ptr_eq(a, b),
+            (Self::Field(a), Self::Field(b)) => Rc::ptr_eq(a, b),
+            (Self::Function(a), Self::Function(b)) => Rc::ptr_eq(a, b),
+            (Self::Adt(a), Self::Adt(b)) => Rc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+}
+
+/// A name in source code, together with the definition it resolved to.
+#[derive(Debug, Clone)]
+pub struct Reference {
+    /// The module the name is in.
+    pub module: ModulePath,
+    /// The location of the name.
+    pub span: Range<u32>,
+    pub definition: Definition,
+    /// If this name is the one given at the definition
+    /// itself, like the name in a variable declaration.
+    pub is_definition: bool,
+    /// If the name is written to, as the target of an assignment.
+    pub is_write: bool,
+}
+
+impl GIRGenerator {
+    /// Records that the name inside the given node refers to a definition.
+    pub(crate) fn reference(&self, node: &CSTNode, definition: Definition) {
+        self.record(node, definition, false)
+    }
+
+    /// Records that the given node declares a definition under its name.
+    pub(crate) fn definition(&self, node: &CSTNode, definition: Definition) {
+        self.record(node, definition, true)
+    }
+
+    /// Records that the name inside the given node refers to a type,
+    /// if the type is one declared in code.
+    pub(crate) fn reference_type(&self, node: &CSTNode, ty: &Type) {
+        if let Type::Adt(adt) = ty {
+            self.reference(node, Definition::Adt(Rc::clone(&adt.ty)))
+        }
+    }
+
+    /// Marks the name inside the given node as written to.
+    /// Its reference must have been recorded before.
+    pub(crate) fn written(&self, node: &CSTNode) {
+        if !self.flags.references {
+            return;
+        }
+        if let Some(span) = node.token_range(SyntaxKind::Identifier) {
+            let mut references = self.references.borrow_mut();
+            let reference = references
+                .iter_mut()
+                .rev()
+                .find(|r| r.span == span && Rc::ptr_eq(&r.module, &self.path));
+            if let Some(reference) = reference {
+                reference.is_write = true;
+            }
+        }
+    }
+
+    fn record(&self, node: &CSTNode, definition: Definition, is_definition: bool) {
+        if !self.flags.references {
+            return;
+        }
+        // Nodes without a name are compiler-generated or missing due to a syntax error
+        if let Some(span) = node.token_range(SyntaxKind::Identifier) {
+            self.references.borrow_mut().push(Reference {
+                module: Rc::clone(&self.path),
+                span,
+                definition,
+                is_definition,
+                is_write: false,
+            })
+        }
+    }
+}
@@ -28,8 +28,11 @@ impl GIRGenerator {
             ast::TypeE::Ident(tok) => {
                 let ty = self.search_type_param(&tok);
                 let ty = ty.or_else(|| self.symbol(&tok));
-                let ty = ty.or_err(&ast.cst, GErr::E300(tok.to_string()))?;
+                let ty = ty.or_error(&ast.cst, || {
+                    GErr::E300(tok.to_string(), self.suggest_type(&tok))
+                })?;
                 Self::check_args_count(&ty, &ast.cst)?;
+                self.reference_type(&ast.cst, &ty);
 
                 if ty.is_function() && !allow_fn {
                     Err(gir_err(ast.cst(), GErr::E301))
@@ -66,7 +69,9 @@ impl GIRGenerator {
             }
 
             ast::TypeE::Generic { ident, types } => {
-                self.symbol_with_type_args(&ident, types.into_iter(), &ast.cst)
+                let ty = self.symbol_with_type_args(&ident, types.into_iter(), &ast.cst)?;
+                self.reference_type(&ast.cst, &ty);
+                Ok(ty)
             }
         }
     }
@@ -107,9 +112,9 @@ impl GIRGenerator {
         args: T,
         cst: &CSTNode,
     ) -> Res<Type> {
-        let mut ty = self
-            .symbol(ident)
-            .or_err(cst, GErr::E300(ident.to_string()))?;
+        let mut ty = self.symbol(ident).or_error(cst, || {
+            GErr::E300(ident.to_string(), self.suggest_type(ident))
+        })?;
         let args = args.map(|p| self.find_type(&p)).collect::<Res<Vec<_>>>()?;
         if !args.is_empty() {
             let args = Rc::new(args);
@@ -259,6 +264,7 @@ impl GIRGenerator {
                     implementor: ty.clone(),
                     interfaces: HashMap::with_capacity(2),
                     methods: HashMap::with_capacity(2),
+                    inherent: vec![],
                 });
                 self.iface_impls.insert(ty.clone(), Rc::clone(&iface_impls));
                 iface_impls
@@ -0,0 +1,246 @@
+//! Smart casts, where local variables are narrowed to a more
+//! specific type based on conditions and control flow.
+//!
+//! A smart cast is a new variable shadowing the cast variable
+//! with the same name, holding its value cast to the narrower type.
+//! Assigning to it is redirected to the original variable,
+//! see [GIRGenerator::assign_local].
+
+use crate::GIRGenerator;
+use ast::CSTNode;
+use error::{GErr, Res};
+use gir_nodes::{
+    declaration::{LocalVariable, Variable},
+    expression::CastType,
+    gir_err, Expr, Literal, Type,
+};
+use std::{ops::Range, rc::Rc};
+use syntax::kind::SyntaxKind;
+
+/// A variable narrowed by a condition: The variable,
+/// the type it is narrowed to and the cast needed for it.
+pub(crate) type Narrowing = (Rc<LocalVariable>, Type, CastType);
+
+/// Info about a variable that is a smart cast of another.
+pub(crate) struct SmartCast {
+    /// The variable that was cast.
+    origin: Rc<LocalVariable>,
+    /// The loop depth the cast was made at.
+    loop_depth: usize,
+}
+
+impl GIRGenerator {
+    /// Tries finding smart casts, where a type can be downcasted
+    /// based on a user-code condition being `holds`.
+    /// Variables assigned to since the given point in the assignment log
+    /// (usually the start of the condition) are not cast.
+    /// Will insert variables for downcasts into current scope/function.
+    pub(crate) fn smart_casts(&mut self, condition: &Expr, holds: bool, since: usize) -> Vec<Expr> {
+        let narrowings = self.narrowings(condition, holds, since);
+        self.apply_narrowings(narrowings)
+    }
+
+    /// Returns all narrowings implied by the condition being `holds`,
+    /// excluding variables assigned to since the given point in the assignment log.
+    pub(crate) fn narrowings(&self, condition: &Expr, holds: bool, since: usize) -> Vec<Narrowing> {
+        let mut list = Vec::new();
+        Self::find_narrowings(&mut list, condition, holds);
+        self.unassigned(list, since..self.assigned_vars.len())
+    }
+
+    fn find_narrowings(list: &mut Vec<Narrowing>, expr: &Expr, holds: bool) {
+        match expr {
+            Expr::Binary {
+                left,
+                operator,
+                right,
+            } => match (operator, &**left, &**right) {
+                // Enum parent to case (parent is Enum:Case)
+                (SyntaxKind::Is, Expr::Variable(Variable::Local(var)), _) if holds => {
+                    let ty = *right.get_type().into_type();
+                    list.push((Rc::clone(var), ty, CastType::Bitcast));
+                }
+
+                // Nullable to non-null (a != null, or a == null being false)
+                (
+                    op,
+                    Expr::Variable(Variable::Local(var)),
+                    Expr::Cast {
+                        inner: box Expr::Literal(Literal::Null),
+                        ..
+                    },
+                ) if (*op == SyntaxKind::BangEqual && holds)
+                    || (*op == SyntaxKind::EqualEqual && !holds) =>
+                {
+                    let ty = *left.get_type().into_nullable();
+                    list.push((Rc::clone(var), ty, CastType::FromNullable));
+                }
+
+                _ => (),
+            },
+
+            Expr::Unary {
+                operator: SyntaxKind::Bang,
+                right,
+            } => Self::find_narrowings(list, right, !holds),
+
+            // 'a and b' is generated as 'if (a) b else false',
+            // 'a or b' as 'if (a) true else b'
+            Expr::If {
+                condition,
+                then_branch,
+                else_branch,
+                ..
+            } => match (&**then_branch, &**else_branch) {
+                (right, Expr::Literal(Literal::Bool(false))) if holds => {
+                    Self::find_narrowings(list, condition, true);
+                    Self::find_narrowings(list, right, true);
+                }
+                (Expr::Literal(Literal::Bool(true)), right) if !holds => {
+                    Self::find_narrowings(list, condition, false);
+                    Self::find_narrowings(list, right, false);
+                }
+                _ => (),
+            },
+
+            Expr::Block(exprs) => Self::find_narrowings(list, exprs.last().unwrap(), holds),
+
+            Expr::Located { inner, .. } => Self::find_narrowings(list, inner, holds),
+
+            _ => (),
+        }
+    }
+
+    /// Removes all narrowings of variables assigned to
+    /// in the given range of the assignment log.
+    pub(crate) fn unassigned(
+        &self,
+        mut narrowings: Vec<Narrowing>,
+        assigned: Range<usize>,
+    ) -> Vec<Narrowing> {
+        let assigned = &self.assigned_vars[assigned];
+        narrowings.retain(|(var, _, _)| {
+            let root = self.cast_root(var);
+            !assigned.iter().any(|a| Rc::ptr_eq(a, &root))
+        });
+        narrowings
+    }
+
+    /// Defines the smart cast variables of the given narrowings in the
+    /// current scope, returning the expressions initializing them.
+    pub(crate) fn apply_narrowings(&mut self, narrowings: Vec<Narrowing>) -> Vec<Expr> {
+        narrowings
+            .into_iter()
+            .map(|(var, ty, cast)| {
+                let mut clone = (*var).clone();
+                clone.mutable = false;
+                clone.ty = ty.clone();
+                let new_var = self.define_variable_(clone, None);
+                self.smart_cast_vars.insert(
+                    Rc::as_ptr(&new_var),
+                    SmartCast {
+                        origin: Rc::clone(&var),
+                        loop_depth: self.loop_depth,
+                    },
+                );
+
+                Expr::store(
+                    Expr::lvar(&new_var),
+                    Expr::cast(Expr::lvar(&var), ty, cast),
+                    true,
+                )
+            })
+            .collect()
+    }
+
+    /// Returns the variable that the given variable is a smart cast of,
+    /// following multiple casts. Returns the variable itself if it is not a cast.
+    pub(crate) fn cast_root(&self, var: &Rc<LocalVariable>) -> Rc<LocalVariable> {
+        match self.smart_cast_vars.get(&Rc::as_ptr(var)) {
+            Some(cast) => self.cast_root(&cast.origin),
+            None => Rc::clone(var),
+        }
+    }
+
+    /// Returns if the given expression never finishes evaluating,
+    /// as it always returns or breaks.
+    pub(crate) fn diverges(expr: &Expr) -> bool {
+        match expr {
+            Expr::Return(_) | Expr::Break(_) => true,
+            Expr::Block(exprs) => exprs.iter().any(Self::diverges),
+            Expr::If {
+                then_branch,
+                else_branch,
+                ..
+            } => Self::diverges(then_branch) && Self::diverges(else_branch),
+            Expr::Switch {
+                branches,
+                else_branch,
+                ..
+            } => branches.iter().all(|(_, b)| Self::diverges(b)) && Self::diverges(else_branch),
+            Expr::Located { inner, .. } => Self::diverges(inner),
+            _ => false,
+        }
+    }
+
+    /// Assigns a value to a local variable, recording the assignment.
+    /// If the variable is a smart cast, the variable it is a cast of is
+    /// assigned as well. The smart cast stays valid if the value has the
+    /// type of the cast, and is removed from scope otherwise.
+    pub(crate) fn assign_local(
+        &mut self,
+        var: &Rc<LocalVariable>,
+        value: Expr,
+        cst: &CSTNode,
+        value_cst: &CSTNode,
+    ) -> Res<Expr> {
+        let (origin, loop_depth) = match self.smart_cast_vars.get(&Rc::as_ptr(var)) {
+            Some(cast) => (Rc::clone(&cast.origin), cast.loop_depth),
+            None => {
+                self.assigned_vars.push(Rc::clone(var));
+                let (value, matching_types) = self.try_cast(value, &var.ty);
+                return if !var.mutable {
+                    Err(gir_err(
+                        cst.clone(),
+                        GErr::E200(Expr::lvar(var).human_name()),
+                    ))
+                } else if !matching_types {
+                    Err(gir_err(value_cst.clone(), GErr::E201))
+                } else {
+                    Ok(Expr::store(Expr::lvar(var), value, false))
+                };
+            }
+        };
+
+        let (value, fits_cast) = self.try_cast(value, &var.ty);
+        if fits_cast {
+            let origin_store = self.assign_local(&origin, Expr::lvar(var), cst, value_cst)?;
+            Ok(Expr::Block(vec![
+                Expr::store(Expr::lvar(var), value, false),
+                origin_store,
+            ]))
+        } else if loop_depth != self.loop_depth {
+            // Code using the cast earlier in the loop would
+            // see an outdated value on the next iteration
+            Err(gir_err(cst.clone(), GErr::E249(var.name.clone())))
+        } else {
+            self.remove_smart_cast(var, &origin);
+            self.assign_local(&origin, value, cst, value_cst)
+        }
+    }
+
+    /// Removes a smart cast variable from scope, making its
+    /// name refer to the variable it is a cast of again.
+    /// The cast can share a scope with its origin (after an early return),
+    /// so the origin takes its place instead of the name being removed.
+    fn remove_smart_cast(&mut self, var: &Rc<LocalVariable>, origin: &Rc<LocalVariable>) {
+        let env = self
+            .environments
+            .iter_mut()
+            .rev()
+            .find(|env| env.get(&var.name).map_or(false, |v| Rc::ptr_eq(v, var)));
+        if let Some(env) = env {
+            env.insert(var.name.clone(), Rc::clone(origin));
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use crate::GIRGenerator;
+use error::Suggestion;
+use gir_nodes::{
+    types::{TypeParameterBound, TypeVariable},
+    Type,
+};
+use smol_str::SmolStr;
+use std::rc::Rc;
+
+/// Names of all builtin types, see [GIRGenerator::symbol].
+const BUILTIN_TYPES: [&str; 14] = [
+    "None", "bool", "i8", "i16", "i32", "i64", "isize", "u8", "u16", "u32", "u64", "usize", "f32",
+    "f64",
+];
+
+/// Suggestions for errors on unknown names.
+/// Each of them looks for a similar name in the scope the name was
+/// searched in; variables, types and declarations additionally
+/// look for a std module that contains the name.
+impl GIRGenerator {
+    /// Suggestions for an unknown variable.
+    pub(crate) fn suggest_var(&self, name: &str) -> Suggestion {
+        let mut names = self.decl_names();
+        let closure_envs = self.closure_data.iter().flat_map(|c| c.outer_env.iter());
+        for env in self.environments.iter().chain(closure_envs) {
+            names.extend(env.keys().cloned());
+        }
+        self.with_std_import(
+            Suggestion::similar(name, names.iter().map(SmolStr::as_str)),
+            name,
+        )
+    }
+
+    /// Suggestions for an unknown type.
+    pub(crate) fn suggest_type(&self, name: &str) -> Suggestion {
+        let mut names = self.decl_names();
+        names.extend(BUILTIN_TYPES.iter().map(SmolStr::new));
+        if let Some(params) = &self.type_params {
+            names.extend(params.iter().map(|p| p.name.clone()));
+        }
+        self.with_std_import(
+            Suggestion::similar(name, names.iter().map(SmolStr::as_str)),
+            name,
+        )
+    }
+
+    /// Suggestions for an unknown field or method on the given type.
+    pub(crate) fn suggest_member(&self, ty: &Type, name: &str) -> Suggestion {
+        let mut names = Vec::new();
+        match ty {
+            Type::Adt(adt) => {
+                let adt = adt.ty.borrow();
+                names.extend(adt.fields.keys().cloned());
+                names.extend(adt.methods.keys().cloned());
+            }
+
+            Type::Variable(TypeVariable {
+                bound: TypeParameterBound::Interface(interface),
+                ..
+            }) => names.extend(interface.as_adt().ty.borrow().methods.keys().cloned()),
+
+            _ => (),
+        }
+
+        if let Some(impls) = self.maybe_get_iface_impls(ty) {
+            names.extend(impls.borrow().methods.keys().cloned());
+        }
+        Suggestion::similar(name, names.iter().map(SmolStr::as_str))
+    }
+
+    /// Returns the names of all declarations available in the current module,
+    /// either declared in it or imported.
+    fn decl_names(&self) -> Vec<SmolStr> {
+        let module = self.module.borrow();
+        let mut names = module.declarations.keys().cloned().collect::<Vec<_>>();
+        names.extend(module.imports.decls.keys().cloned());
+        for imported in &module.imports.modules {
+            let imported = imported.borrow();
+            names.extend(imported.declarations.keys().cloned());
+            names.extend(imported.exports.decls.keys().cloned());
+        }
+        names
+    }
+
+    /// Adds the import declaration of the given name to the suggestion,
+    /// if the name is declared in a std module that is not imported.
+    fn with_std_import(&self, mut suggestion: Suggestion, name: &str) -> Suggestion {
+        let path = Rc::clone(&self.module.borrow().path);
+        suggestion.import = self
+            .modules
+            .iter()
+            .filter_map(|module| module.try_borrow().ok())
+            .find(|module| {
+                module.path.parts()[0] == "std"
+                    && matches!(module.find_import(name), Some(d) if d.visible(&path))
+            })
+            .map(|module| format!("import {}/{}", module.path, name));
+        suggestion
+    }
+}
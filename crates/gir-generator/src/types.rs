@@ -78,20 +78,25 @@ impl GIRGenerator {
         params: &[TypeParameter],
         cst: &CSTNode,
     ) {
-        for (index, (arg, param)) in args
-            .iter()
-            .zip(params.iter())
-            .filter(|(a, p)| !self.matches_bound(a, &p.bound))
-            .enumerate()
-        {
-            self.err(
-                cst.clone(),
-                GErr::E239 {
-                    index,
-                    argument: arg.to_string(),
-                    bound: param.bound.to_string(),
-                },
-            )
+        // Interface bounds can use the other parameters ('I: Iter[E]')
+        let all_args = Rc::new(args.clone());
+        for (index, (arg, param)) in args.iter().zip(params.iter()).enumerate() {
+            let bound = match &param.bound {
+                TypeParameterBound::Interface(iface) => {
+                    TypeParameterBound::Interface(Box::new(iface.resolve(&all_args)))
+                }
+                bound => bound.clone(),
+            };
+            if !self.matches_bound(arg, &bound) {
+                self.err(
+                    cst.clone(),
+                    GErr::E239 {
+                        index,
+                        argument: arg.to_string(),
+                        bound: bound.to_string(),
+                    },
+                )
+            }
         }
     }
 
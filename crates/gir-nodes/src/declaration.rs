@@ -87,6 +87,9 @@ pub struct ADT {
     /// - "free-wr(&ADT)": Frees a WR by decrementing the refcount of all fields
     /// - "free-sr(&ADT, act)": Frees a SR by decrementing the refcount of all fields and calling free if act == true
     pub methods: IndexMap<SmolStr, MutRc<Function>>,
+    /// All static methods of this ADT. They do not take a receiver
+    /// and are called using static access (`Type:method()`).
+    pub static_methods: IndexMap<SmolStr, MutRc<Function>>,
     /// All constructors of the ADT, if any. They are simply methods
     /// with special constraints to enforce safety.
     pub constructors: Vec<MutRc<Function>>,
@@ -9,6 +9,7 @@ use crate::{
     types::ToInstance,
     Function, Literal, Type,
 };
+use ast::CSTNode;
 use common::MutRc;
 use std::rc::Rc;
 use syntax::kind::SyntaxKind;
@@ -144,6 +145,15 @@ pub enum Expr {
     /// Therefore, some methods like get_token or get_type do not
     /// need to be implemented for this.
     Intrinsic(Intrinsic),
+
+    /// An expression along with the source it was generated from.
+    /// Produced for statements and branches, allowing passes
+    /// over GIR to report errors at them.
+    /// Behaves exactly like the inner expression otherwise.
+    Located {
+        inner: Box<Expr>,
+        cst: CSTNode,
+    },
 }
 
 impl Expr {
@@ -245,6 +255,13 @@ impl Expr {
         Expr::Break(Box::new(val))
     }
 
+    pub fn located(inner: Expr, cst: CSTNode) -> Expr {
+        Expr::Located {
+            inner: Box::new(inner),
+            cst,
+        }
+    }
+
     pub fn cast(val: Expr, to: Type, method: CastType) -> Expr {
         Expr::Cast {
             inner: Box::new(val),
@@ -329,6 +346,8 @@ impl Expr {
                 Intrinsic::ConcreteMethodGet(get) => get.iface_method.to_type(),
                 _ => Type::Any,
             },
+
+            Expr::Located { inner, .. } => inner.get_type(),
         }
     }
 
@@ -356,6 +375,8 @@ impl Expr {
 
             Expr::Load { field, .. } => field.mutable,
 
+            Expr::Located { inner, .. } => inner.assignable(),
+
             Expr::If {
                 then_branch,
                 else_branch,
@@ -410,6 +431,7 @@ impl Expr {
             Expr::Closure { .. } => "closure literal",
             Expr::TypeGet(_) => "type access",
             Expr::Intrinsic(_) => "<intrinsic>",
+            Expr::Located { inner, .. } => inner.human_name(),
         }
     }
 }
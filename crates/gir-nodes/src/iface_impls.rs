@@ -1,7 +1,7 @@
 use crate::{Function, Instance, Module, Type, ADT};
 use common::MutRc;
 use smol_str::SmolStr;
-use std::collections::HashMap;
+use std::{collections::HashMap, rc::Rc};
 
 /// An implementation of an interface.
 #[derive(Debug)]
@@ -14,6 +14,31 @@ pub struct IFaceImpl {
     pub ast: ast::IfaceImpl,
 }
 
+/// An inherent impl block ('impl Type { ... }'), adding methods
+/// to a type without implementing an interface.
+/// When in a different module than the type, its methods are
+/// extension methods that are only visible where the module is imported.
+#[derive(Debug)]
+pub struct InherentImpl {
+    pub implementor: Type,
+    pub methods: HashMap<SmolStr, MutRc<Function>>,
+    /// Module that the impl block is in.
+    pub module: MutRc<Module>,
+    pub ast: ast::IfaceImpl,
+}
+
+impl InherentImpl {
+    /// Returns if this impl contains extension methods.
+    /// Inherent impls on ADTs in the ADT's own module are instead
+    /// regular methods of the ADT, except on interfaces.
+    pub fn is_extension(&self) -> bool {
+        self.implementor.try_adt().map_or(true, |adt| {
+            let adt = adt.ty.borrow();
+            adt.ty.is_interface() || !Rc::ptr_eq(&adt.module, &self.module)
+        })
+    }
+}
+
 /// A struct representing all interfaces implemented by a type.
 /// A simple map of interfaces is not enough, as it does not
 /// prevent naming collisions.
@@ -26,4 +51,6 @@ pub struct IFaceImpls {
     /// Interface is always a strong reference.
     pub interfaces: HashMap<Type, IFaceImpl>,
     pub methods: HashMap<SmolStr, MutRc<Function>>,
+    /// All inherent impls on the type.
+    pub inherent: Vec<InherentImpl>,
 }
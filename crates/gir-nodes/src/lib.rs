@@ -11,9 +11,9 @@ pub mod types;
 
 use ast::CSTNode;
 pub use declaration::{Declaration, Function, ADT};
-use error::{Error, ErrorSpan, GErr};
+use error::{Error, ErrorSpan, GErr, Severity};
 pub use expression::Expr;
-pub use iface_impls::{IFaceImpl, IFaceImpls};
+pub use iface_impls::{IFaceImpl, IFaceImpls, InherentImpl};
 pub use literal::Literal;
 pub use module::Module;
 pub use types::{Instance, Type};
@@ -23,5 +23,7 @@ pub fn gir_err(cst: CSTNode, err: GErr) -> Error {
     Error {
         index: ErrorSpan::Span(cst.text_range()),
         kind: err,
+        severity: Severity::Error,
+        details: Box::default(),
     }
 }
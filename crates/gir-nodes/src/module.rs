@@ -2,9 +2,10 @@ use crate::{gir_err, Declaration, Function};
 use ast::CSTNode;
 use common::{mutrc_new, ModulePath, MutRc};
 use drop_bomb::DebugDropBomb;
-use error::{GErr, Res};
+use error::{ErrorSpan, GErr, Res};
 use smol_str::SmolStr;
 use std::{
+    cell::RefCell,
     collections::{HashMap, HashSet},
     rc::Rc,
 };
@@ -24,9 +25,10 @@ pub struct Module {
     /// All exports from other modules.
     pub exports: Imports,
 
-    /// A list of all global names (classes/interfaces/functions) in this module.
+    /// A list of all global names (classes/interfaces/functions) in this module,
+    /// together with where they were defined or imported.
     /// Used to ensure that no naming collision occurs.
-    pub used_names: HashSet<SmolStr>,
+    pub used_names: HashMap<SmolStr, CSTNode>,
 
     pub path: ModulePath,
     pub src: Rc<String>,
@@ -65,9 +67,22 @@ impl Module {
 
     /// Tries to reserve the given name.
     pub fn try_reserve_name(&mut self, node: &CSTNode, name: &SmolStr) -> Res<()> {
-        if !self.used_names.insert(name.clone()) {
-            Err(gir_err(node.clone(), GErr::E100(name.clone())))
+        if let Some(prev) = self.used_names.get(name) {
+            let mut err = gir_err(node.clone(), GErr::E100(name.clone()));
+            // Names from the prelude are reserved without a location
+            let is_prelude = |node: &CSTNode| node.text_range().start == node.text_range().end;
+            if !is_prelude(prev) {
+                err = err.with_label(
+                    ErrorSpan::Span(prev.text_range()),
+                    "previous definition here",
+                );
+            }
+            if is_prelude(prev) || is_prelude(node) {
+                err = err.with_note(format!("'{}' is imported from the prelude", name));
+            }
+            Err(err)
         } else {
+            self.used_names.insert(name.clone(), node.clone());
             Ok(())
         }
     }
@@ -79,7 +94,7 @@ impl Module {
             functions: Vec::with_capacity(10),
             imports: Imports::default(),
             exports: Imports::default(),
-            used_names: HashSet::with_capacity(10),
+            used_names: HashMap::with_capacity(10),
             path: Rc::clone(&ast.path),
             src: Rc::clone(&ast.src),
             compiled: false,
@@ -94,15 +109,28 @@ pub struct Imports {
     pub decls: HashMap<SmolStr, Declaration>,
     pub modules: Vec<MutRc<Module>>,
     pub unresolved: Vec<UnresolvedImport>,
+    /// All modules anything was imported from, used
+    /// for visibility of extension methods.
+    /// On exports, these are the modules whose extensions
+    /// are visible to importers of the module.
+    pub sources: Vec<MutRc<Module>>,
+    /// All names in `decls` that were looked up, used
+    /// for the unused import lint.
+    pub used: RefCell<HashSet<SmolStr>>,
 }
 
 impl Imports {
     fn get(&self, name: &str) -> Option<Declaration> {
-        self.decls.get(name).cloned().or_else(|| {
-            self.modules
+        match self.decls.get(name) {
+            Some(decl) => {
+                self.used.borrow_mut().insert(name.into());
+                Some(decl.clone())
+            }
+            None => self
+                .modules
                 .iter()
-                .find_map(|m| m.borrow().find_import(name))
-        })
+                .find_map(|m| m.borrow().find_import(name)),
+        }
     }
 }
 
@@ -112,7 +140,8 @@ impl Imports {
 pub struct UnresolvedImport {
     pub ast: ast::Import,
     pub module: MutRc<Module>,
-    pub symbol: SmolStr,
+    /// The symbol to import, `None` on wildcard imports.
+    pub symbol: Option<ast::ImportSymbol>,
 }
 
 pub struct BorrowedAST(pub ast::Module, DebugDropBomb);
@@ -2,8 +2,8 @@ use crate::{
     declaration::{ADTType, Declaration, Function, Variable, Visibility, ADT},
     expression::{CastType, ConcreteMethodGet, Expr, Intrinsic},
     module::Module,
-    types::print_type_args,
-    Literal,
+    types::{print_type_args, Bound, TypeParameterBound, TypeParameters},
+    Literal, Type,
 };
 use std::{
     fmt,
@@ -18,7 +18,7 @@ impl Display for Module {
     fn fmt(&self, f: &mut Formatter) -> R {
         writeln!(f, "--> {}:", self.path)?;
         writeln!(f, "Used names: ")?;
-        for name in &self.used_names {
+        for name in self.used_names.keys() {
             writeln!(f, "{} ", name)?;
         }
         writeln!(f, "\n\n")?;
@@ -39,6 +39,38 @@ impl Declaration {
     }
 }
 
+/// Prints only the signature of the declaration, without its body.
+impl Display for Declaration {
+    fn fmt(&self, f: &mut Formatter) -> R {
+        match self {
+            Declaration::Function(func) => func.borrow().signature(f),
+            Declaration::Adt(adt) => adt.borrow().signature(f),
+        }
+    }
+}
+
+/// Type parameters are printed with their bound in the signature,
+/// so leave it out on uses of them.
+fn signature_type(f: &mut Formatter, ty: &Type) -> R {
+    match ty {
+        Type::Variable(var) => write!(f, "{}", var.name),
+        _ => write!(f, "{}", ty),
+    }
+}
+
+fn print_type_params(f: &mut Formatter, params: &TypeParameters) -> R {
+    for (i, param) in params.iter().enumerate() {
+        write!(f, "{}{}", if i == 0 { "[" } else { ", " }, param.name)?;
+        if param.bound != TypeParameterBound::Bound(Bound::Unbounded) {
+            write!(f, ": {}", param.bound)?;
+        }
+    }
+    if !params.is_empty() {
+        write!(f, "]")?;
+    }
+    Ok(())
+}
+
 impl Function {
     fn display(&self, f: &mut Formatter, indent_size: usize) -> R {
         let indent = repeat(' ').take(indent_size).collect::<String>();
@@ -92,6 +124,27 @@ impl Function {
     }
 }
 
+impl Function {
+    fn signature(&self, f: &mut Formatter) -> R {
+        write!(f, "func {}", self.name)?;
+        print_type_params(f, &self.type_parameters)?;
+        write!(f, "(")?;
+        for (i, param) in self.parameters.iter().enumerate() {
+            if i != 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}: ", param.name)?;
+            signature_type(f, &param.ty)?;
+        }
+        write!(f, ")")?;
+        if !self.ret_type.is_none() {
+            write!(f, " -> ")?;
+            signature_type(f, &self.ret_type)?;
+        }
+        Ok(())
+    }
+}
+
 impl Debug for Function {
     fn fmt(&self, f: &mut Formatter<'_>) -> R {
         self.display(f, 0)
@@ -133,6 +186,19 @@ impl ADT {
     }
 }
 
+impl ADT {
+    fn signature(&self, f: &mut Formatter) -> R {
+        match &self.ty {
+            ADTType::Class { .. } => write!(f, "class {}", self.name),
+            ADTType::Interface => write!(f, "interface {}", self.name),
+            ADTType::Enum { .. } => write!(f, "enum {}", self.name),
+            // Names of cases already include the enum ('Enum:Case')
+            ADTType::EnumCase { .. } => write!(f, "case {}", self.name),
+        }?;
+        print_type_params(f, &self.type_parameters)
+    }
+}
+
 impl Debug for ADT {
     fn fmt(&self, f: &mut Formatter<'_>) -> R {
         self.display(f, 0)
@@ -279,6 +345,8 @@ impl Expr {
             Expr::TypeGet(ty) => write!(f, "get_type({})", ty),
 
             Expr::Intrinsic(int) => write!(f, "intrinsic({})", int),
+
+            Expr::Located { inner, .. } => inner.display(f, indent_size),
         }
     }
 }
@@ -163,6 +163,8 @@ impl IRGenerator {
 
             Expr::Intrinsic(int) => self.intrinsic(int),
 
+            Expr::Located { inner, .. } => self.expression_(inner, no_load),
+
             Expr::TypeGet(_) => panic!("Invalid IR instruction"),
 
             Expr::Closure { .. } => todo!(),
@@ -22,6 +22,10 @@ pub enum Token {
     Comma,
     #[token(".")]
     Dot,
+    #[token("..")]
+    DotDot,
+    #[token("..=")]
+    DotDotEqual,
     #[token("-")]
     Minus,
     #[token("+")]
@@ -73,6 +77,8 @@ pub enum Token {
 
     #[token("and")]
     And,
+    #[token("as")]
+    As,
     #[token("break")]
     Break,
     #[token("class")]
@@ -128,6 +134,8 @@ pub enum Token {
     Value,
     #[token("variadic")]
     Variadic,
+    #[token("static")]
+    Static,
 
     #[regex(r"/\*([^*]|\*+[^*/])*\*?")] // https://github.com/maciejhirsz/logos/issues/180
     #[error]
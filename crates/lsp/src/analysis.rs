@@ -0,0 +1,566 @@
+//! Compilation of a program to GIR, and the queries of the editor
+//! that are answered using the result.
+
+use crate::{project::SourceFile, text};
+use common::{ModPath, MutRc};
+use error::Errors;
+use gir_generator::{CompiledGIR, Definition, GIRFlags, GIRGenerator, Reference};
+use gir_nodes::{
+    declaration::{ADTType, Visibility},
+    types::{TypeParameterBound, TypeVariable},
+    Declaration, Function, Module, Type, ADT,
+};
+use serde_json::{json, Value};
+use smol_str::SmolStr;
+use std::{
+    collections::HashMap,
+    ops::Range,
+    panic::{self, AssertUnwindSafe},
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+/// A compiled program.
+pub struct Analysis {
+    /// The GIR of the program; `None` if the compiler panicked.
+    pub gir: Option<CompiledGIR>,
+    /// All names in the program and the standard library,
+    /// along with what they refer to.
+    references: Vec<Reference>,
+    /// The file of every module, by module path.
+    files: HashMap<String, PathBuf>,
+    /// The diagnostics of every file of the program, in protocol format.
+    /// Files without any are included to clear diagnostics of the editor.
+    pub diagnostics: Vec<(PathBuf, Vec<Value>)>,
+}
+
+impl Analysis {
+    /// Compiles the given modules, recording all references.
+    /// `std` is the already compiled standard library to use.
+    pub fn compile(files: Vec<SourceFile>, std: Option<&Analysis>, flags: GIRFlags) -> Analysis {
+        let mut analysis = Analysis {
+            gir: None,
+            references: std.map_or_else(Vec::new, |std| std.references.clone()),
+            files: std.map_or_else(HashMap::new, |std| std.files.clone()),
+            diagnostics: Vec::with_capacity(files.len()),
+        };
+
+        let mut modules = Vec::with_capacity(files.len());
+        let mut syntax_errors = false;
+        for file in files {
+            syntax_errors |= !file.errors.errors.is_empty();
+            analysis
+                .files
+                .insert(file.module.path.to_string(), file.path.clone());
+            let diagnostics = analysis.to_diagnostics(&file.errors);
+            analysis.diagnostics.push((file.path, diagnostics));
+            modules.push(file.module);
+        }
+        if modules.is_empty() {
+            return analysis;
+        }
+
+        let flags = GIRFlags {
+            references: true,
+            ..flags
+        };
+        let std = std.and_then(|std| std.gir.as_ref());
+        // Code that is being written can contain anything, and a
+        // crashing language server is worse than one missing information.
+        // The compiler is not meant to handle code with syntax errors,
+        // crashing on it is expected and not worth reporting
+        let prev_hook = if syntax_errors {
+            let hook = panic::take_hook();
+            panic::set_hook(Box::new(|_| {}));
+            Some(hook)
+        } else {
+            None
+        };
+        let gir = panic::catch_unwind(AssertUnwindSafe(|| match std {
+            Some(std) => GIRGenerator::with_cached_std(modules, std, flags).analyze(),
+            None => GIRGenerator::new(modules, flags).analyze(),
+        }))
+        .ok();
+        if let Some(hook) = prev_hook {
+            panic::set_hook(hook);
+        }
+
+        if let Some(gir) = &gir {
+            analysis.references.extend(gir.references.iter().cloned());
+            // Errors caused by syntax errors are not helpful, only show these first
+            if !syntax_errors {
+                for errors in &gir.warnings {
+                    let diagnostics = analysis.to_diagnostics(errors);
+                    let file = analysis.files.get(&errors.origin);
+                    if let Some((_, list)) = analysis
+                        .diagnostics
+                        .iter_mut()
+                        .find(|(path, _)| Some(path) == file)
+                    {
+                        list.extend(diagnostics)
+                    }
+                }
+            }
+        }
+        analysis.gir = gir;
+        analysis
+    }
+
+    /// Returns if the given file is part of the program.
+    pub fn contains(&self, file: &Path) -> bool {
+        self.diagnostics.iter().any(|(path, _)| path == file)
+    }
+
+    /// Returns the compiled module of the given file.
+    pub fn module(&self, file: &Path) -> Option<MutRc<Module>> {
+        let (name, _) = self.files.iter().find(|(_, path)| *path == file)?;
+        self.gir
+            .as_ref()?
+            .modules
+            .iter()
+            .find(|module| module.borrow().path.to_string() == *name)
+            .cloned()
+    }
+
+    /// Returns the source of the given file, as it was compiled.
+    pub fn source(&self, file: &Path) -> Option<Rc<String>> {
+        self.module(file)
+            .map(|module| Rc::clone(&module.borrow().src))
+    }
+
+    /// Returns the name at the given offset in the file.
+    fn reference_at(&self, file: &Path, offset: usize) -> Option<&Reference> {
+        let module = self.module(file)?;
+        let module = &module.borrow().path;
+        self.references
+            .iter()
+            .filter(|reference| reference.module == *module)
+            .find(|reference| contains(&reference.span, offset))
+    }
+
+    /// Returns the file and range of the definition of the name
+    /// at the given offset.
+    pub fn definition(&self, file: &Path, offset: usize) -> Option<(PathBuf, Value)> {
+        let reference = self.reference_at(file, offset)?;
+        let definition = self
+            .references
+            .iter()
+            .find(|other| other.is_definition && other.definition.same(&reference.definition))?;
+
+        let module = definition.module.to_string();
+        let file = self.files.get(&module)?;
+        let src = self.source(file)?;
+        let span = definition.span.start as usize..definition.span.end as usize;
+        Some((file.clone(), text::range(&src, span)))
+    }
+
+    /// Returns the signature of what the name at the given offset
+    /// refers to, along with the range of the name.
+    pub fn hover(&self, file: &Path, offset: usize) -> Option<(String, Value)> {
+        let reference = self.reference_at(file, offset)?;
+        let signature = match &reference.definition {
+            Definition::Local(var) => format!("{} {}: {}", val_var(var.mutable), var.name, var.ty),
+            Definition::Field(field) => {
+                format!("{} {}: {}", val_var(field.mutable), field.name, field.ty)
+            }
+            Definition::Function(func) => Declaration::Function(Rc::clone(func)).to_string(),
+            Definition::Adt(adt) => Declaration::Adt(Rc::clone(adt)).to_string(),
+        };
+
+        let src = self.source(file)?;
+        let span = reference.span.start as usize..reference.span.end as usize;
+        Some((signature, text::range(&src, span)))
+    }
+
+    /// Returns completion items for the given offset.
+    pub fn completions(&self, file: &Path, offset: usize) -> Vec<Value> {
+        self.completions_(file, offset).unwrap_or_default()
+    }
+
+    fn completions_(&self, file: &Path, offset: usize) -> Option<Vec<Value>> {
+        let module = self.module(file)?;
+        let src = Rc::clone(&module.borrow().src);
+        let before = &src[..offset.min(src.len())];
+
+        let line = &before[before.rfind('\n').map_or(0, |i| i + 1)..];
+        let line = line.trim_start();
+        if let Some(path) = line
+            .strip_prefix("import ")
+            .or_else(|| line.strip_prefix("export "))
+        {
+            return Some(self.import_completions(&module, path.trim_start()));
+        }
+
+        // Skip the part of the name that was already typed
+        let start = before
+            .trim_end_matches(|c: char| c.is_alphanumeric() || c == '_')
+            .len();
+        let before = &before[..start];
+        if let Some(receiver) = before.strip_suffix('.') {
+            let ty = self.receiver_type(&module, receiver, start - 1)?;
+            Some(self.member_completions(&module, &ty, offset))
+        } else if let Some(receiver) = before.strip_suffix(':') {
+            let adt = self.static_receiver(&module, receiver, start - 1)?;
+            Some(static_completions(&adt))
+        } else {
+            Some(self.scope_completions(&module, offset))
+        }
+    }
+
+    /// Completions of module paths and the declarations in modules
+    /// inside an import. `path` is the path typed so far.
+    fn import_completions(&self, module: &MutRc<Module>, path: &str) -> Vec<Value> {
+        let from = Rc::clone(&module.borrow().path);
+        let mut segments: Vec<&str> = path.split('/').collect();
+        segments.pop();
+
+        // Relative imports start at the directory of the importing module
+        let mut prefix: Vec<&str> = Vec::new();
+        if matches!(segments.first(), Some(&".") | Some(&"..")) {
+            let parts = from.parts();
+            prefix = parts[..parts.len() - 1]
+                .iter()
+                .map(SmolStr::as_str)
+                .collect();
+        }
+        for segment in segments {
+            match segment {
+                "." => (),
+                ".." => {
+                    prefix.pop();
+                }
+                _ => prefix.push(segment),
+            }
+        }
+
+        let mut items = Vec::new();
+        for other in &self.gir.as_ref().unwrap().modules {
+            let other = other.borrow();
+            let parts = other.path.parts();
+            if parts.len() < prefix.len() || !parts.iter().zip(&prefix).all(|(a, b)| a == b) {
+                continue;
+            }
+
+            if let Some(name) = parts.get(prefix.len()) {
+                if !items
+                    .iter()
+                    .any(|item: &Value| item["label"] == name.as_str())
+                {
+                    items.push(json!({ "label": name.as_str(), "kind": MODULE }));
+                }
+            } else {
+                for (name, decl) in importable(&other, &from) {
+                    items.push(declaration_item(&name, &decl));
+                }
+            }
+        }
+        items
+    }
+
+    /// Returns the type of the expression before a '.' at `dot`,
+    /// if it is a name or 'this'.
+    fn receiver_type(&self, module: &MutRc<Module>, receiver: &str, dot: usize) -> Option<Type> {
+        if receiver.ends_with("this") {
+            return enclosing_adt(module, dot).map(|adt| Declaration::Adt(adt).to_type());
+        }
+
+        let path = Rc::clone(&module.borrow().path);
+        let reference = self
+            .references
+            .iter()
+            .find(|r| r.module == path && r.span.end as usize == dot)?;
+        match &reference.definition {
+            Definition::Local(var) => Some(var.ty.clone()),
+            Definition::Field(field) => Some(field.ty.clone()),
+            _ => None,
+        }
+    }
+
+    /// Returns the ADT named before a ':' at `colon`.
+    fn static_receiver(
+        &self,
+        module: &MutRc<Module>,
+        receiver: &str,
+        colon: usize,
+    ) -> Option<MutRc<ADT>> {
+        let path = Rc::clone(&module.borrow().path);
+        let reference = self
+            .references
+            .iter()
+            .find(|r| r.module == path && r.span.end as usize == colon);
+        match reference.map(|r| &r.definition) {
+            Some(Definition::Adt(adt)) => Some(Rc::clone(adt)),
+            _ => {
+                let start = receiver
+                    .trim_end_matches(|c: char| c.is_alphanumeric() || c == '_')
+                    .len();
+                match module.borrow().find_decl(&receiver[start..])? {
+                    Declaration::Adt(adt) => Some(adt),
+                    Declaration::Function(_) => None,
+                }
+            }
+        }
+    }
+
+    /// Completions of the fields and methods of a type.
+    fn member_completions(&self, module: &MutRc<Module>, ty: &Type, offset: usize) -> Vec<Value> {
+        let from = Rc::clone(&module.borrow().path);
+        let adt = match ty {
+            Type::Variable(TypeVariable {
+                bound: TypeParameterBound::Interface(iface),
+                ..
+            }) => iface.try_adt(),
+            _ => ty.try_adt(),
+        };
+
+        let mut items = Vec::new();
+        if let Some(adt) = adt {
+            let adt_ref = adt.ty.borrow();
+            let own = adt_ref.module.borrow().path.clone();
+            let visible = |visibility: Visibility| match visibility {
+                // Private members are only visible inside the ADT itself
+                Visibility::Private => {
+                    own == from && contains(&adt_ref.ast.cst.text_range(), offset)
+                }
+                _ => visibility.from(&own, &from),
+            };
+
+            for field in adt_ref.fields.values().filter(|f| visible(f.visibility)) {
+                items.push(json!({
+                    "label": field.name.as_str(),
+                    "kind": FIELD,
+                    "detail": format!("{} {}: {}", val_var(field.mutable), field.name, field.ty),
+                }));
+            }
+            for (name, method) in &adt_ref.methods {
+                if writable(name) && visible(method.borrow().visibility) {
+                    items.push(function_item(name, method, METHOD));
+                }
+            }
+        }
+
+        if let Some(impls) = self.gir.as_ref().unwrap().iface_impls.get(ty) {
+            for (name, method) in impls.borrow().methods.iter().filter(|(n, _)| writable(n)) {
+                items.push(function_item(name, method, METHOD));
+            }
+        }
+        items
+    }
+
+    /// Completions of everything in scope: Declarations and imports of the
+    /// module, and variables of the function at the offset.
+    fn scope_completions(&self, module: &MutRc<Module>, offset: usize) -> Vec<Value> {
+        let module = module.borrow();
+        let mut items: Vec<Value> = module
+            .declarations
+            .iter()
+            .chain(&module.imports.decls)
+            .chain(&module.exports.decls)
+            .filter(|(name, _)| writable(name))
+            .map(|(name, decl)| declaration_item(name, decl))
+            .collect();
+        // Wildcard imports, like the prelude
+        for other in module.imports.modules.iter().chain(&module.exports.modules) {
+            for (name, decl) in importable(&other.borrow(), &module.path) {
+                items.push(declaration_item(&name, &decl));
+            }
+        }
+
+        let function = module
+            .functions
+            .iter()
+            .filter(|func| {
+                let func = func.borrow();
+                matches!(&func.ast, Some(ast) if contains(&ast.cst.text_range(), offset))
+            })
+            .min_by_key(|func| {
+                let range = func.borrow().ast.as_ref().unwrap().cst.text_range();
+                range.end - range.start
+            });
+        if let Some(func) = function {
+            let func = func.borrow();
+            let vars = func.parameters.iter().chain(func.variables.values());
+            for var in vars.filter(|var| writable(&var.name)) {
+                items.push(json!({
+                    "label": var.name.as_str(),
+                    "kind": VARIABLE,
+                    "detail": format!("{} {}: {}", val_var(var.mutable), var.name, var.ty),
+                }));
+            }
+        }
+        items
+    }
+
+    /// Converts the errors to diagnostics in protocol format.
+    fn to_diagnostics(&self, errors: &Errors) -> Vec<Value> {
+        let src = errors.src.as_ref().map_or("", |src| src.as_str());
+        let file = |module: &str| self.files.get(module).map(|f| f.display().to_string());
+        errors
+            .diagnostics(&file)
+            .into_iter()
+            .map(|diagnostic| {
+                let range = match &diagnostic.span {
+                    Some(span) => text::range(src, trim(src, span.byte_start..span.byte_end)),
+                    None => text::range(src, 0..0),
+                };
+                let mut message = diagnostic.message.clone();
+                for note in &diagnostic.notes {
+                    message.push_str(&format!("\n{}: {}", note.kind, note.message));
+                }
+                let related: Vec<Value> = diagnostic
+                    .labels
+                    .iter()
+                    .filter(|label| label.module == diagnostic.module)
+                    .filter_map(|label| {
+                        let file = self.files.get(&label.module)?;
+                        let span = label.span.byte_start..label.span.byte_end;
+                        Some(json!({
+                            "location": {
+                                "uri": text::path_to_uri(file),
+                                "range": text::range(src, trim(src, span)),
+                            },
+                            "message": label.message,
+                        }))
+                    })
+                    .collect();
+
+                json!({
+                    "range": range,
+                    "severity": if diagnostic.severity == "error" { ERROR } else { WARNING },
+                    "code": diagnostic.code,
+                    "source": "gelixrs",
+                    "message": message,
+                    "relatedInformation": related,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Returns the innermost ADT declared in the module whose declaration
+/// contains the offset.
+fn enclosing_adt(module: &MutRc<Module>, offset: usize) -> Option<MutRc<ADT>> {
+    let module = module.borrow();
+    let mut adts: Vec<MutRc<ADT>> = Vec::new();
+    for decl in module.declarations.values() {
+        if let Declaration::Adt(adt) = decl {
+            adts.push(Rc::clone(adt));
+            if let ADTType::Enum { cases } = &adt.borrow().ty {
+                adts.extend(cases.values().cloned());
+            }
+        }
+    }
+
+    adts.into_iter()
+        .filter(|adt| contains(&adt.borrow().ast.cst.text_range(), offset))
+        .min_by_key(|adt| {
+            let range = adt.borrow().ast.cst.text_range();
+            range.end - range.start
+        })
+}
+
+/// Completions of the enum cases and static methods of an ADT.
+fn static_completions(adt: &MutRc<ADT>) -> Vec<Value> {
+    let adt = adt.borrow();
+    let mut items = Vec::new();
+    if let ADTType::Enum { cases } = &adt.ty {
+        for (name, case) in cases.iter() {
+            items.push(json!({
+                "label": name.as_str(),
+                "kind": ENUM_MEMBER,
+                "detail": Declaration::Adt(Rc::clone(case)).to_string(),
+            }));
+        }
+    }
+    for (name, method) in adt.methods.iter().filter(|(name, _)| writable(name)) {
+        let is_static = matches!(&method.borrow().ast, Some(ast) if ast.is_static());
+        if is_static {
+            items.push(function_item(name, method, FUNCTION));
+        }
+    }
+    items
+}
+
+/// All declarations other modules can import from the module.
+fn importable(module: &Module, from: &ModPath) -> Vec<(SmolStr, Declaration)> {
+    let exported = module.exports.modules.iter().flat_map(|other| {
+        let other = other.borrow();
+        other
+            .declarations
+            .iter()
+            .chain(&other.exports.decls)
+            .map(|(name, decl)| (name.clone(), decl.clone()))
+            .collect::<Vec<_>>()
+    });
+    module
+        .declarations
+        .iter()
+        .chain(&module.exports.decls)
+        .map(|(name, decl)| (name.clone(), decl.clone()))
+        .chain(exported)
+        .filter(|(name, decl)| writable(name) && decl.visible(from))
+        .collect()
+}
+
+/// Returns if the name can be written in code. Names of
+/// declarations and variables created by the compiler cannot.
+fn writable(name: &str) -> bool {
+    name.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
+
+fn declaration_item(name: &SmolStr, decl: &Declaration) -> Value {
+    let kind = match decl {
+        Declaration::Function(_) => FUNCTION,
+        Declaration::Adt(adt) => match adt.borrow().ty {
+            ADTType::Class { .. } => CLASS,
+            ADTType::Interface => INTERFACE,
+            ADTType::Enum { .. } => ENUM,
+            ADTType::EnumCase { .. } => ENUM_MEMBER,
+        },
+    };
+    json!({ "label": name.as_str(), "kind": kind, "detail": decl.to_string() })
+}
+
+fn function_item(name: &SmolStr, func: &MutRc<Function>, kind: u8) -> Value {
+    json!({
+        "label": name.as_str(),
+        "kind": kind,
+        "detail": Declaration::Function(Rc::clone(func)).to_string(),
+    })
+}
+
+fn val_var(mutable: bool) -> &'static str {
+    if mutable {
+        "var"
+    } else {
+        "val"
+    }
+}
+
+/// Removes whitespace at the end of the span; spans of syntax nodes include it.
+fn trim(src: &str, span: Range<usize>) -> Range<usize> {
+    match src.get(span.clone()) {
+        Some(text) if !text.trim().is_empty() => span.start..span.start + text.trim_end().len(),
+        _ => span,
+    }
+}
+
+/// Returns if the offset is inside the range, or directly after it.
+fn contains(range: &Range<u32>, offset: usize) -> bool {
+    range.start as usize <= offset && offset <= range.end as usize
+}
+
+// Diagnostic severities of the protocol.
+const ERROR: u8 = 1;
+const WARNING: u8 = 2;
+
+// Completion item kinds of the protocol.
+const METHOD: u8 = 2;
+const FUNCTION: u8 = 3;
+const FIELD: u8 = 5;
+const VARIABLE: u8 = 6;
+const CLASS: u8 = 7;
+const INTERFACE: u8 = 8;
+const MODULE: u8 = 9;
+const ENUM: u8 = 13;
+const ENUM_MEMBER: u8 = 20;
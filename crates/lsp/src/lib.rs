@@ -0,0 +1,13 @@
+//! A language server for gelix, communicating with editors
+//! using the Language Server Protocol.
+//! It provides diagnostics, hover, go to definition,
+//! completion and document symbols.
+
+mod analysis;
+mod project;
+mod rpc;
+mod server;
+mod symbols;
+mod text;
+
+pub use server::Server;
@@ -0,0 +1,15 @@
+use gelixrs_lsp::Server;
+use std::io;
+
+/// Runs the language server over stdin and stdout.
+fn main() {
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    let code = Server::default()
+        .run(&mut stdin.lock(), &mut stdout.lock())
+        .unwrap_or_else(|err| {
+            eprintln!("Failed to communicate with the client: {}", err);
+            1
+        });
+    std::process::exit(code);
+}
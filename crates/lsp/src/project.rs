@@ -0,0 +1,111 @@
+//! Finding and parsing the modules of the program a file belongs to.
+//! This mirrors how the compiler reads its input, except that files
+//! open in the editor are read from their unsaved text.
+
+use common::ModPath;
+use error::Errors;
+use smol_str::SmolStr;
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+/// All files open in the editor, with their current text.
+pub type Documents = HashMap<PathBuf, Rc<String>>;
+
+/// A parsed module, along with the file it is from.
+pub struct SourceFile {
+    pub path: PathBuf,
+    pub module: ast::Module,
+    /// The syntax errors in the module.
+    pub errors: Errors,
+}
+
+/// Returns the input the compiler would be given to compile the
+/// program containing the file: The closest directory containing a
+/// 'main.gel', or the file itself if there is none.
+/// Directories outside the workspace `root` are not searched.
+pub fn find_input(file: &Path, root: Option<&Path>) -> PathBuf {
+    for dir in file.ancestors().skip(1) {
+        if dir.join("main.gel").is_file() {
+            return dir.to_path_buf();
+        }
+        if !matches!(root, Some(root) if dir != root && dir.starts_with(root)) {
+            break;
+        }
+    }
+    file.to_path_buf()
+}
+
+/// Finds the standard library, searching the same places
+/// as the compiler. The workspace `root` takes the place of the
+/// current directory, which is meaningless for a language server.
+pub fn find_std(root: Option<&Path>) -> Option<PathBuf> {
+    let local = root.map(|root| root.join("std"));
+    let user = dirs::data_dir().map(|dir| dir.join("gelix").join("std"));
+    let system = Some(PathBuf::from("/usr/local/lib/gelix/std"));
+    local
+        .into_iter()
+        .chain(user)
+        .chain(system)
+        .find(|dir| dir.is_dir())
+}
+
+/// Parses all modules of the given input.
+/// Modules are returned even if they contain syntax errors.
+pub fn load(input: &Path, documents: &Documents) -> Vec<SourceFile> {
+    let mut files = Vec::new();
+    load_modules(input, &mut ModPath::new(), documents, &mut files);
+    files
+}
+
+fn load_modules(
+    input: &Path,
+    path: &mut ModPath,
+    documents: &Documents,
+    files: &mut Vec<SourceFile>,
+) {
+    path.push(file_stem(input));
+
+    if let Ok(dir) = input.read_dir() {
+        for file in dir.filter_map(Result::ok).map(|entry| entry.path()) {
+            // A file named 'module.gel' has the containing directory as its module path
+            if file.file_name() == Some("module.gel".as_ref()) {
+                files.extend(load_file(&file, path, documents));
+            } else {
+                load_modules(&file, path, documents, files);
+            }
+        }
+    } else if input.extension() == Some("gel".as_ref()) {
+        files.extend(load_file(input, path, documents));
+    }
+
+    path.pop();
+}
+
+fn load_file(file: &Path, path: &ModPath, documents: &Documents) -> Option<SourceFile> {
+    let src = match documents.get(file) {
+        Some(src) => Rc::clone(src),
+        None => Rc::new(fs::read_to_string(file).ok()?),
+    };
+    let (cst, errors) = parser::parse(&src).into_parts();
+    Some(SourceFile {
+        path: file.to_path_buf(),
+        module: ast::Module {
+            cst,
+            path: Rc::new(path.clone()),
+            src: Rc::clone(&src),
+        },
+        errors: Errors {
+            errors,
+            src: Some(src),
+            origin: path.to_string(),
+        },
+    })
+}
+
+fn file_stem(path: &Path) -> SmolStr {
+    SmolStr::new(path.file_stem().unwrap_or_default().to_string_lossy())
+}
@@ -0,0 +1,65 @@
+//! The JSON-RPC transport of the protocol: messages with
+//! a `Content-Length` header, sent over stdin and stdout.
+
+use serde_json::{json, Value};
+use std::io::{self, BufRead, Write};
+
+/// The error code for requests the server does not know.
+pub const METHOD_NOT_FOUND: i64 = -32601;
+/// The error code for requests with missing or invalid parameters.
+pub const INVALID_PARAMS: i64 = -32602;
+
+/// Reads the next message. Returns `None` once the input is closed.
+pub fn read_message(input: &mut impl BufRead) -> io::Result<Option<Value>> {
+    let mut length = None;
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some(value) = header.strip_prefix("Content-Length:") {
+            length = value.trim().parse::<usize>().ok();
+        }
+    }
+
+    let length = length.ok_or_else(|| invalid_data("Missing Content-Length header."))?;
+    let mut body = vec![0; length];
+    input.read_exact(&mut body)?;
+    serde_json::from_slice(&body)
+        .map(Some)
+        .map_err(|_| invalid_data("Message is not valid JSON."))
+}
+
+/// Writes a message, including its header.
+pub fn write_message(output: &mut impl Write, message: &Value) -> io::Result<()> {
+    let body = message.to_string();
+    write!(output, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    output.flush()
+}
+
+/// A successful response to the request with the given ID.
+pub fn response(id: Value, result: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "result": result })
+}
+
+/// A failed response to the request with the given ID.
+pub fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message },
+    })
+}
+
+/// A notification sent to the client.
+pub fn notification(method: &str, params: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "method": method, "params": params })
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
@@ -0,0 +1,275 @@
+//! The state of the server and the handling of messages.
+
+use crate::{
+    analysis::Analysis,
+    project::{self, Documents},
+    rpc::{self, INVALID_PARAMS, METHOD_NOT_FOUND},
+    symbols,
+    text::{self, path_to_uri, uri_to_path},
+};
+use gir_generator::GIRFlags;
+use serde_json::{json, Value};
+use std::{
+    collections::HashMap,
+    io::{self, BufRead, Write},
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+#[derive(Default)]
+pub struct Server {
+    /// The root of the workspace opened in the editor, if any.
+    root: Option<PathBuf>,
+    /// The directory of the standard library, if found.
+    std_dir: Option<PathBuf>,
+    /// The standard library compiled from disk, once it was needed.
+    /// Programs are compiled against it to not recompile it on every change.
+    std: Option<Analysis>,
+    documents: Documents,
+    /// The last analysis of every program, by its input.
+    programs: HashMap<PathBuf, Analysis>,
+    /// If the client requested shutdown; it is expected to exit next.
+    shutdown: bool,
+}
+
+impl Server {
+    /// Handles messages until the client asks the server to exit.
+    /// Returns the exit code of the server.
+    pub fn run(&mut self, input: &mut impl BufRead, output: &mut impl Write) -> io::Result<i32> {
+        while let Some(message) = rpc::read_message(input)? {
+            if message["method"] == "exit" {
+                return Ok(if self.shutdown { 0 } else { 1 });
+            }
+            for reply in self.handle(message) {
+                rpc::write_message(output, &reply)?;
+            }
+        }
+        Ok(1)
+    }
+
+    /// Handles a message, returning all messages to send in reply.
+    pub fn handle(&mut self, message: Value) -> Vec<Value> {
+        let method = message["method"].as_str().unwrap_or_default();
+        let params = &message["params"];
+        match message.get("id") {
+            Some(id) => {
+                let reply = match self.request(method, params) {
+                    Ok(result) => rpc::response(id.clone(), result),
+                    Err((code, msg)) => rpc::error_response(id.clone(), code, msg),
+                };
+                vec![reply]
+            }
+            None => self.notification(method, params),
+        }
+    }
+
+    fn request(&mut self, method: &str, params: &Value) -> Result<Value, (i64, &'static str)> {
+        match method {
+            "initialize" => Ok(self.initialize(params)),
+            "shutdown" => {
+                self.shutdown = true;
+                Ok(Value::Null)
+            }
+
+            "textDocument/hover" => {
+                let hover = self
+                    .locate(params)?
+                    .and_then(|(analysis, file, offset)| analysis.hover(&file, offset));
+                Ok(hover.map_or(Value::Null, |(signature, range)| {
+                    json!({
+                        "contents": {
+                            "kind": "markdown",
+                            "value": format!("```gelix\n{}\n```", signature),
+                        },
+                        "range": range,
+                    })
+                }))
+            }
+
+            "textDocument/definition" => {
+                let definition = self
+                    .locate(params)?
+                    .and_then(|(analysis, file, offset)| analysis.definition(&file, offset));
+                Ok(definition.map_or(
+                    Value::Null,
+                    |(file, range)| json!({ "uri": path_to_uri(&file), "range": range }),
+                ))
+            }
+
+            "textDocument/completion" => {
+                let items = self
+                    .locate(params)?
+                    .map(|(analysis, file, offset)| analysis.completions(&file, offset));
+                Ok(json!(items.unwrap_or_default()))
+            }
+
+            "textDocument/documentSymbol" => {
+                let file = document_path(params).ok_or(INVALID_URI)?;
+                let src = match self.read(&file) {
+                    Some(src) => src,
+                    None => return Ok(Value::Null),
+                };
+                let (cst, _) = parser::parse(&src).into_parts();
+                let module = ast::Module {
+                    cst,
+                    path: Default::default(),
+                    src,
+                };
+                Ok(json!(symbols::document_symbols(&module)))
+            }
+
+            _ => Err((METHOD_NOT_FOUND, "Unknown method.")),
+        }
+    }
+
+    fn notification(&mut self, method: &str, params: &Value) -> Vec<Value> {
+        let file = match document_path(params) {
+            Some(file) => file,
+            None => return vec![],
+        };
+
+        match method {
+            "textDocument/didOpen" => {
+                let src = params["textDocument"]["text"].as_str().unwrap_or_default();
+                self.documents
+                    .insert(file.clone(), Rc::new(src.to_string()));
+            }
+            "textDocument/didChange" => {
+                // The server only supports full synchronization,
+                // every change contains the entire text
+                let changes = params["contentChanges"].as_array();
+                match changes
+                    .and_then(|c| c.last())
+                    .and_then(|c| c["text"].as_str())
+                {
+                    Some(src) => self
+                        .documents
+                        .insert(file.clone(), Rc::new(src.to_string())),
+                    None => return vec![],
+                };
+            }
+            "textDocument/didClose" => {
+                self.documents.remove(&file);
+            }
+            "textDocument/didSave" => (),
+            _ => return vec![],
+        }
+        self.analyze(&file)
+    }
+
+    fn initialize(&mut self, params: &Value) -> Value {
+        self.root = params["rootUri"]
+            .as_str()
+            .and_then(uri_to_path)
+            .or_else(|| params["rootPath"].as_str().map(PathBuf::from));
+        self.std_dir = project::find_std(self.root.as_deref());
+
+        json!({
+            "capabilities": {
+                "textDocumentSync": {
+                    "openClose": true,
+                    "change": 1,
+                    "save": { "includeText": false },
+                },
+                "hoverProvider": true,
+                "definitionProvider": true,
+                "documentSymbolProvider": true,
+                "completionProvider": {
+                    "triggerCharacters": [".", ":", "/"],
+                },
+            },
+            "serverInfo": {
+                "name": "gelixrs_lsp",
+                "version": env!("CARGO_PKG_VERSION"),
+            },
+        })
+    }
+
+    /// Compiles the program containing the given file, returning
+    /// notifications with the diagnostics of all its files.
+    fn analyze(&mut self, file: &Path) -> Vec<Value> {
+        // Files of the standard library are compiled on their own, as a library
+        let std_dir = self.std_dir.clone().filter(|std| file.starts_with(std));
+        let input = std_dir
+            .clone()
+            .unwrap_or_else(|| project::find_input(file, self.root.as_deref()));
+        let files = project::load(&input, &self.documents);
+
+        let analysis = if std_dir.is_some() {
+            let flags = GIRFlags {
+                library: true,
+                ..GIRFlags::default()
+            };
+            Analysis::compile(files, None, flags)
+        } else if let Some(std) = self.std() {
+            Analysis::compile(files, Some(std), GIRFlags::default())
+        } else {
+            let flags = GIRFlags {
+                no_std: true,
+                no_prelude: true,
+                ..GIRFlags::default()
+            };
+            Analysis::compile(files, None, flags)
+        };
+
+        let notifications = analysis
+            .diagnostics
+            .iter()
+            .map(|(path, diagnostics)| {
+                rpc::notification(
+                    "textDocument/publishDiagnostics",
+                    json!({ "uri": path_to_uri(path), "diagnostics": diagnostics }),
+                )
+            })
+            .collect();
+        self.programs.insert(input, analysis);
+        notifications
+    }
+
+    /// Returns the standard library, compiling it if it was not yet.
+    fn std(&mut self) -> Option<&Analysis> {
+        if self.std.is_none() {
+            let flags = GIRFlags {
+                library: true,
+                ..GIRFlags::default()
+            };
+            let files = project::load(self.std_dir.as_ref()?, &Documents::new());
+            self.std = Some(Analysis::compile(files, None, flags));
+        }
+        self.std.as_ref().filter(|std| std.gir.is_some())
+    }
+
+    /// Returns the analysis of the document in the request parameters,
+    /// along with its path and the offset of the requested position.
+    /// `None` if the document was not compiled successfully.
+    fn locate(&self, params: &Value) -> Result<Option<Located<'_>>, (i64, &'static str)> {
+        let file = document_path(params).ok_or(INVALID_URI)?;
+        let analysis = self
+            .programs
+            .values()
+            .find(|analysis| analysis.contains(&file));
+        Ok(analysis.and_then(|analysis| {
+            let src = analysis.source(&file)?;
+            let offset = text::offset(&src, &params["position"]);
+            Some((analysis, file, offset))
+        }))
+    }
+
+    /// Returns the current text of the file.
+    fn read(&self, file: &Path) -> Option<Rc<String>> {
+        match self.documents.get(file) {
+            Some(src) => Some(Rc::clone(src)),
+            None => std::fs::read_to_string(file).ok().map(Rc::new),
+        }
+    }
+}
+
+/// Returns the path of the document the parameters refer to.
+fn document_path(params: &Value) -> Option<PathBuf> {
+    uri_to_path(params["textDocument"]["uri"].as_str()?)
+}
+
+/// An analysis, a file in it, and an offset in the file.
+type Located<'a> = (&'a Analysis, PathBuf, usize);
+
+const INVALID_URI: (i64, &str) = (INVALID_PARAMS, "Invalid document URI.");
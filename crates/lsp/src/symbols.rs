@@ -0,0 +1,121 @@
+//! The outline of a document, built from its syntax tree alone
+//! so that it is available even if the program does not compile.
+
+use crate::text;
+use ast::{Adt, CSTNode, Function, Module};
+use serde_json::{json, Value};
+use syntax::kind::SyntaxKind;
+
+/// Returns the symbols declared in the module, with members of
+/// classes and interfaces nested inside them.
+pub fn document_symbols(module: &Module) -> Vec<Value> {
+    let src = module.src.as_str();
+    let functions = module
+        .functions()
+        .map(|func| function(src, &func, FUNCTION));
+    let adts = module.adts().map(|adt| adt_symbol(src, &adt));
+    let impls = module.impls().map(|im| {
+        let name = match im.iface() {
+            Some(iface) => format!(
+                "impl {} for {}",
+                iface.cst.text().trim(),
+                im.implementor().cst.text().trim()
+            ),
+            None => format!("impl {}", im.implementor().cst.text().trim()),
+        };
+        let methods = im.methods().map(|m| function(src, &m, METHOD)).collect();
+        symbol(src, name, NAMESPACE, &im.cst, None, methods)
+    });
+    functions.chain(adts).chain(impls).collect()
+}
+
+fn adt_symbol(src: &str, adt: &Adt) -> Value {
+    let kind = match adt.kind() {
+        SyntaxKind::Interface => INTERFACE,
+        SyntaxKind::Enum => ENUM,
+        SyntaxKind::EnumCase => ENUM_MEMBER,
+        _ => CLASS,
+    };
+    let members = adt.members().map(|member| {
+        let name = member.name().to_string();
+        symbol(src, name, FIELD, &member.cst, Some(&member.cst), vec![])
+    });
+    let constructors = adt.constructors().map(|c| {
+        symbol(
+            src,
+            "construct".to_string(),
+            CONSTRUCTOR,
+            &c.cst,
+            None,
+            vec![],
+        )
+    });
+    let methods = adt.methods().map(|m| function(src, &m, METHOD));
+    let cases = adt.cases().map(|case| adt_symbol(src, &case));
+    let children = members
+        .chain(constructors)
+        .chain(methods)
+        .chain(cases)
+        .collect();
+
+    let name = adt.name();
+    symbol(
+        src,
+        name.name().to_string(),
+        kind,
+        &adt.cst,
+        Some(&name.cst),
+        children,
+    )
+}
+
+fn function(src: &str, func: &Function, kind: u8) -> Value {
+    let name = func.sig().name();
+    symbol(
+        src,
+        name.name().to_string(),
+        kind,
+        &func.cst,
+        Some(&name.cst),
+        vec![],
+    )
+}
+
+/// Creates a symbol for the given node. `name_node` is the node whose identifier
+/// is selected when the symbol is picked; the start of the node if absent.
+fn symbol(
+    src: &str,
+    name: String,
+    kind: u8,
+    node: &CSTNode,
+    name_node: Option<&CSTNode>,
+    children: Vec<Value>,
+) -> Value {
+    let span = node.text_range();
+    let start = span.start as usize;
+    // Spans of nodes include trivia following them, which is not part of the symbol
+    let end = start + src[start..span.end as usize].trim_end().len();
+    let selection = name_node
+        .and_then(|n| n.token_range(SyntaxKind::Identifier))
+        .map_or(start..start, |r| r.start as usize..r.end as usize);
+
+    json!({
+        // Editors reject symbols without a name, which declarations with syntax errors can have
+        "name": if name.is_empty() { "<missing>".to_string() } else { name },
+        "kind": kind,
+        "range": text::range(src, start..end),
+        "selectionRange": text::range(src, selection),
+        "children": children,
+    })
+}
+
+// Symbol kinds of the protocol.
+const NAMESPACE: u8 = 3;
+const CLASS: u8 = 5;
+const METHOD: u8 = 6;
+const FIELD: u8 = 8;
+const CONSTRUCTOR: u8 = 9;
+const ENUM: u8 = 10;
+const INTERFACE: u8 = 11;
+const FUNCTION: u8 = 12;
+const ENUM_MEMBER: u8 = 22;
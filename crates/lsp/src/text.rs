@@ -0,0 +1,94 @@
+//! Conversion between byte offsets and the positions of the protocol,
+//! as well as between file URIs and paths.
+
+use serde_json::{json, Value};
+use std::{
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+/// Returns the position of the given byte offset.
+/// Positions have a line and a character inside it, both starting
+/// at 0. Characters are counted in UTF-16 code units.
+pub fn position(src: &str, offset: usize) -> Value {
+    let offset = clamp(src, offset);
+    let line_start = src[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let line = src[..line_start].matches('\n').count();
+    let character: usize = src[line_start..offset].chars().map(char::len_utf16).sum();
+    json!({ "line": line, "character": character })
+}
+
+/// Returns the range of the given byte offsets.
+pub fn range(src: &str, span: Range<usize>) -> Value {
+    json!({ "start": position(src, span.start), "end": position(src, span.end) })
+}
+
+/// Returns the byte offset of the given position.
+/// Positions past the end of a line or the source are moved to its end.
+pub fn offset(src: &str, position: &Value) -> usize {
+    let line = position["line"].as_u64().unwrap_or(0) as usize;
+    let character = position["character"].as_u64().unwrap_or(0) as usize;
+
+    let line_start = if line == 0 {
+        0
+    } else {
+        match src.match_indices('\n').nth(line - 1) {
+            Some((i, _)) => i + 1,
+            None => return src.len(),
+        }
+    };
+
+    let mut units = 0;
+    for (i, c) in src[line_start..].char_indices() {
+        if units >= character || c == '\n' {
+            return line_start + i;
+        }
+        units += c.len_utf16();
+    }
+    src.len()
+}
+
+/// Moves the offset back to the start of a character if it points
+/// inside one, and to the end of the source if it is past it.
+fn clamp(src: &str, offset: usize) -> usize {
+    let mut offset = offset.min(src.len());
+    while !src.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    offset
+}
+
+/// Returns the path of a `file://` URI.
+pub fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    let path = uri.strip_prefix("file://")?;
+    let mut bytes = Vec::with_capacity(path.len());
+    let mut rest = path.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        match (byte, tail) {
+            (b'%', [hi, lo, tail @ ..]) => {
+                let hex = [*hi, *lo];
+                bytes.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+                rest = tail;
+            }
+            _ => {
+                bytes.push(byte);
+                rest = tail;
+            }
+        }
+    }
+    String::from_utf8(bytes).ok().map(PathBuf::from)
+}
+
+/// Returns the `file://` URI of a path.
+pub fn path_to_uri(path: &Path) -> String {
+    let mut uri = String::from("file://");
+    for byte in path.to_string_lossy().bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                uri.push(byte as char)
+            }
+            _ => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    uri
+}
@@ -5,13 +5,22 @@ use syntax::kind::SyntaxKind;
 // All tokens that indicate that a function has a body (bodies are optional in enum and interface definitions).
 static START_OF_FN_BODY: [SyntaxKind; 2] = [SyntaxKind::LeftBrace, SyntaxKind::Equal];
 
+// All tokens that can start a type.
+static TYPE_START: [SyntaxKind; 4] = [
+    SyntaxKind::Identifier,
+    SyntaxKind::Tilde,
+    SyntaxKind::Star,
+    SyntaxKind::LeftParen,
+];
+
 // All tokens that can be modifiers at all.
-static MODIFIERS: [SyntaxKind; 5] = [
+static MODIFIERS: [SyntaxKind; 6] = [
     SyntaxKind::Mod,
     SyntaxKind::Priv,
     SyntaxKind::Extern,
     SyntaxKind::Variadic,
     SyntaxKind::Value,
+    SyntaxKind::Static,
 ];
 
 // All tokens that can be modifiers on any declaration.
@@ -20,7 +29,7 @@ static GLOBAL_MODIFIERS: [SyntaxKind; 2] = [SyntaxKind::Mod, SyntaxKind::Priv];
 // All tokens that can be modifiers on a class member.
 static MEMBER_MODIFIERS: [SyntaxKind; 0] = [];
 // All tokens that can be modifiers on a method.
-static METHOD_MODIFIERS: [SyntaxKind; 0] = [];
+static METHOD_MODIFIERS: [SyntaxKind; 1] = [SyntaxKind::Static];
 // All tokens that can be modifiers on a constructor.
 static CONSTRUCTOR_MODIFIERS: [SyntaxKind; 0] = [];
 
@@ -38,7 +47,8 @@ impl<'p> Parser<'p> {
             SyntaxKind::Func => SyntaxKind::FunctionDecl,
             SyntaxKind::Import | SyntaxKind::Export => SyntaxKind::ImportDecl,
             SyntaxKind::Impl => SyntaxKind::ImplDecl,
-            _ => SyntaxKind::AdtDecl,
+            SyntaxKind::Class | SyntaxKind::Interface | SyntaxKind::Enum => SyntaxKind::AdtDecl,
+            _ => SyntaxKind::ErrorNode,
         };
         self.start_node_at(checkpoint, ty);
 
@@ -86,21 +96,25 @@ impl<'p> Parser<'p> {
         self.check_mods(&mods, "function");
         self.generic_ident("'func'");
         self.consume(SyntaxKind::LeftParen, "'('", "function name");
-        self.func_parameters();
+        self.func_parameters(true);
         if self.matches(SyntaxKind::Arrow) {
             self.type_()
         }
         self.end_node();
     }
 
-    pub fn func_parameters(&mut self) {
+    pub fn func_parameters(&mut self, allow_defaults: bool) {
         if !self.check(SyntaxKind::RightParen) {
             loop {
                 self.start_node(SyntaxKind::Parameter);
                 self.consume(SyntaxKind::Identifier, "parameter name", "left parenthesis");
                 self.consume(SyntaxKind::Colon, "':'", "parameter name");
                 self.type_();
+                if allow_defaults {
+                    self.parameter_default();
+                }
                 self.end_node();
+                self.recover(&[SyntaxKind::Comma, SyntaxKind::RightParen], false);
                 if !self.matches(SyntaxKind::Comma) {
                     break;
                 }
@@ -109,13 +123,22 @@ impl<'p> Parser<'p> {
         self.consume(SyntaxKind::RightParen, "')'", "parameters");
     }
 
+    // Reads the default value of a parameter ('a: i64 = 5'), if any.
+    fn parameter_default(&mut self) {
+        if self.matches(SyntaxKind::Equal) {
+            self.node_with(SyntaxKind::Initializer, Self::expression);
+        }
+    }
+
     fn generic_adt(&mut self, conf: ADTConfig) {
         self.check_mods(conf.modifiers, conf.name);
         self.generic_ident("ADT identifier");
 
         self.consume(SyntaxKind::LeftBrace, "'{'", "before body");
 
+        let until = conf.member_start();
         while !self.check(SyntaxKind::RightBrace) && !self.is_at_end() {
+            let start = self.source.position();
             match self.peek_past_modifiers() {
                 SyntaxKind::Var | SyntaxKind::Val if conf.has_members => self.adt_member(),
                 SyntaxKind::Construct if conf.has_constructors => self.constructor(),
@@ -123,6 +146,10 @@ impl<'p> Parser<'p> {
                 SyntaxKind::Identifier if conf.has_cases => self.enum_case(),
                 _ => self.error_at_current(GErr::E004),
             }
+            self.recover(&until, false);
+            if self.source.position() == start && !self.is_at_end() {
+                self.skip_erroneous();
+            }
         }
 
         self.consume(SyntaxKind::RightBrace, "'}'", "body");
@@ -131,6 +158,8 @@ impl<'p> Parser<'p> {
     fn method(&mut self, force_extern: bool) {
         self.start_node(SyntaxKind::Method);
         self.consume_modifiers();
+        // Static methods always have a body, even on interfaces
+        let force_extern = force_extern && !self.modifiers.contains(&SyntaxKind::Static);
 
         self.advance(); // Consume 'func'
         self.function_(&METHOD_MODIFIERS, force_extern);
@@ -177,7 +206,9 @@ impl<'p> Parser<'p> {
                 if self.matches(SyntaxKind::Colon) {
                     self.type_()
                 }
+                self.parameter_default();
                 self.end_node();
+                self.recover(&[SyntaxKind::Comma, SyntaxKind::RightParen], false);
                 if !self.matches(SyntaxKind::Comma) {
                     break;
                 }
@@ -226,6 +257,7 @@ impl<'p> Parser<'p> {
                     self.consume(SyntaxKind::Colon, "':'", "member name");
                     self.type_();
                     self.end_node();
+                    self.recover(&[SyntaxKind::Comma, SyntaxKind::RightParen], false);
 
                     if !self.matches(SyntaxKind::Comma) {
                         break;
@@ -240,28 +272,83 @@ impl<'p> Parser<'p> {
 
     fn import_declaration(&mut self) {
         self.check_mods(&IMPORT_MODIFIERS, "import/export");
-        self.consume(SyntaxKind::Identifier, "path", "import/export");
+        while self.matches(SyntaxKind::Dot) || self.matches(SyntaxKind::DotDot) {
+            self.consume(SyntaxKind::Slash, "'/'", "'.' or '..'");
+        }
+        self.import_path_part("import/export");
         while self.matches(SyntaxKind::Slash) {
-            self.consume_either(
-                SyntaxKind::Identifier,
-                SyntaxKind::Plus,
-                "'+' or path",
-                "'/'",
-            );
+            match self.peek() {
+                SyntaxKind::Plus => {
+                    self.advance();
+                    break;
+                }
+
+                SyntaxKind::LeftBrace => {
+                    self.advance();
+                    loop {
+                        self.node_with(SyntaxKind::ImportSymbol, |this| {
+                            this.consume(SyntaxKind::Identifier, "name", "import list");
+                            this.import_alias();
+                        });
+                        self.recover(&[SyntaxKind::Comma, SyntaxKind::RightBrace], false);
+                        if !self.matches(SyntaxKind::Comma) {
+                            break;
+                        }
+                    }
+                    self.consume(SyntaxKind::RightBrace, "'}'", "import list");
+                    break;
+                }
+
+                _ => self.import_path_part("'/'"),
+            }
+        }
+    }
+
+    // Reads a single part of an import path. If it is the last part,
+    // it is the imported symbol and gets wrapped in an ImportSymbol node.
+    fn import_path_part(&mut self, after: &'static str) {
+        self.skip_whitespace();
+        let checkpoint = self.checkpoint();
+        self.consume(SyntaxKind::Identifier, "'+', '{' or path", after);
+        if !self.check(SyntaxKind::Slash) {
+            self.start_node_at(checkpoint, SyntaxKind::ImportSymbol);
+            self.import_alias();
+            self.end_node();
+        }
+    }
+
+    fn import_alias(&mut self) {
+        if self.matches(SyntaxKind::As) {
+            self.consume(SyntaxKind::Identifier, "alias", "'as'");
         }
     }
 
     fn iface_impl(&mut self) {
-        self.node_with(SyntaxKind::Implementing, |this| this.type_());
-        self.consume(SyntaxKind::For, "'for'", "interface name");
-        self.node_with(SyntaxKind::Implementor, |this| this.type_());
+        // Inherent impls ('impl Type { ... }') only have an implementor
+        self.skip_whitespace();
+        let checkpoint = self.checkpoint();
+        self.type_();
+        if self.check(SyntaxKind::For) {
+            self.start_node_at(checkpoint, SyntaxKind::Implementing);
+            self.end_node();
+            self.advance(); // Consume 'for'
+            self.node_with(SyntaxKind::Implementor, |this| this.type_());
+        } else {
+            self.start_node_at(checkpoint, SyntaxKind::Implementor);
+            self.end_node();
+        }
         self.consume(SyntaxKind::LeftBrace, "'{'", "impl body");
 
         while !self.check(SyntaxKind::RightBrace) && !self.is_at_end() {
-            match self.peek() {
+            let start = self.source.position();
+            match self.peek_past_modifiers() {
                 SyntaxKind::Func => self.method(false),
                 _ => self.error_at_current(GErr::E004),
             }
+            self.recover(&[SyntaxKind::RightBrace, SyntaxKind::Func], false);
+            if self.source.position() == start && !self.is_at_end() {
+                self.skip_erroneous();
+            }
         }
         self.consume(SyntaxKind::RightBrace, "'}'", "impl body");
     }
@@ -278,6 +365,7 @@ impl<'p> Parser<'p> {
                     self.type_();
                 }
                 self.end_node();
+                self.recover(&[SyntaxKind::Comma, SyntaxKind::RightBracket], false);
                 if !self.matches(SyntaxKind::Comma) {
                     break;
                 }
@@ -302,11 +390,17 @@ impl<'p> Parser<'p> {
     // modifier, then restoring state and returning the first non-modifier.
     fn peek_past_modifiers(&mut self) -> SyntaxKind {
         self.modifiers.clear();
+        if self.poisoned {
+            return SyntaxKind::EndOfFile;
+        }
+
         self.source.save();
-        while MODIFIERS.contains(&self.peek()) {
+        self.pass_whitespace();
+        while self.peek_raw().map_or(false, |k| MODIFIERS.contains(&k)) {
             self.source.next();
+            self.pass_whitespace();
         }
-        let res = self.peek();
+        let res = self.peek_raw().unwrap_or(SyntaxKind::EndOfFile);
         self.source.restore();
         res
     }
@@ -318,7 +412,7 @@ impl<'p> Parser<'p> {
             .iter()
             .filter(|m| !allowed.contains(&m) && !GLOBAL_MODIFIERS.contains(&m))
         {
-            self.error_at_current(GErr::E006 {
+            self.error(GErr::E006 {
                 modifier: format!("{:?}", mod_),
                 on: name,
             })
@@ -329,12 +423,16 @@ impl<'p> Parser<'p> {
     pub fn type_(&mut self) {
         let check = self.checkpoint();
         self.start_node(SyntaxKind::Type);
-        let token = self.advance();
-        match token.kind {
+        if !TYPE_START.contains(&self.peek()) {
+            // Report before advancing to not consume the token
+            self.error_at_current(GErr::E003);
+        }
+        match self.advance_checked() {
             SyntaxKind::Identifier => {
                 if self.matches(SyntaxKind::LeftBracket) {
                     loop {
                         self.type_();
+                        self.recover(&[SyntaxKind::Comma, SyntaxKind::RightBracket], false);
                         if !self.matches(SyntaxKind::Comma) {
                             break;
                         }
@@ -352,6 +450,7 @@ impl<'p> Parser<'p> {
                 if !self.check(SyntaxKind::RightParen) {
                     loop {
                         self.type_();
+                        self.recover(&[SyntaxKind::Comma, SyntaxKind::RightParen], false);
                         if !self.matches(SyntaxKind::Comma) {
                             break;
                         }
@@ -385,6 +484,25 @@ struct ADTConfig {
     force_extern: bool,
 }
 
+impl ADTConfig {
+    /// Returns all tokens that can start a member of this ADT,
+    /// used to recover from errors inside its body.
+    fn member_start(&self) -> Vec<SyntaxKind> {
+        let mut start = vec![SyntaxKind::RightBrace, SyntaxKind::Func];
+        start.extend_from_slice(&MODIFIERS);
+        if self.has_members {
+            start.extend_from_slice(&[SyntaxKind::Var, SyntaxKind::Val]);
+        }
+        if self.has_constructors {
+            start.push(SyntaxKind::Construct);
+        }
+        if self.has_cases {
+            start.push(SyntaxKind::Identifier);
+        }
+        start
+    }
+}
+
 const CLASS_CONF: ADTConfig = ADTConfig {
     name: "class",
     modifiers: &[SyntaxKind::Extern, SyntaxKind::Value],
@@ -2,6 +2,19 @@ use crate::Parser;
 use error::GErr;
 use syntax::kind::SyntaxKind;
 
+// All tokens that end recovery from an error inside a block,
+// besides the start of a new line.
+static STATEMENT_BOUNDARY: [SyntaxKind; 8] = [
+    SyntaxKind::RightBrace,
+    SyntaxKind::Var,
+    SyntaxKind::Val,
+    SyntaxKind::If,
+    SyntaxKind::For,
+    SyntaxKind::Return,
+    SyntaxKind::Break,
+    SyntaxKind::When,
+];
+
 impl<'p> Parser<'p> {
     /// A 'higher' expression is an expression that is only allowed to appear
     /// as top-level inside a block.
@@ -17,8 +30,16 @@ impl<'p> Parser<'p> {
         self.start_node(SyntaxKind::Variable);
         self.advance(); // Consume 'var' or 'val'
         self.consume(SyntaxKind::Identifier, "variable name", "var/val");
-        self.consume(SyntaxKind::Equal, "'='", "variable name");
-        self.node_with(SyntaxKind::Initializer, Self::expression);
+        if self.matches(SyntaxKind::Colon) {
+            self.type_();
+            // The initializer is optional when the type is given
+            if self.matches(SyntaxKind::Equal) {
+                self.node_with(SyntaxKind::Initializer, Self::expression);
+            }
+        } else {
+            self.consume(SyntaxKind::Equal, "':' or '='", "variable name");
+            self.node_with(SyntaxKind::Initializer, Self::expression);
+        }
         self.end_node();
     }
 
@@ -34,11 +55,12 @@ impl<'p> Parser<'p> {
         }
     }
 
-    fn block(&mut self) {
+    pub fn block(&mut self) {
         self.start_node(SyntaxKind::Block);
         self.advance(); // Consume '{'
         while !self.check(SyntaxKind::RightBrace) && !self.is_at_end() {
             self.higher_expression();
+            self.recover(&STATEMENT_BOUNDARY, true);
         }
         self.consume(SyntaxKind::RightBrace, "'}'", "block");
         self.end_node();
@@ -104,11 +126,12 @@ impl<'p> Parser<'p> {
         self.consume(SyntaxKind::RightParen, "')'", "when value");
         self.consume(SyntaxKind::LeftBrace, "'{'", "when value");
 
+        let outer_body = self.when_body.replace(self.brackets);
         let mut else_branch_found = false;
-        while !self.matches(SyntaxKind::RightBrace) {
+        while !self.matches(SyntaxKind::RightBrace) && !self.is_at_end() {
             if self.matches(SyntaxKind::Else) {
                 if else_branch_found {
-                    self.error_at_current(GErr::E007);
+                    self.error(GErr::E007);
                 }
                 self.consume(SyntaxKind::Arrow, "'->'", "when condition");
                 self.start_node(SyntaxKind::ExprElse);
@@ -116,12 +139,21 @@ impl<'p> Parser<'p> {
                 else_branch_found = true;
             } else {
                 self.start_node(SyntaxKind::WhenBranch);
+                self.matches(SyntaxKind::Is);
                 self.node_with(SyntaxKind::ExprCondition, Self::expression);
+                if self.check(SyntaxKind::Identifier) {
+                    self.advance(); // Binding of the value
+                }
+                if self.matches(SyntaxKind::If) {
+                    self.node_with(SyntaxKind::ExprGuard, Self::expression);
+                }
                 self.consume(SyntaxKind::Arrow, "'->'", "when condition");
                 self.node_with(SyntaxKind::ExprBody, Self::expression);
             }
-            self.end_node()
+            self.end_node();
+            self.recover(&[SyntaxKind::RightBrace, SyntaxKind::Else], true);
         }
+        self.when_body = outer_body;
 
         self.end_node();
     }
@@ -131,7 +163,12 @@ impl<'p> Parser<'p> {
         self.unary();
 
         while let Some((lbp, rbp)) = self.peek().infix_binding_power() {
-            if lbp < minimum_binding_power {
+            // 'is' at the start of a line directly inside a when body begins
+            // the next branch ('is Type binding -> ...') instead of continuing the expression
+            let next_branch = self.check(SyntaxKind::Is)
+                && self.when_body == Some(self.brackets)
+                && self.last_was_newline();
+            if lbp < minimum_binding_power || next_branch {
                 return;
             }
 
@@ -180,7 +217,14 @@ impl<'p> Parser<'p> {
                     self.advance(); // Consume '('
                     if !self.check(SyntaxKind::RightParen) {
                         loop {
-                            self.node_with(SyntaxKind::CallArgument, Self::expression);
+                            self.node_with(SyntaxKind::CallArgument, |this| {
+                                if this.named_argument_ahead() {
+                                    this.advance(); // Consume name
+                                    this.advance(); // Consume ':'
+                                }
+                                this.expression()
+                            });
+                            self.recover(&[SyntaxKind::Comma, SyntaxKind::RightParen], false);
                             if !self.matches(SyntaxKind::Comma) {
                                 break;
                             }
@@ -221,6 +265,29 @@ impl<'p> Parser<'p> {
         }
     }
 
+    // Is the parser looking at a named call argument ('name: value')?
+    // Static access ('Type:name') looks the same; it is written without
+    // whitespace around the colon, which a named argument needs unless
+    // its value does not start with an identifier ('f(a:1)').
+    fn named_argument_ahead(&mut self) -> bool {
+        if !self.check(SyntaxKind::Identifier) {
+            return false;
+        }
+
+        self.source.save();
+        self.source.next();
+        let mut spaced = self.peek_raw().map(|k| k.should_skip()) == Some(true);
+        self.pass_whitespace();
+        let is_named = self.peek_raw() == Some(SyntaxKind::Colon) && {
+            self.source.next();
+            spaced |= self.peek_raw().map(|k| k.should_skip()) == Some(true);
+            self.pass_whitespace();
+            spaced || self.peek_raw() != Some(SyntaxKind::Identifier)
+        };
+        self.source.restore();
+        is_named
+    }
+
     fn primary(&mut self) {
         match self.peek() {
             SyntaxKind::False
@@ -246,6 +313,7 @@ impl<'p> Parser<'p> {
         if self.matches(SyntaxKind::LeftBracket) {
             loop {
                 self.type_();
+                self.recover(&[SyntaxKind::Comma, SyntaxKind::RightBracket], false);
                 if !self.matches(SyntaxKind::Comma) {
                     break;
                 }
@@ -279,7 +347,7 @@ impl<'p> Parser<'p> {
 
     fn closure(&mut self) {
         self.start_node(SyntaxKind::FunctionSignature);
-        self.func_parameters();
+        self.func_parameters(false);
         if self.matches(SyntaxKind::Colon) {
             self.type_()
         }
@@ -0,0 +1,353 @@
+//! Incremental reparsing: Applying an edit to a previous parse result
+//! by only re-lexing the tokens around the edit and only reparsing
+//! the smallest block or declaration containing them.
+
+use crate::{
+    parse, util::builder::NodeBuilder, Lexeme, Node, NodeOrToken, NodeVec, ParseResult, Parser,
+};
+use error::{Error, ErrorSpan};
+use lexer::Lexer;
+use smol_str::SmolStr;
+use std::{cmp::Ordering, ops::Range, rc::Rc};
+use syntax::kind::SyntaxKind;
+
+/// A change to source code: `range` of the previous source is replaced with `text`.
+#[derive(Debug, Clone)]
+pub struct TextEdit {
+    pub range: Range<usize>,
+    pub text: String,
+}
+
+impl TextEdit {
+    /// Returns if the range of the edit is inside of the source,
+    /// not reversed and does not split any character.
+    fn fits(&self, src: &str) -> bool {
+        self.range.start <= self.range.end
+            && src.is_char_boundary(self.range.start)
+            && src.is_char_boundary(self.range.end)
+    }
+}
+
+/// Applies the edit to the source `previous` was parsed from and parses it again.
+/// The result is always identical to [parse] on the edited source; nodes the edit
+/// does not affect are reused, if the edit can be contained in a block or a
+/// top-level declaration. The previous tree is otherwise parsed again entirely.
+/// Edits with a range that does not fit the source are ignored.
+///
+/// Spans of nodes are absolute, so nodes after an edit that changes the length of the
+/// source are moved into new nodes; they keep their tokens and are not reparsed.
+pub fn reparse(previous: ParseResult, edit: &TextEdit) -> ParseResult {
+    let mut src = previous.green_node.text();
+    if !edit.fits(&src) {
+        return parse(&src);
+    }
+    src.replace_range(edit.range.clone(), &edit.text);
+
+    let old = tokens(&previous.green_node);
+    let relexed = match relex(&old, &src, edit) {
+        Some(relexed) => relexed,
+        None => return parse(&src),
+    };
+
+    // All nodes containing the changed tokens, with their parent and index in it
+    let mut path = Vec::new();
+    let mut parent = previous.green_node.clone();
+    while let Some((index, node)) = enclosing_child(&parent, &relexed.changed) {
+        path.push((parent, index, node.clone()));
+        parent = node;
+    }
+
+    // Try the innermost candidate first; the parse of a node only depends
+    // on its own tokens, so any candidate that parses cleanly is correct
+    while let Some((parent, index, node)) = path.pop() {
+        let is_declaration = path.is_empty() && DECLARATIONS.contains(&node.kind());
+        if node.kind() != SyntaxKind::Block && !is_declaration {
+            continue;
+        }
+        // The previous parse of the node must not have depended on the code after it
+        // either; that code was parsed in a different state otherwise, like after an error
+        if parse_alone(&node, &previous_lexemes(&node, &old)).is_none() {
+            continue;
+        }
+
+        let lexemes = edited_lexemes(&node, &old, &relexed, &src);
+        if let Some((new_node, errors)) = parse_alone(&node, &lexemes) {
+            let delta = relexed.delta;
+            let mut root = replace_child(&parent, index, new_node, delta);
+            while let Some((ancestor, index, _)) = path.pop() {
+                root = replace_child(&ancestor, index, root, delta);
+            }
+            return ParseResult {
+                green_node: root,
+                errors: splice_errors(previous.errors, errors, node.text_range(), delta),
+            };
+        }
+    }
+
+    parse(&src)
+}
+
+/// All top-level declarations, which can be reparsed on their own.
+static DECLARATIONS: [SyntaxKind; 4] = [
+    SyntaxKind::FunctionDecl,
+    SyntaxKind::AdtDecl,
+    SyntaxKind::ImportDecl,
+    SyntaxKind::ImplDecl,
+];
+
+/// A token of a previous tree, along with its byte offset.
+struct OldToken {
+    start: usize,
+    kind: SyntaxKind,
+    text: SmolStr,
+}
+
+/// Returns all tokens of the tree, in order.
+fn tokens(root: &Node) -> Vec<OldToken> {
+    fn collect(node: &Node, start: &mut usize, out: &mut Vec<OldToken>) {
+        for child in node.children_with_tokens() {
+            match child {
+                NodeOrToken::Node(node) => collect(&node, start, out),
+                NodeOrToken::Token(token) => {
+                    let text = token.text().clone();
+                    let len = text.len();
+                    out.push(OldToken {
+                        start: *start,
+                        kind: token.kind(),
+                        text,
+                    });
+                    *start += len;
+                }
+            }
+        }
+    }
+
+    let mut out = Vec::new();
+    collect(root, &mut 0, &mut out);
+    out
+}
+
+/// The tokens of the edited source that differ from the previous tokens.
+struct Relexed {
+    /// The range of previous tokens that were replaced, as byte offsets
+    /// into the previous source.
+    changed: Range<usize>,
+    /// The same range as indices into the previous tokens.
+    changed_tokens: Range<usize>,
+    /// The new tokens replacing them, with their offsets in the edited source.
+    tokens: Vec<(SyntaxKind, usize, usize)>,
+    /// The difference in length between the edited and previous source.
+    delta: isize,
+}
+
+/// Lexes the edited source, starting shortly before the edit and stopping as soon as
+/// the tokens line up with the previous ones again; all later tokens are unchanged
+/// since lexing only depends on the source following the start of a token.
+/// Returns `None` if the edit does not line up with the previous source at all.
+fn relex(old: &[OldToken], src: &str, edit: &TextEdit) -> Option<Relexed> {
+    let old_len = old.last().map_or(0, |tok| tok.start + tok.text.len());
+    if edit.range.end > old_len || edit.range.start > edit.range.end {
+        return None;
+    }
+    let delta = edit.text.len() as isize - edit.range.len() as isize;
+    let edit_end = edit.range.start + edit.text.len();
+
+    // A token ending right before the edit might now continue into it, and the
+    // lexer might have looked past the end of the token before that one to decide
+    // where it ends ('1.' followed by a digit is a float)
+    let touching = old
+        .iter()
+        .position(|tok| tok.start + tok.text.len() >= edit.range.start)
+        .unwrap_or(old.len());
+    let mut first = touching.saturating_sub(1);
+    // An unterminated string is an error token consisting only of the quote.
+    // It is a string once another quote appears anywhere after it
+    if let Some(quote) = old[..first]
+        .iter()
+        .position(|tok| tok.kind == SyntaxKind::Error && tok.text == "\"")
+    {
+        first = quote;
+    }
+    let start = old.get(first).map_or(0, |tok| tok.start);
+
+    let mut tokens = Vec::new();
+    let mut lexer = Lexer::new(&src[start..]);
+    while let Some((tok, lexeme)) = lexer.next() {
+        let tok_start = start + lexer.span().start;
+        if tok_start >= edit_end {
+            let old_start = (tok_start as isize - delta) as usize;
+            if let Ok(index) = old.binary_search_by_key(&old_start, |tok| tok.start) {
+                return Some(Relexed {
+                    changed: start..old_start,
+                    changed_tokens: first..index,
+                    tokens,
+                    delta,
+                });
+            }
+        }
+        tokens.push((tok.into(), tok_start, lexeme.len()));
+    }
+
+    Some(Relexed {
+        changed: start..old_len,
+        changed_tokens: first..old.len(),
+        tokens,
+        delta,
+    })
+}
+
+/// Returns the child node of `node` that contains all changed tokens,
+/// excluding its first and last token which must be unchanged.
+fn enclosing_child(node: &Node, changed: &Range<usize>) -> Option<(usize, Node)> {
+    node.children_with_tokens()
+        .enumerate()
+        .find_map(|(index, child)| {
+            let child = child.into_node()?;
+            let span = child.text_range();
+            if (span.start as usize) < changed.start && changed.end < span.end as usize {
+                Some((index, child))
+            } else {
+                None
+            }
+        })
+}
+
+/// Returns the previous tokens of the node.
+fn previous_lexemes<'t>(node: &Node, old: &'t [OldToken]) -> Vec<Lexeme<'t>> {
+    let span = node.text_range();
+    old.iter()
+        .skip_while(|tok| tok.start < span.start as usize)
+        .take_while(|tok| tok.start < span.end as usize)
+        .map(|tok| Lexeme {
+            kind: tok.kind,
+            lexeme: &tok.text,
+            start: tok.start,
+        })
+        .collect()
+}
+
+/// Returns the tokens of the node in the edited source.
+fn edited_lexemes<'t>(
+    node: &Node,
+    old: &[OldToken],
+    relexed: &Relexed,
+    src: &'t str,
+) -> Vec<Lexeme<'t>> {
+    let span = node.text_range();
+    let (start, end) = (span.start as usize, span.end as usize);
+    let shift = |pos: usize| (pos as isize + relexed.delta) as usize;
+
+    let before = old[..relexed.changed_tokens.start]
+        .iter()
+        .skip_while(|tok| tok.start < start)
+        .map(|tok| (tok.kind, tok.start, tok.text.len()));
+    let after = old[relexed.changed_tokens.end..]
+        .iter()
+        .take_while(|tok| tok.start < end)
+        .map(|tok| (tok.kind, shift(tok.start), tok.text.len()));
+    before
+        .chain(relexed.tokens.iter().copied())
+        .chain(after)
+        .map(|(kind, start, len)| Lexeme {
+            kind,
+            lexeme: &src[start..start + len],
+            start,
+        })
+        .collect()
+}
+
+/// Parses the tokens of a block or declaration on its own, as the given node
+/// was parsed. Returns `None` if the result might differ from parsing the whole
+/// source: if the parser did not consume all tokens, if it looked past them,
+/// or if it ended with an error it did not recover from.
+fn parse_alone(node: &Node, lexemes: &[Lexeme]) -> Option<(Node, Vec<Error>)> {
+    let start = node.text_range().start;
+    let mut parser = Parser::new(lexemes);
+    parser.builder = NodeBuilder::starting_at(start);
+    if node.kind() == SyntaxKind::Block {
+        parser.block();
+    } else {
+        parser.declaration();
+    }
+    if parser.poisoned || !parser.source.is_exhausted() || parser.source.reached_end() {
+        return None;
+    }
+
+    // Errors at the first token are assumed to be from the code before the node
+    let at_start = |err: &Error| matches!(&err.index, ErrorSpan::Span(s) if s.start == start);
+    if parser.errors.iter().any(at_start) {
+        return None;
+    }
+
+    let root = parser.builder.finish();
+    let mut children = root.children_with_tokens();
+    match (children.next(), children.next()) {
+        (Some(NodeOrToken::Node(node)), None) => Some((node, parser.errors)),
+        _ => None,
+    }
+}
+
+/// Returns `parent` with the child at `index` replaced, moving all
+/// children after it by `delta`.
+fn replace_child(parent: &Node, index: usize, replacement: Node, delta: isize) -> Node {
+    let mut replacement = Some(replacement);
+    let children = parent
+        .children_with_tokens()
+        .enumerate()
+        .map(|(i, child)| match i.cmp(&index) {
+            Ordering::Less => child,
+            Ordering::Equal => NodeOrToken::Node(replacement.take().unwrap()),
+            Ordering::Greater => moved(child, delta),
+        })
+        .collect::<NodeVec>();
+
+    let span = parent.text_range();
+    let end = (span.end as isize + delta) as u32;
+    Node::new(Rc::new(children), parent.kind(), span.start..end)
+}
+
+/// Moves the node by `delta` bytes, which requires new nodes
+/// for the entire subtree as long as it is not 0.
+fn moved(child: NodeOrToken, delta: isize) -> NodeOrToken {
+    match child {
+        NodeOrToken::Node(node) if delta != 0 => {
+            let children = node
+                .children_with_tokens()
+                .map(|child| moved(child, delta))
+                .collect::<NodeVec>();
+            let span = node.text_range();
+            let span = (span.start as isize + delta) as u32..(span.end as isize + delta) as u32;
+            NodeOrToken::Node(Node::new(Rc::new(children), node.kind(), span))
+        }
+        _ => child,
+    }
+}
+
+/// Replaces the errors inside the reparsed node with its new errors,
+/// moving all errors after it by `delta`. Errors are ordered by
+/// their position, since the parser never goes back.
+/// Errors at the first token of the node are from the code before
+/// it, which ran into the node when it expected something else.
+fn splice_errors(
+    previous: Vec<Error>,
+    reparsed: Vec<Error>,
+    node: Range<u32>,
+    delta: isize,
+) -> Vec<Error> {
+    let mut before = Vec::new();
+    let mut after = Vec::new();
+    for mut error in previous {
+        match &mut error.index {
+            ErrorSpan::Span(span) if span.start >= node.end => {
+                span.start = (span.start as isize + delta) as u32;
+                span.end = (span.end as isize + delta) as u32;
+                after.push(error);
+            }
+            ErrorSpan::Span(span) if span.start > node.start => (),
+            _ => before.push(error),
+        }
+    }
+    before.extend(reparsed);
+    before.extend(after);
+    before
+}
@@ -1,5 +1,6 @@
 mod declaration;
 mod expression;
+mod incremental;
 mod nodes;
 mod util;
 
@@ -8,19 +9,51 @@ use crate::util::{
     source::Source,
 };
 use common::bench;
-use error::{Error, ErrorSpan, GErr};
+use error::{Error, ErrorSpan, GErr, Severity};
+pub use incremental::{reparse, TextEdit};
 use lexer::Lexer;
 pub use nodes::*;
 use syntax::kind::SyntaxKind;
 
-pub fn parse(input: &str) -> Result<ParseResult, Vec<Error>> {
-    let lexer = Lexer::new(input);
-    let lexemes = lexer
-        .map(|(tok, lexeme)| Lexeme {
+/// Returns the closing bracket of the given opening bracket, if it is one.
+fn closing_bracket(kind: SyntaxKind) -> Option<SyntaxKind> {
+    match kind {
+        SyntaxKind::LeftParen => Some(SyntaxKind::RightParen),
+        SyntaxKind::LeftBracket => Some(SyntaxKind::RightBracket),
+        SyntaxKind::LeftBrace => Some(SyntaxKind::RightBrace),
+        _ => None,
+    }
+}
+
+/// All tokens that start a top-level declaration.
+/// Recovery from errors always stops at these.
+static DECLARATION_START: [SyntaxKind; 7] = [
+    SyntaxKind::Enum,
+    SyntaxKind::Class,
+    SyntaxKind::Func,
+    SyntaxKind::Import,
+    SyntaxKind::Export,
+    SyntaxKind::Impl,
+    SyntaxKind::Interface,
+];
+
+/// Parses the given source. This always produces a tree, even if the source
+/// contains syntax errors; tokens the parser had to skip are wrapped in
+/// [SyntaxKind::ErrorNode] and the errors are part of the result.
+///
+/// The tree is lossless: whitespace and comments are kept as tokens
+/// inside the node that was open when the parser passed them, so
+/// [Node::text] of the root reproduces `input` exactly.
+pub fn parse(input: &str) -> ParseResult {
+    let mut lexer = Lexer::new(input);
+    let mut lexemes = Vec::new();
+    while let Some((tok, lexeme)) = lexer.next() {
+        lexemes.push(Lexeme {
             kind: tok.into(),
             lexeme,
-        })
-        .collect::<Vec<_>>();
+            start: lexer.span().start,
+        });
+    }
     let parser = Parser::new(&lexemes);
     parser.parse()
 }
@@ -29,6 +62,8 @@ pub fn parse(input: &str) -> Result<ParseResult, Vec<Error>> {
 struct Lexeme<'t> {
     kind: SyntaxKind,
     lexeme: &'t str,
+    /// Byte offset of the lexeme in the source.
+    start: usize,
 }
 
 struct Parser<'p> {
@@ -41,28 +76,40 @@ struct Parser<'p> {
     /// A list of all errors encountered during parsing.
     errors: Vec<Error>,
     poisoned: bool,
+    /// The amount of braces consumed that were not closed yet.
+    braces: usize,
+    /// The amount of brackets of any kind consumed that were not closed yet.
+    brackets: usize,
+    /// The value of `brackets` inside the body of the innermost `when`
+    /// being parsed, where a line starting with 'is' begins the next branch.
+    when_body: Option<usize>,
 
     /// Stores the modifiers of the current global declaration.
     modifiers: Vec<SyntaxKind>,
 }
 
 impl<'p> Parser<'p> {
-    fn parse(mut self) -> Result<ParseResult, Vec<Error>> {
+    fn parse(mut self) -> ParseResult {
         bench!("parser", {
             while self.peek() != SyntaxKind::EndOfFile {
+                // Braces left open by a declaration with errors do not carry over
+                self.braces = 0;
                 self.declaration();
+                self.recover(&DECLARATION_START, false);
+                // Recovery stops at closing braces of the declaration that were
+                // not consumed due to an error; there is no outer construct here
                 if self.poisoned {
-                    self.try_depoison();
+                    self.poisoned = false;
+                    if !self.is_at_end() {
+                        self.skip_erroneous();
+                    }
                 }
             }
         });
 
-        if self.errors.is_empty() {
-            Ok(ParseResult {
-                green_node: self.builder.finish(),
-            })
-        } else {
-            Err(self.errors)
+        ParseResult {
+            green_node: self.builder.finish(),
+            errors: self.errors,
         }
     }
 
@@ -81,7 +128,9 @@ impl<'p> Parser<'p> {
     }
 
     fn consume(&mut self, kind: SyntaxKind, want: &'static str, after: &'static str) {
-        if self.advance_checked() != kind {
+        if self.check(kind) {
+            self.advance();
+        } else {
             self.error_at_current(GErr::E001 { want, after });
         }
     }
@@ -100,35 +149,96 @@ impl<'p> Parser<'p> {
         }
     }
 
+    /// Reports an error at the current token and poisons the parser.
+    /// While poisoned, the parser acts as if it reached the end of the file,
+    /// which unwinds it to the next place that calls `recover`.
     fn error_at_current(&mut self, err: GErr) {
+        if !self.poisoned {
+            self.error(err);
+            self.poisoned = true;
+        }
+    }
+
+    /// Reports an error at the current token without poisoning the parser,
+    /// for errors that do not affect the structure of the code.
+    fn error(&mut self, err: GErr) {
         if self.poisoned {
-            self.advance_checked();
             return;
         }
 
         let err = Error {
-            index: ErrorSpan::Token(self.source.position()),
+            index: ErrorSpan::Span(self.source.span()),
             kind: err,
+            severity: Severity::Error,
+            details: Box::default(),
         };
         self.errors.push(err);
-        self.poisoned = true;
     }
 
-    fn try_depoison(&mut self) {
-        let recoverable = &[
-            SyntaxKind::Enum,
-            SyntaxKind::Class,
-            SyntaxKind::Func,
-            SyntaxKind::Import,
-            SyntaxKind::Export,
-            SyntaxKind::Impl,
-            SyntaxKind::Interface,
-            SyntaxKind::EndOfFile,
-        ];
-        while !recoverable.contains(&self.peek()) {
-            self.advance_checked();
+    /// Recovers from an error by skipping tokens until one of `until`
+    /// is reached, then unpoisons the parser. Skipped tokens are put into
+    /// an `ErrorNode`. Brackets opened while skipping are skipped as a whole.
+    /// If `at_newline` is set, the start of a new line also ends recovery.
+    ///
+    /// Stops without unpoisoning at tokens that belong to an outer construct:
+    /// the start of a declaration or a closing brace not in `until`.
+    /// The construct containing it then recovers instead. Closing braces
+    /// that do not close any brace are skipped, as no construct contains them.
+    fn recover(&mut self, until: &[SyntaxKind], at_newline: bool) {
+        if !self.poisoned {
+            return;
+        }
+
+        let checkpoint = self.checkpoint();
+        let mut skipped = false;
+        // Closing brackets of the brackets opened while skipping
+        let mut open = Vec::new();
+        while let Some(Lexeme { kind, lexeme, .. }) = self.source.get_current() {
+            // Closing brackets that were not opened while skipping belong to outer code
+            let is_closing = matches!(
+                kind,
+                SyntaxKind::RightParen | SyntaxKind::RightBracket | SyntaxKind::RightBrace
+            );
+            let outer = is_closing && !open.contains(&kind);
+            if DECLARATION_START.contains(&kind)
+                || ((open.is_empty() || outer) && until.contains(&kind))
+            {
+                self.poisoned = !until.contains(&kind);
+                break;
+            }
+            if open.is_empty()
+                && at_newline
+                && kind == SyntaxKind::Whitespace
+                && lexeme.contains('\n')
+            {
+                self.poisoned = false;
+                break;
+            }
+
+            if let Some(closing) = closing_bracket(kind) {
+                open.push(closing);
+            } else if is_closing && !outer {
+                while open.pop() != Some(kind) {}
+            } else if outer && kind == SyntaxKind::RightBrace && self.braces > 0 {
+                break;
+            }
+            skipped |= !kind.should_skip();
+            self.advance_inner();
+        }
+
+        if skipped {
+            self.builder
+                .start_node_at(SyntaxKind::ErrorNode, checkpoint);
+            self.end_node();
         }
-        self.poisoned = false;
+    }
+
+    /// Puts the current token into an `ErrorNode`. Used by lists of items
+    /// to ensure progress when an item could not consume any tokens.
+    fn skip_erroneous(&mut self) {
+        self.node_with(SyntaxKind::ErrorNode, |this| {
+            this.advance_inner();
+        });
     }
 
     /// Is the current token the given kind?
@@ -141,39 +251,64 @@ impl<'p> Parser<'p> {
         self.peek_next() == kind
     }
 
+    /// Consumes the current token. Returns an `EndOfFile` lexeme
+    /// without consuming anything at the end or while poisoned.
     fn advance(&mut self) -> Lexeme<'p> {
         self.skip_whitespace();
-        self.advance_inner()
+        if self.is_at_end() {
+            Lexeme {
+                kind: SyntaxKind::EndOfFile,
+                lexeme: "",
+                start: 0,
+            }
+        } else {
+            self.advance_inner()
+        }
     }
 
     fn advance_inner(&mut self) -> Lexeme<'p> {
-        let Lexeme { kind, lexeme } = self.source.get_current().unwrap();
+        let current = self.source.get_current().unwrap();
         self.source.next();
+        match current.kind {
+            SyntaxKind::LeftParen | SyntaxKind::LeftBracket => self.brackets += 1,
+            SyntaxKind::LeftBrace => {
+                self.braces += 1;
+                self.brackets += 1;
+            }
+            SyntaxKind::RightParen | SyntaxKind::RightBracket => {
+                self.brackets = self.brackets.saturating_sub(1)
+            }
+            SyntaxKind::RightBrace => {
+                self.braces = self.braces.saturating_sub(1);
+                self.brackets = self.brackets.saturating_sub(1);
+            }
+            _ => (),
+        }
 
-        self.builder.token(kind, lexeme.into());
-        Lexeme { kind, lexeme }
+        self.builder.token(current.kind, current.lexeme.into());
+        current
     }
 
     fn advance_checked(&mut self) -> SyntaxKind {
-        if self.is_at_end() {
-            SyntaxKind::EndOfFile
-        } else {
-            self.advance().kind
-        }
+        self.advance().kind
     }
 
     fn peek(&mut self) -> SyntaxKind {
+        if self.poisoned {
+            return SyntaxKind::EndOfFile;
+        }
         self.skip_whitespace();
         self.peek_raw().unwrap_or(SyntaxKind::EndOfFile)
     }
 
     fn peek_next(&mut self) -> SyntaxKind {
+        if self.poisoned {
+            return SyntaxKind::EndOfFile;
+        }
         self.source.save();
-        self.skip_whitespace();
+        self.pass_whitespace();
         self.source.next();
-        while self.peek_raw().map(|k| k.should_skip()) == Some(true) {
-            self.source.next();
-        }
+        self.pass_whitespace();
         let ret = self.peek_raw().unwrap_or(SyntaxKind::EndOfFile);
         self.source.restore();
         ret
@@ -183,18 +318,37 @@ impl<'p> Parser<'p> {
         self.source.get_last().kind.should_skip()
     }
 
+    /// Is the current token at the start of a new line?
+    fn last_was_newline(&mut self) -> bool {
+        let last = self.source.get_last();
+        last.kind.should_skip() && last.lexeme.contains('\n')
+    }
+
     fn peek_raw(&self) -> Option<SyntaxKind> {
         self.source.get_current().map(|Lexeme { kind, .. }| kind)
     }
 
     fn skip_whitespace(&mut self) {
+        // Whitespace is left for recovery, which might stop at a newline
+        if self.poisoned {
+            return;
+        }
         while self.peek_raw().map(|k| k.should_skip()) == Some(true) {
             self.advance_inner();
         }
     }
 
+    /// Moves past whitespace without putting it into the tree.
+    /// Only for lookahead that restores the source afterwards,
+    /// since the tree would otherwise lose the whitespace.
+    fn pass_whitespace(&mut self) {
+        while self.peek_raw().map(|k| k.should_skip()) == Some(true) {
+            self.source.next();
+        }
+    }
+
     fn is_at_end(&self) -> bool {
-        self.source.get_current().is_none()
+        self.poisoned || self.source.get_current().is_none()
     }
 
     fn node_with<T: FnOnce(&mut Self)>(&mut self, kind: SyntaxKind, content: T) {
@@ -227,6 +381,9 @@ impl<'p> Parser<'p> {
             builder: NodeBuilder::new(),
             errors: vec![],
             poisoned: false,
+            braces: 0,
+            brackets: 0,
+            when_body: None,
             modifiers: Vec::with_capacity(4),
         }
     }
@@ -235,10 +392,36 @@ impl<'p> Parser<'p> {
 #[derive(Debug)]
 pub struct ParseResult {
     green_node: Node,
+    errors: Vec<Error>,
 }
 
 impl ParseResult {
     pub fn root(self) -> Node {
         self.green_node
     }
+
+    /// The tree, without giving up the result; to keep
+    /// it for [reparse] while also using the tree.
+    pub fn tree(&self) -> &Node {
+        &self.green_node
+    }
+
+    /// All syntax errors, in the order they were encountered.
+    pub fn errors(&self) -> &[Error] {
+        &self.errors
+    }
+
+    pub fn has_errors(&self) -> bool {
+        !self.errors.is_empty()
+    }
+
+    pub fn into_errors(self) -> Vec<Error> {
+        self.errors
+    }
+
+    /// The tree and the syntax errors, for tools that use the
+    /// tree even if the source contains errors.
+    pub fn into_parts(self) -> (Node, Vec<Error>) {
+        (self.green_node, self.errors)
+    }
 }
@@ -45,6 +45,13 @@ impl NodeOrToken {
         }
     }
 
+    fn text_len(&self) -> u32 {
+        match self {
+            NodeOrToken::Node(n) => n.span.end - n.span.start,
+            NodeOrToken::Token(t) => t.text.len() as u32,
+        }
+    }
+
     fn debug_fmt(&self, f: &mut Formatter<'_>, indent_size: usize) -> fmt::Result {
         let indent = iter::repeat(' ').take(indent_size).collect::<String>();
         write!(f, "{}{:?}", indent, self.kind())?;
@@ -119,6 +126,82 @@ impl Node {
         self.span.clone()
     }
 
+    /// The range of the first direct child token of the given kind, if any.
+    /// Unlike [text_range], this does not include trivia following the token.
+    pub fn token_range(&self, kind: SyntaxKind) -> Option<Range<u32>> {
+        let mut start = self.span.start;
+        for child in self.children.iter() {
+            let len = match child {
+                NodeOrToken::Node(node) => node.span.end - node.span.start,
+                NodeOrToken::Token(token) if token.kind == kind => {
+                    return Some(start..start + token.text.len() as u32)
+                }
+                NodeOrToken::Token(token) => token.text.len() as u32,
+            };
+            start += len;
+        }
+        None
+    }
+
+    /// The source text of this node, including all trivia inside it.
+    /// The parser puts every token into the tree, so the text of
+    /// the root node is exactly the source code that was parsed.
+    pub fn text(&self) -> String {
+        let mut text = String::with_capacity((self.span.end - self.span.start) as usize);
+        self.write_text(&mut text);
+        text
+    }
+
+    fn write_text(&self, out: &mut String) {
+        for child in self.children.iter() {
+            match child {
+                NodeOrToken::Node(node) => node.write_text(out),
+                NodeOrToken::Token(token) => out.push_str(token.text()),
+            }
+        }
+    }
+
+    /// Returns this node with some of its descendants replaced, given as pairs of
+    /// the old and the new node. Everything after a replaced node is moved by the
+    /// difference in length, keeping all spans consistent with the text of the
+    /// new tree. Old nodes that are not part of this node are ignored.
+    pub fn replace(&self, replacements: &[(Node, Node)]) -> Node {
+        if replacements.is_empty() {
+            return self.clone();
+        }
+        self.replace_at(self.span.start, replacements)
+    }
+
+    fn replace_at(&self, start: u32, replacements: &[(Node, Node)]) -> Node {
+        let contains_replaced = replacements
+            .iter()
+            .any(|(old, _)| self.span.start <= old.span.start && old.span.end <= self.span.end);
+        if !contains_replaced && start == self.span.start {
+            return self.clone();
+        }
+
+        let mut end = start;
+        let children = self
+            .children
+            .iter()
+            .map(|child| {
+                let child = match child {
+                    NodeOrToken::Node(node) => {
+                        let node = match replacements.iter().find(|(old, _)| old == node) {
+                            Some((_, new)) => new.replace_at(end, &[]),
+                            None => node.replace_at(end, replacements),
+                        };
+                        NodeOrToken::Node(node)
+                    }
+                    NodeOrToken::Token(_) => child.clone(),
+                };
+                end += child.text_len();
+                child
+            })
+            .collect::<NodeVec>();
+        Node::new(Rc::new(children), self.kind, start..end)
+    }
+
     pub fn dummy() -> Self {
         Self {
             children: Rc::new(NodeVec::new()),
@@ -127,6 +210,16 @@ impl Node {
         }
     }
 
+    /// An empty node standing in for a child missing from `parent`
+    /// due to a syntax error. It is placed at the end of its parent.
+    pub fn missing(parent: &Node) -> Self {
+        Self {
+            children: Rc::new(NodeVec::new()),
+            kind: SyntaxKind::ErrorNode,
+            span: parent.span.end..parent.span.end,
+        }
+    }
+
     pub fn new(children: Rc<NodeVec>, kind: SyntaxKind, span: Range<u32>) -> Self {
         Self {
             children,
@@ -82,12 +82,17 @@ impl NodeBuilder {
     }
 
     pub fn new() -> Self {
+        Self::starting_at(0)
+    }
+
+    /// Creates a builder for a tree of source starting at the given offset.
+    pub fn starting_at(offset: u32) -> Self {
         Self {
             nodes: vec![WorkNode {
                 children: SmallVec::new(),
                 kind: SyntaxKind::Root,
-                start: 0,
-                end: 0,
+                start: offset,
+                end: offset,
             }],
         }
     }
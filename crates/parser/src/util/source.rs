@@ -1,14 +1,31 @@
 use crate::Lexeme;
+use std::{cell::Cell, ops::Range};
 
 pub(crate) struct Source<'s> {
     lexemes: &'s [Lexeme<'s>],
     current: usize,
     saved: usize,
+    /// If the parser ever looked for a lexeme past the last one.
+    /// Used by incremental reparsing to know if the end of the
+    /// lexemes influenced the parse.
+    reached_end: Cell<bool>,
 }
 
 impl<'s> Source<'s> {
     pub fn get_current(&self) -> Option<Lexeme<'s>> {
-        self.lexemes.get(self.current).copied()
+        let current = self.lexemes.get(self.current).copied();
+        if current.is_none() {
+            self.reached_end.set(true);
+        }
+        current
+    }
+
+    pub fn reached_end(&self) -> bool {
+        self.reached_end.get()
+    }
+
+    pub fn is_exhausted(&self) -> bool {
+        self.current == self.lexemes.len()
     }
 
     pub fn get_last(&self) -> Lexeme<'s> {
@@ -19,6 +36,22 @@ impl<'s> Source<'s> {
         self.current
     }
 
+    /// Returns the byte span of the current lexeme,
+    /// or an empty span at the end of the source if there is none.
+    pub fn span(&self) -> Range<u32> {
+        let (start, end) = match self.get_current() {
+            Some(lex) => (lex.start, lex.start + lex.lexeme.len()),
+            None => {
+                let end = self
+                    .lexemes
+                    .last()
+                    .map_or(0, |lex| lex.start + lex.lexeme.len());
+                (end, end)
+            }
+        };
+        (start as u32)..(end as u32)
+    }
+
     pub fn next(&mut self) {
         self.current += 1;
     }
@@ -36,6 +69,7 @@ impl<'s> Source<'s> {
             lexemes,
             current: 0,
             saved: 0,
+            reached_end: Cell::new(false),
         }
     }
 }
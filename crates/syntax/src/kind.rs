@@ -10,6 +10,9 @@ pub enum SyntaxKind {
 
     /// An import or export declaration
     ImportDecl,
+    /// A single imported symbol inside an import declaration,
+    /// containing its name and optional alias ('Thing as Other')
+    ImportSymbol,
     /// A top-level function declaration
     FunctionDecl,
     /// A top-level ADT declaration
@@ -39,7 +42,7 @@ pub enum SyntaxKind {
     AdtMember,
     /// The implementor on iface impls
     Implementor,
-    /// The implemented iface on iface impls
+    /// The implemented iface on iface impls; missing on inherent impls
     Implementing,
     /// A case inside of an enum declaration
     EnumCase,
@@ -49,7 +52,7 @@ pub enum SyntaxKind {
 
     /// A variable declaration like 'var x: String = "hello"'
     Variable,
-    /// An initializer of a variable or member
+    /// An initializer of a variable or member, or the default value of a parameter
     Initializer,
     /// A block containing other expressions
     Block,
@@ -66,7 +69,8 @@ pub enum SyntaxKind {
     BreakExpr,
     /// A when expression
     WhenExpr,
-    /// A when branch, containing 1 ExprCondition (missing on else) and 1 ExprBody
+    /// A when branch, containing 1 ExprCondition (missing on else), an optional
+    /// ExprGuard and 1 ExprBody
     WhenBranch,
 
     /// Condition of if, for and when expressions.
@@ -75,6 +79,8 @@ pub enum SyntaxKind {
     ExprBody,
     /// The body of the else branch of if, for and when expressions.
     ExprElse,
+    /// The guard of a when branch ('x if guard -> ...').
+    ExprGuard,
     /// A binary expression like '5 + 5'
     BinaryExpr,
     /// A prefix expression, currently only '!false'
@@ -91,7 +97,7 @@ pub enum SyntaxKind {
     GetStaticExpr,
     /// Callee of a call or get expression
     Callee,
-    /// Argument of a call expression
+    /// Argument of a call expression, optionally named ('name: value')
     CallArgument,
     /// A literal expression, only contains the literal token
     Literal,
@@ -103,6 +109,9 @@ pub enum SyntaxKind {
     /// A type literal like "String", "String?", "(u32, u32): u64"
     Type,
 
+    /// Tokens skipped by the parser while recovering from a syntax error.
+    ErrorNode,
+
     /// This special variant is used for SyntaxKind::is_token.
     __TokenStart,
 
@@ -115,6 +124,8 @@ pub enum SyntaxKind {
     Tilde,
     Comma,
     Dot,
+    DotDot,
+    DotDotEqual,
     Minus,
     Plus,
     Semicolon,
@@ -142,6 +153,7 @@ pub enum SyntaxKind {
     Float,
 
     And,
+    As,
     Break,
     Class,
     Construct,
@@ -170,6 +182,7 @@ pub enum SyntaxKind {
     Extern,
     Value,
     Variadic,
+    Static,
 
     Error,
     Comment,
@@ -189,10 +202,11 @@ impl SyntaxKind {
             Self::Or => (10, 9),
             Self::And => (12, 11),
             Self::BangEqual | Self::EqualEqual => (14, 13),
-            Self::Less | Self::LessEqual | Self::Greater | Self::GreaterEqual => (16, 15),
-            Self::Plus | Self::Minus => (16, 15),
-            Self::Star | Self::Slash => (18, 17),
-            Self::Is => (20, 19),
+            Self::DotDot | Self::DotDotEqual => (16, 15),
+            Self::Less | Self::LessEqual | Self::Greater | Self::GreaterEqual => (18, 17),
+            Self::Plus | Self::Minus => (18, 17),
+            Self::Star | Self::Slash => (20, 19),
+            Self::Is => (22, 21),
             _ => return None,
         })
     }